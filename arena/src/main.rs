@@ -12,9 +12,12 @@ use musolver::{
         arena::{
             ActionRecorder, Agent, AgenteAleatorio, AgenteMusolver, Kibitzer, MusAction, MusArena,
         },
-        Accion, CuatroJugadores, DosJugadores, FaseEnvites, Juego, Lance, Mano,
+        Accion, CuatroJugadores, DosJugadores, FaseEnvites, Juego, Lance, Mano, Variante,
+    },
+    solver::{
+        BetAbstraction, Fallback, GameType, LanceGame, LanceGameTwoHands, MusGameTwoPlayers,
+        SolverError, Strategy, StrategyConfig,
     },
-    solver::{GameType, LanceGame, MusGameTwoPlayers, SolverError, Strategy, StrategyConfig},
     Game,
 };
 
@@ -156,12 +159,29 @@ impl Kibitzer<CuatroJugadores> for KibitzerCli {
 pub struct AgenteCli {
     history: Arc<Mutex<Vec<Accion>>>,
     game_type: GameType,
+    bet_abstraction: Arc<BetAbstraction>,
+    variante: Variante,
 }
 
 impl AgenteCli {
-    pub fn new(game_type: GameType, history: Arc<Mutex<Vec<Accion>>>) -> Self {
-        Self { game_type, history }
+    pub fn new(
+        game_type: GameType,
+        bet_abstraction: Arc<BetAbstraction>,
+        history: Arc<Mutex<Vec<Accion>>>,
+    ) -> Self {
+        Self {
+            game_type,
+            bet_abstraction,
+            history,
+            variante: Variante::default(),
+        }
+    }
+
+    /// Variante de reglas de la partida, necesaria para interpretar las manos.
+    pub fn with_variante(self, variante: Variante) -> Self {
+        Self { variante, ..self }
     }
+
     fn get_actions<G>(&self, game: &mut G, history: &[Accion]) -> Vec<Accion>
     where
         G: Game<Action = Accion>,
@@ -207,10 +227,22 @@ impl Agent<CuatroJugadores> for AgenteCli {
     async fn actuar(&mut self, partida_mus: &FaseEnvites<CuatroJugadores>) -> Accion {
         let next_actions = match self.game_type {
             GameType::LanceGame(_) => {
-                let mut game = LanceGame::from_partida_mus(partida_mus, true).unwrap();
+                let mut game = LanceGame::from_partida_mus(partida_mus, true)
+                    .unwrap()
+                    .with_bet_abstraction(self.bet_abstraction.clone());
                 self.get_actions(&mut game, &self.history.lock().unwrap())
             }
-            GameType::LanceGameTwoHands(_) => todo!(),
+            GameType::LanceGameTwoHands(lance) => {
+                let mut game = LanceGameTwoHands::new(lance, *partida_mus.tantos(), false)
+                    .with_bet_abstraction(self.bet_abstraction.clone())
+                    .with_max_tantos(partida_mus.max_tantos())
+                    .with_variante(self.variante)
+                    .with_hands(partida_mus.manos().clone());
+                for action in self.history.lock().unwrap().iter() {
+                    game.act_jugador(*action);
+                }
+                game.actions()
+            }
             GameType::MusGame => todo!(),
             GameType::MusGameTwoHands => todo!(),
             GameType::MusGameTwoPlayers => todo!(),
@@ -224,10 +256,14 @@ impl Agent<DosJugadores> for AgenteCli {
     async fn actuar(&mut self, partida_mus: &FaseEnvites<DosJugadores>) -> Accion {
         let next_actions = match self.game_type {
             GameType::MusGameTwoPlayers => {
-                let mut game = MusGameTwoPlayers::new(*partida_mus.tantos(), false).with_hands([
-                    partida_mus.manos()[0].clone(),
-                    partida_mus.manos()[1].clone(),
-                ]);
+                let mut game = MusGameTwoPlayers::new(*partida_mus.tantos(), false, 0)
+                    .with_bet_abstraction(self.bet_abstraction.clone())
+                    .with_max_tantos(partida_mus.max_tantos())
+                    .with_variante(self.variante)
+                    .with_hands([
+                        partida_mus.manos()[0].clone(),
+                        partida_mus.manos()[1].clone(),
+                    ]);
                 self.get_actions(&mut game, &self.history.lock().unwrap())
             }
             _ => todo!(),
@@ -238,8 +274,22 @@ impl Agent<DosJugadores> for AgenteCli {
 fn show_strategy_data(strategy: &StrategyConfig) {
     println!("\tTipo partida: {:?}", strategy.game_config.game_type);
     println!("\tJuego abstracto: {}", strategy.game_config.abstract_game);
+    println!("\tTantos del chico: {}", strategy.game_config.max_tantos);
+    println!("\tVariante: {:?}", strategy.game_config.variante);
+    if let Some(signals) = &strategy.game_config.signals {
+        println!(
+            "\tSeñas: {:?} (rivales: {})",
+            signals.vocabulary, signals.opponent_probability
+        );
+    }
     println!("\tIteraciones:{:?}", strategy.trainer_config.iterations);
     println!("\tMétodo de cálculo: {:?}", strategy.trainer_config.method);
+    for (tantos, stop_reason) in &strategy.stop_reasons {
+        println!(
+            "\tFin del entrenamiento {}:{}: {stop_reason}",
+            tantos[0], tantos[1]
+        );
+    }
     println!();
 }
 
@@ -291,6 +341,31 @@ struct Args {
 
     #[arg(short, long, num_args = 4, required = true, value_enum)]
     agents: Vec<AgentType>,
+
+    /// Alternativas, en orden, cuando la estrategia no tiene un conjunto de información:
+    /// abstract, tantos:N, uniform o first-action.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "abstract,tantos:2,uniform"
+    )]
+    fallback: Vec<Fallback>,
+}
+
+/// Muestra cuántas decisiones ha tomado el agente con la estrategia y con cada alternativa.
+fn show_fallback_hits(nombre: &str, agente: &AgenteMusolver, fallbacks: &[Fallback]) {
+    let hits = agente.hits();
+    let niveles: Vec<String> = std::iter::once(None)
+        .chain(fallbacks.iter().copied().map(Some))
+        .map(|fallback| {
+            let count = hits.get(&fallback).copied().unwrap_or_default();
+            match fallback {
+                None => format!("estrategia {count}"),
+                Some(fallback) => format!("{fallback:?} {count}"),
+            }
+        })
+        .collect();
+    println!("Decisiones de {nombre}: {}", niveles.join(", "));
 }
 
 fn main() {
@@ -332,15 +407,37 @@ fn main() {
         ),
         None => (GameType::MusGameTwoPlayers, None),
     };
-    let mut arena = MusArena::<CuatroJugadores>::new([0, 0], lance);
+    let max_tantos = strategy
+        .as_ref()
+        .map_or(FaseEnvites::<CuatroJugadores>::MAX_TANTOS, |s| {
+            s.strategy_config.game_config.max_tantos
+        });
+    let variante = strategy.as_ref().map_or_else(Variante::default, |s| {
+        s.strategy_config.game_config.variante
+    });
+    assert!(
+        strategy
+            .as_ref()
+            .is_none_or(|s| s.strategy_config.game_config.signals.is_none()),
+        "La arena no juega las señas: la estrategia debe estar entrenada sin señas."
+    );
+    let mut arena = MusArena::<CuatroJugadores>::new([0, 0], lance)
+        .with_max_tantos(max_tantos)
+        .with_variante(variante);
 
     let action_recorder = ActionRecorder::new();
 
     let agente_aleatorio = AgenteAleatorio::new(action_recorder.history());
-    let agente_cli = AgenteCli::new(game_type, action_recorder.history());
+    // Las acciones del jugador humano deben ser las del árbol con el que se entrenó la estrategia.
+    let bet_abstraction = strategy
+        .as_ref()
+        .map_or_else(Default::default, Strategy::bet_abstraction);
+    let agente_cli = AgenteCli::new(game_type, bet_abstraction, action_recorder.history())
+        .with_variante(variante);
 
     let mut cli_client = 0;
     let mut nombres_jugadores = vec![];
+    let mut agentes_musolver = vec![];
     for (i, agent) in args.agents.iter().enumerate() {
         match agent {
             AgentType::Cli => {
@@ -355,8 +452,10 @@ fn main() {
             AgentType::Musolver => {
                 if let Some(s) = &strategy {
                     let agente_musolver =
-                        AgenteMusolver::new(s.to_owned(), action_recorder.history());
-                    arena.agents.push(Box::new(agente_musolver.clone()))
+                        AgenteMusolver::new(s.to_owned(), action_recorder.history())
+                            .with_fallbacks(args.fallback.clone());
+                    arena.agents.push(Box::new(agente_musolver.clone()));
+                    agentes_musolver.push((format!("Musolver#{i}"), agente_musolver));
                 } else {
                     panic!("Cannot load musolver: strategy not available.");
                 }
@@ -370,7 +469,10 @@ fn main() {
     arena.kibitzers.push(Box::new(kibitzer_cli));
 
     loop {
-        arena.start();
+        futures::executor::block_on(arena.start());
+        for (nombre, agente) in &agentes_musolver {
+            show_fallback_hits(nombre, agente, &args.fallback);
+        }
         println!("Pulsa una tecla para continuar...");
         let _ = io::stdin().read_line(&mut String::new());
     }
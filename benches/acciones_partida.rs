@@ -29,7 +29,7 @@ fn bench_acciones_partida(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let mut baraja = Baraja::baraja_mus();
-                baraja.barajar();
+                baraja.barajar(&mut rand::thread_rng());
                 let manos = baraja.repartir_manos();
                 let partida =
                     FaseEnvites::<CuatroJugadores>::new_partida_lance(Lance::Grande, manos, [0, 0])
@@ -1,5 +1,3 @@
-use std::rc::Rc;
-
 use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
 use musolver::{
     Cfr, CfrMethod,
@@ -127,7 +125,7 @@ fn bench_exploitability(c: &mut Criterion) {
                 cfr.train(&mut game, CfrMethod::FsiCfr, 500, |_, _| {});
                 (cfr, game)
             },
-            |(mut cfr, mut game)| {
+            |(cfr, mut game)| {
                 cfr.exploitability(&mut game);
             },
             BatchSize::SmallInput,
@@ -1,14 +1,15 @@
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use musolver::{
-    mus::{Baraja, Lance},
-    solver::{InfoSet, ManosNormalizadas},
+    Game,
+    mus::{Accion, Baraja, Lance},
+    solver::{InfoSet, ManosNormalizadas, MusGame},
 };
 
 fn bench_info_set_prefix(c: &mut Criterion) {
     let mut group = c.benchmark_group("InfoSetStr");
-    let mut baraja = Baraja::baraja_mus();
     for i in 0..10 {
-        baraja.barajar();
+        let mut baraja = Baraja::baraja_mus();
+        baraja.barajar(&mut rand::thread_rng());
         let manos = baraja.repartir_manos();
         let manos_normalizadas = ManosNormalizadas::normalizar_mano(&manos, &Lance::Grande);
         group.bench_with_input(
@@ -50,5 +51,18 @@ fn bench_info_set_prefix(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_info_set_prefix);
+fn bench_info_set_key(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InfoSetKey");
+    let mut baraja = Baraja::baraja_mus();
+    baraja.barajar(&mut rand::thread_rng());
+    let mut game = MusGame::new([0, 0], false, 1).with_hands(baraja.repartir_manos());
+    for action in [Accion::NoMus, Accion::Paso, Accion::Envido(2)] {
+        game.act(action);
+    }
+    group.bench_function("info_set_str", |b| b.iter(|| game.info_set_str(1)));
+    group.bench_function("info_set_key", |b| b.iter(|| game.info_set_key(1)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_info_set_prefix, bench_info_set_key);
 criterion_main!(benches);
@@ -6,7 +6,7 @@ fn bench_hay_lance_juego(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let mut baraja = Baraja::baraja_mus();
-                baraja.barajar();
+                baraja.barajar(&mut rand::thread_rng());
                 baraja.repartir_manos::<4>()
             },
             |manos| {
@@ -22,7 +22,7 @@ fn bench_hay_lance_pares(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let mut baraja = Baraja::baraja_mus();
-                baraja.barajar();
+                baraja.barajar(&mut rand::thread_rng());
                 baraja.repartir_manos::<4>()
             },
             |manos| {
@@ -38,7 +38,7 @@ fn bench_se_juega_lance_juego(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let mut baraja = Baraja::baraja_mus();
-                baraja.barajar();
+                baraja.barajar(&mut rand::thread_rng());
                 baraja.repartir_manos::<4>()
             },
             |manos| {
@@ -54,7 +54,7 @@ fn bench_se_juega_lance_pares(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let mut baraja = Baraja::baraja_mus();
-                baraja.barajar();
+                baraja.barajar(&mut rand::thread_rng());
                 baraja.repartir_manos::<4>()
             },
             |manos| {
@@ -15,7 +15,6 @@ fn main() {
     );
 
     cfr.nodes()
-        .iter()
         .filter(|(_, node)| node.get_average_strategy()[0] > 0.5)
         .for_each(|(info_set, node)| {
             println!(
@@ -58,8 +57,7 @@ impl FaseMus {
         }
     }
 
-    fn repartir_cartas(num_cartas: usize) -> [usize; 4] {
-        let mut rng = rand::thread_rng();
+    fn repartir_cartas<R: Rng + ?Sized>(num_cartas: usize, rng: &mut R) -> [usize; 4] {
         array::from_fn(|_| rng.gen_range(0..num_cartas))
     }
 }
@@ -128,7 +126,7 @@ impl Game for FaseMus {
             .join(",")
     }
 
-    fn new_random(&mut self) {
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         match self.fase {
             Fase::Repartir => {
                 self.turn = Some(0);
@@ -142,7 +140,7 @@ impl Game for FaseMus {
             }
         }
         self.history.push("R".into());
-        self.manos = Some(Self::repartir_cartas(self.num_cartas));
+        self.manos = Some(Self::repartir_cartas(self.num_cartas, rng));
     }
 
     fn reset(&mut self) {
@@ -1,4 +1,4 @@
-use musolver::{Cfr, Game};
+use musolver::{Cfr, Game, InfoSetKey};
 
 fn main() {
     let mut rps = Rps::new();
@@ -14,12 +14,12 @@ fn main() {
     let strategy1: Vec<(_, _)> = rps
         .actions()
         .into_iter()
-        .zip(cfr.nodes()["0"].get_average_strategy())
+        .zip(cfr.node("0").unwrap().get_average_strategy())
         .collect();
     let strategy2: Vec<(_, _)> = rps
         .actions()
         .into_iter()
-        .zip(cfr.nodes()["1"].get_average_strategy())
+        .zip(cfr.node("1").unwrap().get_average_strategy())
         .collect();
     println!("Strategy player 1: {strategy1:?}");
     println!("Strategy player 2: {strategy2:?}");
@@ -78,6 +78,11 @@ impl Game for Rps {
             .join(",")
     }
 
+    // The second player does not see the action of the first one.
+    fn public_state_key(&self) -> InfoSetKey {
+        self.history.len() as InfoSetKey
+    }
+
     fn actions(&self) -> Vec<Self::Action> {
         vec![RpsAction::Rock, RpsAction::Paper, RpsAction::Scissors]
     }
@@ -95,7 +100,7 @@ impl Game for Rps {
         };
     }
 
-    fn new_random(&mut self) {
+    fn new_random<R: rand::Rng + ?Sized>(&mut self, _rng: &mut R) {
         todo!()
     }
 
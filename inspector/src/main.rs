@@ -41,7 +41,8 @@ impl Inspector {
                     if let Some(a) = action {
                         match a {
                             screen::LoaderAction::OpenExplorer(strategy) => {
-                                self.screen = Screen::Explorer(screen::ActionPath::new(strategy));
+                                self.screen =
+                                    Screen::Explorer(Box::new(screen::ActionPath::new(strategy)));
                             }
                             screen::LoaderAction::OpenGame(strategy) => {
                                 let (screen, task) = screen::MusArenaUi::new(strategy.clone());
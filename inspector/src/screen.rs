@@ -9,6 +9,6 @@ pub use game::*;
 
 pub enum Screen {
     Loader(Loader),
-    Explorer(ActionPath),
+    Explorer(Box<ActionPath>),
     Game(MusArenaUi),
 }
@@ -18,7 +18,7 @@ use iced::{
 use itertools::Itertools;
 use musolver::{
     Game,
-    mus::{Accion, Baraja, DistribucionCartaIter, FasePartida, Lance, Mano, RankingManos},
+    mus::{Accion, DistribucionCartaIter, FasePartida, Lance, Mano, RankingManos, Variante},
     solver::{
         AbstractChica, AbstractGrande, AbstractJuego, AbstractJugada, AbstractPares, AbstractPunto,
         GameType, HandConfiguration, InfoSet, LanceGame, MusGameTwoPlayers, Strategy,
@@ -132,16 +132,20 @@ impl ActionPath {
         let mut action_path = Self {
             one_hand_squares: vec![],
             two_hands_squares: vec![],
-            buckets: Buckets::new(&Lance::Grande, None),
+            buckets: Buckets::new(
+                &Lance::Grande,
+                None,
+                strategy.strategy_config.game_config.variante,
+            ),
             view_mode: match game_type {
                 GameType::MusGameTwoHands => ViewMode::TwoHands,
                 _ => ViewMode::OneHand,
             },
             strategy: strategy.to_owned(),
             selected_tantos_mano: Some(0),
-            tantos_mano: Vec::from_iter(0..40),
+            tantos_mano: Vec::from_iter(0..strategy.strategy_config.game_config.max_tantos),
             selected_tantos_postre: Some(0),
-            tantos_postre: Vec::from_iter(0..40),
+            tantos_postre: Vec::from_iter(0..strategy.strategy_config.game_config.max_tantos),
             selected_actions: vec![],
             actions: vec![],
             selected_strategy: Some(HandConfiguration::CuatroManos),
@@ -185,7 +189,10 @@ impl ActionPath {
             GameType::LanceGame(lance) | GameType::LanceGameTwoHands(lance) => {
                 let tipo_estrategia = self.selected_strategy.unwrap();
                 let abstract_game_lance = if abstract_game { Some(lance) } else { None };
-                let mut lance_game = LanceGame::new(lance, tantos, abstract_game);
+                let mut lance_game = LanceGame::new(lance, tantos, abstract_game)
+                    .with_bet_abstraction(self.strategy.bet_abstraction())
+                    .with_max_tantos(self.strategy.strategy_config.game_config.max_tantos)
+                    .with_variante(self.strategy.strategy_config.game_config.variante);
                 lance_game.new_with_configuration(tipo_estrategia);
                 for action in &history {
                     lance_game.act(*action);
@@ -207,16 +214,16 @@ impl ActionPath {
             }
             GameType::MusGame => {
                 manos[turno as usize] = mano1.clone();
-                self.strategy.actions(&manos, tantos, &history)
+                self.strategy.actions(&manos, tantos, &history).ok()
             }
             GameType::MusGameTwoHands => {
                 manos[turno as usize] = mano1.clone();
                 manos[turno as usize + 2] = mano2.unwrap().clone();
-                self.strategy.actions(&manos, tantos, &history)
+                self.strategy.actions(&manos, tantos, &history).ok()
             }
             GameType::MusGameTwoPlayers => {
                 manos[turno as usize] = mano1.clone();
-                self.strategy.actions(&manos, tantos, &history)
+                self.strategy.actions(&manos, tantos, &history).ok()
             }
         }
     }
@@ -306,10 +313,11 @@ impl ActionPath {
             } else {
                 None
             };
+            let variante = self.strategy.strategy_config.game_config.variante;
             self.buckets = if let FasePartida::Envites(lance) = fase_partida {
-                Buckets::new(&lance, has_pares)
+                Buckets::new(&lance, has_pares, variante)
             } else {
-                Buckets::new(&Lance::Grande, has_pares)
+                Buckets::new(&Lance::Grande, has_pares, variante)
             };
 
             match self.view_mode {
@@ -632,6 +640,10 @@ impl ActionPath {
                     self.strategy.strategy_config.game_config.abstract_game,
                     self.strategy.strategy_config.game_config.max_mus_rounds,
                 )
+                .with_bet_abstraction(self.strategy.bet_abstraction())
+                .with_discard_abstraction(self.strategy.discard_abstraction())
+                .with_max_tantos(self.strategy.strategy_config.game_config.max_tantos)
+                .with_variante(self.strategy.strategy_config.game_config.variante)
                 .with_hands([manos[0].clone(), manos[1].clone()]);
                 self.selected_history()
                     .into_iter()
@@ -920,12 +932,11 @@ impl<Message> canvas::Program<Message> for SquareData<Message> {
         bounds: iced::Rectangle,
         cursor: mouse::Cursor,
     ) -> (canvas::event::Status, Option<Message>) {
-        if let canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) = event {
-            if cursor.position_in(bounds).is_some() {
-                if let Some(callback) = &self.on_hover {
-                    return (canvas::event::Status::Captured, Some(callback()));
-                }
-            }
+        if let canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) = event
+            && cursor.position_in(bounds).is_some()
+            && let Some(callback) = &self.on_hover
+        {
+            return (canvas::event::Status::Captured, Some(callback()));
         }
         (canvas::event::Status::Ignored, None)
     }
@@ -937,8 +948,8 @@ pub struct Buckets {
 }
 
 impl Buckets {
-    pub fn new(lance: &Lance, has_pares: Option<bool>) -> Self {
-        let one_hand_list = Self::one_hand_list(lance);
+    pub fn new(lance: &Lance, has_pares: Option<bool>, variante: Variante) -> Self {
+        let one_hand_list = Self::one_hand_list(lance, variante);
         let mut buckets = HashMap::new();
         one_hand_list
             .iter()
@@ -965,16 +976,12 @@ impl Buckets {
         self.buckets.get(jugada).map(|(hands, _)| hands)
     }
 
-    pub fn probability(&self, jugada: &AbstractJugada) -> Option<&f64> {
-        self.buckets.get(jugada).map(|(_, probability)| probability)
-    }
-
     pub fn jugadas(&self) -> &Vec<AbstractJugada> {
         &self.jugadas
     }
 
-    fn one_hand_list(lance: &Lance) -> Vec<(Mano, f64)> {
-        let manos = DistribucionCartaIter::new(Baraja::FREC_BARAJA_MUS)
+    fn one_hand_list(lance: &Lance, variante: Variante) -> Vec<(Mano, f64)> {
+        let manos = DistribucionCartaIter::new(variante.frecuencias())
             .map(|(cards, prob)| (Mano::new(cards), prob));
         manos
             .filter(|(hand, _)| hand.jugada(lance).is_some())
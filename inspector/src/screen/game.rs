@@ -10,10 +10,10 @@ use image::GenericImageView;
 use musolver::{
     Game,
     mus::{
-        Accion, CuatroJugadores, DosJugadores, Lance, Mano, ModalidadMus,
+        Accion, CuatroJugadores, DosJugadores, Lance, Mano, ModalidadMus, Variante,
         arena::{ActionRecorder, Agent, AgenteMusolver, Kibitzer, MusAction, MusArena},
     },
-    solver::{LanceGame, MusGameTwoPlayers, Strategy},
+    solver::{BetAbstraction, Fallback, LanceGame, MusGameTwoPlayers, Strategy},
 };
 
 #[derive(Debug, Clone)]
@@ -82,6 +82,7 @@ pub struct MusArenaUi {
     dealer: usize,
     scoreboard: [u8; 2],
     rounds: [u8; 2],
+    max_tantos: u8,
     game_running: bool,
     lance: Option<Lance>,
 }
@@ -118,6 +119,7 @@ impl MusArenaUi {
                 },
             ],
         };
+        let max_tantos = strategy.strategy_config.game_config.max_tantos;
         let task = Task::run(setup_arena(strategy), GameEvent::ArenaMessage);
         (
             Self {
@@ -128,6 +130,7 @@ impl MusArenaUi {
                 arena_events: vec![],
                 scoreboard: [0, 0],
                 rounds: [0, 0],
+                max_tantos,
                 deck_images: deck(),
                 game_running: false,
                 lance: None,
@@ -364,10 +367,10 @@ impl MusArenaUi {
                     None
                 }
                 ArenaMessage::NewGameRequested => {
-                    if self.scoreboard[0] == 40 {
+                    if self.scoreboard[0] == self.max_tantos {
                         self.rounds[0] += 1;
                     }
-                    if self.scoreboard[1] == 40 {
+                    if self.scoreboard[1] == self.max_tantos {
                         self.rounds[1] += 1;
                     }
                     if let ArenaState::Connected(_connection) = &mut self.state {
@@ -418,17 +421,23 @@ fn setup_arena(strategy: Strategy) -> impl Stream<Item = ArenaMessage> {
             sender: mpsc::Sender<ArenaMessage>,
             receiver: mpsc::Receiver<ArenaCommand>,
             history: Arc<Mutex<Vec<Accion>>>,
+            bet_abstraction: Arc<BetAbstraction>,
+            variante: Variante,
         }
         impl AgentGui {
             fn new(
                 sender: mpsc::Sender<ArenaMessage>,
                 receiver: mpsc::Receiver<ArenaCommand>,
                 history: Arc<Mutex<Vec<Accion>>>,
+                bet_abstraction: Arc<BetAbstraction>,
+                variante: Variante,
             ) -> Self {
                 Self {
                     sender,
                     receiver,
                     history,
+                    bet_abstraction,
+                    variante,
                 }
             }
         }
@@ -440,9 +449,11 @@ fn setup_arena(strategy: Strategy) -> impl Stream<Item = ArenaMessage> {
                 partida_mus: &musolver::mus::FaseEnvites<DosJugadores>,
             ) -> musolver::mus::Accion {
                 let next_actions = {
-                    let mut mus_game =
-                        MusGameTwoPlayers::new(*partida_mus.tantos(), false, todo!())
-                            .with_hands(partida_mus.manos().clone());
+                    let mut mus_game = MusGameTwoPlayers::new(*partida_mus.tantos(), false, 0)
+                        .with_bet_abstraction(self.bet_abstraction.clone())
+                        .with_max_tantos(partida_mus.max_tantos())
+                        .with_variante(self.variante)
+                        .with_hands(partida_mus.manos().clone());
                     for action in self.history.lock().unwrap().iter() {
                         mus_game.act(*action);
                     }
@@ -465,7 +476,9 @@ fn setup_arena(strategy: Strategy) -> impl Stream<Item = ArenaMessage> {
                 &mut self,
                 partida_mus: &musolver::mus::FaseEnvites<CuatroJugadores>,
             ) -> musolver::mus::Accion {
-                let mut lance_game = LanceGame::from_partida_mus(partida_mus, true).unwrap();
+                let mut lance_game = LanceGame::from_partida_mus(partida_mus, true)
+                    .unwrap()
+                    .with_bet_abstraction(self.bet_abstraction.clone());
                 for action in self.history.lock().unwrap().iter() {
                     lance_game.act(*action);
                 }
@@ -488,15 +501,27 @@ fn setup_arena(strategy: Strategy) -> impl Stream<Item = ArenaMessage> {
         }));
 
         let game_type = strategy.strategy_config.game_config.game_type;
+        let max_tantos = strategy.strategy_config.game_config.max_tantos;
+        let variante = strategy.strategy_config.game_config.variante;
+        let bet_abstraction = strategy.bet_abstraction();
         let kibitzer = KibitzerGui::new(sender.clone());
         let action_recorder = ActionRecorder::new();
-        let agent_musolver = AgenteMusolver::new(strategy, action_recorder.history());
-        let agent_gui = AgentGui::new(sender.clone(), receiver_agent, action_recorder.history());
+        let agent_musolver = AgenteMusolver::new(strategy, action_recorder.history())
+            .with_fallbacks(Fallback::DEFAULT_CHAIN.to_vec());
+        let agent_gui = AgentGui::new(
+            sender.clone(),
+            receiver_agent,
+            action_recorder.history(),
+            bet_abstraction,
+            variante,
+        );
 
         match game_type {
             musolver::solver::GameType::LanceGame(_lance) => todo!(),
             musolver::solver::GameType::LanceGameTwoHands(lance) => {
-                let mut arena = MusArena::<CuatroJugadores>::new([0, 0], Some(lance));
+                let mut arena = MusArena::<CuatroJugadores>::new([0, 0], Some(lance))
+                    .with_max_tantos(max_tantos)
+                    .with_variante(variante);
                 arena.agents.push(Box::new(agent_gui));
                 arena.agents.push(Box::new(agent_musolver.clone()));
                 arena.agents.push(Box::new(agent_musolver.clone()));
@@ -512,7 +537,9 @@ fn setup_arena(strategy: Strategy) -> impl Stream<Item = ArenaMessage> {
                 }
             }
             musolver::solver::GameType::MusGame | musolver::solver::GameType::MusGameTwoHands => {
-                let mut arena = MusArena::<CuatroJugadores>::new([0, 0], None);
+                let mut arena = MusArena::<CuatroJugadores>::new([0, 0], None)
+                    .with_max_tantos(max_tantos)
+                    .with_variante(variante);
                 arena.agents.push(Box::new(agent_gui));
                 arena.agents.push(Box::new(agent_musolver.clone()));
                 arena.agents.push(Box::new(agent_musolver.clone()));
@@ -528,7 +555,11 @@ fn setup_arena(strategy: Strategy) -> impl Stream<Item = ArenaMessage> {
                 }
             }
             musolver::solver::GameType::MusGameTwoPlayers => {
-                let mut arena = MusArena::<DosJugadores>::new([36, 36], None);
+                // Se empieza a cuatro tantos del final, como con los 40 tantos habituales.
+                let inicio = max_tantos.saturating_sub(4);
+                let mut arena = MusArena::<DosJugadores>::new([inicio, inicio], None)
+                    .with_max_tantos(max_tantos)
+                    .with_variante(variante);
                 arena.agents.push(Box::new(agent_gui));
                 arena.agents.push(Box::new(agent_musolver.clone()));
                 arena.kibitzers.push(Box::new(kibitzer));
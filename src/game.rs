@@ -10,6 +10,17 @@ pub use self::action_tree::*;
 mod cfr;
 pub use self::cfr::*;
 
+mod parallel_cfr;
+
+mod best_response;
+pub use self::best_response::*;
+
+mod stopping;
+pub use self::stopping::*;
+
+mod sampled_game;
+pub use self::sampled_game::*;
+
 mod graph;
 pub use self::graph::*;
 
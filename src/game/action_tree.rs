@@ -33,11 +33,37 @@ use serde::{Deserialize, Serialize};
 ///     let mut bet = root.add_non_terminal_action(Action::Bet, 1).unwrap();
 ///     bet.add_terminal_action(Action::Pass);
 ///     bet.add_terminal_action(Action::Call);
-#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+#[rkyv(archive_bounds(A: rkyv::Archive))]
+#[rkyv(serialize_bounds(
+    __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+    __S::Error: rkyv::rancor::Source,
+    A: rkyv::Serialize<__S>,
+))]
+#[rkyv(deserialize_bounds(
+    __D::Error: rkyv::rancor::Source,
+    A::Archived: rkyv::Deserialize<A, __D>,
+))]
+#[rkyv(bytecheck(bounds(
+    __C: rkyv::validation::ArchiveContext,
+    __C::Error: rkyv::rancor::Source,
+    A::Archived: rkyv::bytecheck::CheckBytes<__C>,
+)))]
 pub enum ActionNode<P, A> {
     #[default]
     Terminal,
-    NonTerminal(P, Vec<(A, ActionNode<P, A>)>),
+    NonTerminal(P, #[rkyv(omit_bounds)] Vec<(A, ActionNode<P, A>)>),
 }
 
 impl<P, A> ActionNode<P, A>
@@ -120,7 +146,7 @@ where
         P: for<'a> Deserialize<'a>,
     {
         let contents = fs::read_to_string(path)?;
-        let n: ActionNode<P, A> = serde_json::from_str(&contents).unwrap();
+        let n: ActionNode<P, A> = serde_json::from_str(&contents)?;
 
         Ok(n)
     }
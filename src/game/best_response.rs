@@ -0,0 +1,260 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use rand::Rng;
+
+use super::{Cfr, Game, InfoSetKey, NodeType, SampledGame};
+
+/// Monte Carlo estimate of the exploitability. See `Cfr::estimate_exploitability`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExploitabilityEstimate {
+    /// Mean of the exploitability of the batches. It is biased upwards.
+    pub mean: f64,
+    /// Half-width of the 95% interval of the mean due to the sampling noise between batches. It
+    /// is not a confidence interval of the exploitability: every batch has the same upward bias,
+    /// which the interval does not cover. It is only known with more than one batch.
+    pub sampling_margin: Option<f64>,
+    pub batches: usize,
+}
+
+impl fmt::Display for ExploitabilityEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.sampling_margin {
+            Some(margin) => write!(
+                f,
+                "{:.6} (upper-biased, sampling noise ± {:.6})",
+                self.mean, margin
+            ),
+            None => write!(f, "{:.6} (upper-biased)", self.mean),
+        }
+    }
+}
+
+/// Position of a state in the public states of the next level of the best response.
+type ChildRef = (usize, usize);
+
+/// How the value of a state is computed from the values of its children.
+enum Expansion {
+    Terminal(f64),
+    /// Expected value of the children, with their probabilities. Used in chance nodes and in the
+    /// nodes of the opponents.
+    Weighted(Vec<(ChildRef, f64)>),
+    /// Node of the best responder: its information set, the reach probability of the state and a
+    /// child for every action.
+    BestResponse(InfoSetKey, f64, Vec<ChildRef>),
+}
+
+/// States grouped by their public state, keeping the order in which the public states appear.
+struct PublicStates<G> {
+    groups: Vec<Vec<(G, f64)>>,
+    index: HashMap<InfoSetKey, usize>,
+}
+
+impl<G: Game> PublicStates<G> {
+    fn new() -> Self {
+        Self {
+            groups: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, game: G, reach: f64) -> ChildRef {
+        let groups = &mut self.groups;
+        let group = *self
+            .index
+            .entry(game.public_state_key())
+            .or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+        self.groups[group].push((game, reach));
+        (group, self.groups[group].len() - 1)
+    }
+}
+
+impl Cfr {
+    /// Sum over the players of the value of a best response against the average strategy.
+    /// Information sets that have not been visited during training play the uniform strategy.
+    pub fn exploitability<G>(&self, game: &mut G) -> f64
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        self.exploitability_cancellable(game, &AtomicBool::new(false))
+            .unwrap()
+    }
+
+    /// Same as `Cfr::exploitability`, but it gives up the traversal and returns `None` once
+    /// `cancel` is set.
+    pub fn exploitability_cancellable<G>(&self, game: &mut G, cancel: &AtomicBool) -> Option<f64>
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        (0..G::N_PLAYERS)
+            .map(|player| {
+                self.best_response_values(vec![(game.clone(), 1.)], player, cancel)
+                    .map(|values| values[0])
+            })
+            .sum()
+    }
+
+    /// Exact value for `player` of a best response against the average strategy of the other
+    /// players.
+    ///
+    /// It is not a vectorised public tree traversal: every state of the game is enumerated, with
+    /// its own copy of the game, so the cost grows with the number of states. The states are only
+    /// grouped by their public state (see `Game::public_state_key`), with the probability of
+    /// reaching them when `player` plays to reach them, so that the best action of every
+    /// information set is chosen with all its states at hand. Only the states of the public
+    /// states along the current path are kept in memory.
+    pub fn best_response_value<G>(&self, game: &G, player: usize) -> f64
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        self.best_response_values(vec![(game.clone(), 1.)], player, &AtomicBool::new(false))
+            .unwrap()[0]
+    }
+
+    /// Monte Carlo estimate of the exploitability for games whose initial chance node is too
+    /// large to traverse. The exact exploitability is computed on `batches` independent samples
+    /// of `samples` outcomes of the initial chance node, using `SampledGame`.
+    ///
+    /// The estimate is biased upwards: with few outcomes per information set the best response
+    /// on a sample learns the hidden information of its opponents. `samples` has to be large
+    /// compared to the number of outcomes that share an information set for the bias to be
+    /// small. The margin of the estimate only measures the noise between batches, not the bias.
+    pub fn estimate_exploitability<G, R>(
+        &self,
+        game: &G,
+        samples: usize,
+        batches: usize,
+        rng: &mut R,
+    ) -> ExploitabilityEstimate
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+        R: Rng + ?Sized,
+    {
+        let values: Vec<f64> = (0..batches)
+            .map(|_| self.exploitability(&mut SampledGame::new(game, samples, rng)))
+            .collect();
+        let n = batches as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let sampling_margin = (batches > 1).then(|| {
+            let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.);
+            1.96 * (variance / n).sqrt()
+        });
+        ExploitabilityEstimate {
+            mean,
+            sampling_margin,
+            batches,
+        }
+    }
+
+    /// Values for `player` of the states of a public state, given with their reach probability.
+    /// Returns `None` if `cancel` is set before finishing.
+    fn best_response_values<G>(
+        &self,
+        states: Vec<(G, f64)>,
+        player: usize,
+        cancel: &AtomicBool,
+    ) -> Option<Vec<f64>>
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let mut children = PublicStates::new();
+        let mut expansions = Vec::with_capacity(states.len());
+        for (mut game, reach) in states {
+            let expansion = match game.current_player() {
+                NodeType::Terminal => Expansion::Terminal(game.utility(player)),
+                NodeType::Chance => Expansion::Weighted(
+                    game.new_iter()
+                        .map(|(child, prob)| (children.push(child, reach * prob), prob))
+                        .collect(),
+                ),
+                NodeType::Player(current_player) if current_player == player => {
+                    let child_refs = game
+                        .actions()
+                        .into_iter()
+                        .map(|action| {
+                            let mut child = game.clone();
+                            child.act(action);
+                            children.push(child, reach)
+                        })
+                        .collect();
+                    Expansion::BestResponse(game.info_set_key(player), reach, child_refs)
+                }
+                NodeType::Player(current_player) => {
+                    let actions = game.actions();
+                    let strategy = self.average_strategy(&game, current_player, actions.len());
+                    Expansion::Weighted(
+                        std::iter::zip(actions, strategy)
+                            .filter(|(_, prob)| *prob > 0.)
+                            .map(|(action, prob)| {
+                                let mut child = game.clone();
+                                child.act(action);
+                                (children.push(child, reach * prob), prob)
+                            })
+                            .collect(),
+                    )
+                }
+            };
+            expansions.push(expansion);
+        }
+
+        let values: Vec<Vec<f64>> = children
+            .groups
+            .into_iter()
+            .map(|group| self.best_response_values(group, player, cancel))
+            .collect::<Option<_>>()?;
+        let value = |(group, idx): ChildRef| values[group][idx];
+
+        let mut action_values: HashMap<InfoSetKey, Vec<f64>> = HashMap::new();
+        for expansion in &expansions {
+            if let Expansion::BestResponse(info_set_key, reach, child_refs) = expansion {
+                action_values
+                    .entry(*info_set_key)
+                    .or_insert_with(|| vec![0.; child_refs.len()])
+                    .iter_mut()
+                    .zip(child_refs)
+                    .for_each(|(v, child)| *v += reach * value(*child));
+            }
+        }
+        let best_actions: HashMap<InfoSetKey, usize> = action_values
+            .into_iter()
+            .map(|(info_set_key, values)| {
+                let best_action = values
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(idx, _)| idx)
+                    .unwrap();
+                (info_set_key, best_action)
+            })
+            .collect();
+
+        let values = expansions
+            .into_iter()
+            .map(|expansion| match expansion {
+                Expansion::Terminal(utility) => utility,
+                Expansion::Weighted(child_refs) => child_refs
+                    .into_iter()
+                    .map(|(child, prob)| prob * value(child))
+                    .sum(),
+                Expansion::BestResponse(info_set_key, _, child_refs) => {
+                    value(child_refs[best_actions[&info_set_key]])
+                }
+            })
+            .collect();
+        Some(values)
+    }
+}
@@ -1,12 +1,29 @@
 use rand::distributions::WeightedIndex;
 use rand::prelude::Distribution;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    ops::Range,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use super::{GameError, GameGraph};
 
+/// State of the random number generator of `Cfr`.
+#[derive(Debug, Clone, PartialEq, rkyv::Serialize, rkyv::Deserialize, rkyv::Archive)]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
 /// Node of the CFR algorithm.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Serialize, Deserialize, rkyv::Serialize, rkyv::Deserialize, rkyv::Archive,
+)]
 pub struct Node {
     pub regret_sum: Vec<f64>,
     strategy: Vec<f64>,
@@ -22,6 +39,18 @@ impl Node {
         }
     }
 
+    /// Node whose current strategy is `strategy` and whose average strategy is `strategy` with
+    /// weight `weight`, as if it had been played `weight` times. Cumulative regrets start at zero.
+    pub fn from_strategy(strategy: &[f64], weight: f64) -> Self {
+        let mut node = Self::new(strategy.len());
+        let normalizing_sum: f64 = strategy.iter().sum();
+        if normalizing_sum > 0. {
+            node.strategy = strategy.iter().map(|s| s / normalizing_sum).collect();
+        }
+        node.update_strategy_sum(weight);
+        node
+    }
+
     pub fn update_strategy(&mut self) -> &Vec<f64> {
         for i in 0..self.strategy.len() {
             self.strategy[i] = self.regret_sum[i].max(0.);
@@ -37,6 +66,13 @@ impl Node {
         &self.strategy
     }
 
+    /// Regret matching+. Cumulative regrets are floored at zero before computing the new
+    /// strategy.
+    pub fn update_strategy_plus(&mut self) -> &Vec<f64> {
+        self.regret_sum.iter_mut().for_each(|r| *r = r.max(0.));
+        self.update_strategy()
+    }
+
     pub fn strategy(&self) -> &Vec<f64> {
         &self.strategy
     }
@@ -59,9 +95,29 @@ impl Node {
         }
     }
 
-    pub fn get_random_action(&self) -> usize {
+    /// Discounts the cumulative regrets, using `positive` or `negative` depending on their sign,
+    /// and the average strategy.
+    pub fn discount(&mut self, positive: f64, negative: f64, strategy: f64) {
+        self.regret_sum
+            .iter_mut()
+            .for_each(|r| *r *= if *r > 0. { positive } else { negative });
+        self.strategy_sum.iter_mut().for_each(|s| *s *= strategy);
+    }
+
+    /// Actions skipped by regret-based pruning with the given threshold: those with cumulative
+    /// regret below it. Regret matching only gives them zero probability if some regret is
+    /// positive, so otherwise nothing is pruned. With no threshold nothing is pruned either.
+    pub(crate) fn pruned_actions(&self, threshold: Option<f64>) -> Vec<bool> {
+        let prunable = threshold.filter(|_| self.regret_sum.iter().any(|r| *r > 0.));
+        self.regret_sum
+            .iter()
+            .map(|r| prunable.is_some_and(|threshold| *r < threshold.min(0.)))
+            .collect()
+    }
+
+    pub fn get_random_action<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
         let dist = WeightedIndex::new(&self.strategy).unwrap();
-        dist.sample(&mut rand::thread_rng())
+        dist.sample(rng)
     }
 }
 
@@ -163,7 +219,7 @@ pub enum NodeType {
 ///    #         .join(",")
 ///    # }
 ///    #
-///    # fn new_random(&mut self) {
+///    # fn new_random<R: rand::Rng + ?Sized>(&mut self, rng: &mut R) {
 ///    # }
 ///    #
 ///    # fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
@@ -186,8 +242,32 @@ pub trait Game: Sized {
     /// the history slice.
     fn info_set_str(&self, player: usize) -> String;
 
+    /// Compact key of the information set for player P. By default it hashes `info_set_str`.
+    /// Games can override it to feed the pieces of the information set to an `InfoSetHasher`
+    /// without building the string, but the key must be the same as the hash of `info_set_str`:
+    /// `Cfr` checks it in debug builds.
+    fn info_set_key(&self, player: usize) -> InfoSetKey {
+        InfoSetHasher::hash_str(&self.info_set_str(player))
+    }
+
+    /// Length of the prefix of `info_set_str` with the private information of the player, before
+    /// the public history. `Cfr` saves every distinct prefix and history only once, so games
+    /// should override it to save memory. By default the whole string is the prefix.
+    fn info_set_prefix_len(&self, player: usize) -> usize {
+        self.info_set_str(player).len()
+    }
+
     fn history_str(&self) -> String;
 
+    /// Key of the public state: what every player knows about the current state. The best
+    /// response groups the states by their public state, so every information set must only
+    /// contain states of the same public state. By default it hashes `history_str`, which is
+    /// right for games where all the actions of the players are public. Games with hidden
+    /// actions have to override it.
+    fn public_state_key(&self) -> InfoSetKey {
+        InfoSetHasher::hash_str(&self.history_str())
+    }
+
     /// Actions available in the current state of the game.
     fn actions(&self) -> Vec<Self::Action>;
 
@@ -197,8 +277,9 @@ pub trait Game: Sized {
     /// Advance the state with the given action for the current player.
     fn act(&mut self, a: Self::Action);
 
-    /// Picks a random action in chance nodes.
-    fn new_random(&mut self);
+    /// Picks a random action in chance nodes. All the randomness must come from `rng`, so that
+    /// a training with a fixed seed can be reproduced.
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R);
 
     /// Resets the game to its initial state.
     fn reset(&mut self);
@@ -207,6 +288,59 @@ pub trait Game: Sized {
     fn new_iter(&self) -> impl Iterator<Item = (Self, f64)>;
 }
 
+/// Key of an information set in the node table of `Cfr`: the 128-bit hash of its string form.
+///
+/// Two information sets with the same key would share a node without any error in release
+/// builds, so the hash is wide enough to make it negligible: with `n` information sets the
+/// probability of any collision is about `n² / 2^129`, below `1.5e-19` for `10^10` nodes, far more
+/// than fit in memory. Debug builds compare the strings of the information sets of every key.
+pub type InfoSetKey = u128;
+
+/// 128-bit FNV-1a hasher for information sets. The key only depends on the bytes written, not on
+/// how they are split, so a game can write the pieces of its information set one by one and get
+/// the same key as hashing the whole string. It implements `fmt::Write` to write formatted pieces
+/// without allocating.
+#[derive(Debug, Clone, Copy)]
+pub struct InfoSetHasher(InfoSetKey);
+
+impl InfoSetHasher {
+    const OFFSET_BASIS: InfoSetKey = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: InfoSetKey = 0x0000000001000000000000000000013B;
+
+    pub fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    pub fn hash_str(info_set: &str) -> InfoSetKey {
+        Self::new().write_str(info_set).finish()
+    }
+
+    pub fn write_str(&mut self, s: &str) -> &mut Self {
+        for byte in s.bytes() {
+            self.0 ^= byte as InfoSetKey;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+        self
+    }
+
+    pub fn finish(&self) -> InfoSetKey {
+        self.0
+    }
+}
+
+impl Default for InfoSetHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Write for InfoSetHasher {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        InfoSetHasher::write_str(self, s);
+        Ok(())
+    }
+}
+
 #[derive(
     Debug,
     Copy,
@@ -223,6 +357,53 @@ pub enum CfrMethod {
     ChanceSampling,
     ExternalSampling,
     FsiCfr,
+    /// Discounted CFR on top of chance sampling. At the end of every discount block `t`, positive
+    /// regrets are multiplied by `t^alpha / (t^alpha + 1)`, negative regrets by
+    /// `t^beta / (t^beta + 1)` and the average strategy by `(t / (t + 1))^gamma`. Blocks last
+    /// one iteration by default, see `Cfr::with_discount_interval`.
+    Dcfr {
+        alpha: f64,
+        beta: f64,
+        gamma: f64,
+    },
+    /// Linear CFR on top of chance sampling: regrets and average strategy are multiplied by
+    /// `t / (t + 1)` at the end of every discount block `t`, so both the regrets and the strategy
+    /// of iteration `t` weigh `t`. It is equivalent to `Dcfr { alpha: 1., beta: 1., gamma: 1. }`.
+    LinearCfr,
+    /// Outcome sampling MCCFR. A single trajectory is sampled per iteration and utilities are
+    /// corrected with importance weights. The updating player explores uniformly with
+    /// probability `epsilon`.
+    OutcomeSampling {
+        epsilon: f64,
+    },
+}
+
+impl CfrMethod {
+    /// Parameters recommended by Brown and Sandholm for Discounted CFR.
+    pub const DCFR_DEFAULT: CfrMethod = CfrMethod::Dcfr {
+        alpha: 1.5,
+        beta: 0.,
+        gamma: 2.,
+    };
+
+    /// Exploration used by default in outcome sampling.
+    pub const OUTCOME_SAMPLING_DEFAULT: CfrMethod = CfrMethod::OutcomeSampling { epsilon: 0.6 };
+
+    /// Discount factors applied at the end of the discount block `block` to the positive regrets,
+    /// the negative regrets and the average strategy.
+    pub(crate) fn discount_factors(&self, block: f64) -> (f64, f64, f64) {
+        match self {
+            CfrMethod::Dcfr { alpha, beta, gamma } => (
+                block.powf(*alpha) / (block.powf(*alpha) + 1.),
+                block.powf(*beta) / (block.powf(*beta) + 1.),
+                (block / (block + 1.)).powf(*gamma),
+            ),
+            _ => {
+                let weight = block / (block + 1.);
+                (weight, weight, weight)
+            }
+        }
+    }
 }
 
 impl FromStr for CfrMethod {
@@ -235,11 +416,149 @@ impl FromStr for CfrMethod {
             "chance-sampling" => Ok(CfrMethod::ChanceSampling),
             "external-sampling" => Ok(CfrMethod::ExternalSampling),
             "fsi-cfr" => Ok(CfrMethod::FsiCfr),
-            _ => Err(GameError::InvalidCfrMethod(s.to_owned())),
+            "dcfr" => Ok(CfrMethod::DCFR_DEFAULT),
+            "linear-cfr" => Ok(CfrMethod::LinearCfr),
+            "outcome-sampling" => Ok(CfrMethod::OUTCOME_SAMPLING_DEFAULT),
+            method => {
+                // Parámetros explícitos: dcfr:alpha:beta:gamma y outcome-sampling:epsilon.
+                let (name, params) = method
+                    .split_once(':')
+                    .ok_or_else(|| GameError::InvalidCfrMethod(s.to_owned()))?;
+                let params: Option<Vec<f64>> = params.split(':').map(|p| p.parse().ok()).collect();
+                match (name, params.as_deref()) {
+                    ("dcfr", Some(&[alpha, beta, gamma])) => {
+                        Ok(CfrMethod::Dcfr { alpha, beta, gamma })
+                    }
+                    ("outcome-sampling", Some(&[epsilon])) if (0. ..=1.).contains(&epsilon) => {
+                        Ok(CfrMethod::OutcomeSampling { epsilon })
+                    }
+                    _ => Err(GameError::InvalidCfrMethod(s.to_owned())),
+                }
+            }
         }
     }
 }
 
+/// Regret-based pruning. The actions of the traversing player whose cumulative regret is below
+/// `threshold` are not explored, and their regrets are left unchanged. Regret matching never
+/// plays them, so the utility of the node is not affected. Since a pruned action could become
+/// good again as the rest of the strategy changes, every `full_pass_every` iterations the whole
+/// tree is traversed. It applies to `CfrMethod::Cfr`, `CfrMethod::ExternalSampling` and the
+/// methods built on chance sampling.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub struct RegretPruning {
+    /// Cumulative regret below which an action is pruned. It must be negative.
+    pub threshold: f64,
+    /// Number of iterations at the start of the training that are run without pruning.
+    pub warmup: usize,
+    /// Period, in iterations, of the traversals without pruning.
+    pub full_pass_every: usize,
+}
+
+impl RegretPruning {
+    /// Threshold to use in the iteration `iteration`, or `None` if the whole tree has to be
+    /// traversed.
+    pub(crate) fn threshold(&self, iteration: usize) -> Option<f64> {
+        (iteration >= self.warmup && !iteration.is_multiple_of(self.full_pass_every))
+            .then_some(self.threshold)
+    }
+}
+
+/// Strategies used to initialize the nodes created during the training. See
+/// `Cfr::with_warm_start`.
+#[derive(Debug, Clone, Default, rkyv::Serialize, rkyv::Deserialize, rkyv::Archive)]
+pub(crate) struct WarmStart {
+    strategies: HashMap<InfoSetKey, Vec<f64>>,
+    weight: f64,
+}
+
+impl WarmStart {
+    /// New node for the information set `key`. It starts from the strategy of the warm start if
+    /// there is one with `num_actions` actions, and from the uniform strategy otherwise.
+    pub(crate) fn new_node(&self, key: InfoSetKey, num_actions: usize) -> Node {
+        match self.strategies.get(&key) {
+            Some(strategy) if strategy.len() == num_actions => {
+                Node::from_strategy(strategy, self.weight)
+            }
+            _ => Node::new(num_actions),
+        }
+    }
+}
+
+/// String form of the information sets of the nodes of `Cfr`. Information sets are built from a
+/// prefix with the private information of the player (the cards, the discards...) and the
+/// public history, and there are far fewer distinct prefixes and histories than information
+/// sets. So the strings are not saved per node: every distinct part is saved only once, packed
+/// one after another in a single buffer, and every key only keeps the indices of its two parts.
+#[derive(Debug, Clone, Default, rkyv::Serialize, rkyv::Deserialize, rkyv::Archive)]
+pub(crate) struct InfoSetNames {
+    buffer: String,
+    /// Position of every part in `buffer`, packed in a `u64`.
+    spans: Vec<u64>,
+    /// Index of every part by its hash. Parts whose hash collides with a different part are
+    /// saved again without index, so the names are always exact.
+    part_indices: HashMap<u64, u32>,
+    /// Indices of the prefix and the history of every key.
+    names: HashMap<InfoSetKey, [u32; 2]>,
+}
+
+impl InfoSetNames {
+    /// Bits of the span used by the length of the string. The remaining ones hold its start.
+    const LEN_BITS: u32 = 24;
+
+    /// Saves the string of `key`, unless it already has one. `prefix_len` is the length of the
+    /// private prefix of the string. See `Game::info_set_prefix_len`.
+    pub(crate) fn insert(&mut self, key: InfoSetKey, info_set: &str, prefix_len: usize) {
+        if !self.names.contains_key(&key) {
+            let (prefix, history) = info_set.split_at(prefix_len);
+            let parts = [self.insert_part(prefix), self.insert_part(history)];
+            self.names.insert(key, parts);
+        }
+    }
+
+    fn insert_part(&mut self, part: &str) -> u32 {
+        let hash = InfoSetHasher::hash_str(part) as u64;
+        if let Some(&idx) = self.part_indices.get(&hash)
+            && self.part(idx) == part
+        {
+            return idx;
+        }
+        assert!(
+            part.len() < 1 << Self::LEN_BITS,
+            "Information set too long: {part}"
+        );
+        let idx = u32::try_from(self.spans.len()).expect("Too many information set parts");
+        let start = self.buffer.len() as u64;
+        self.buffer.push_str(part);
+        self.spans.push(start << Self::LEN_BITS | part.len() as u64);
+        self.part_indices.entry(hash).or_insert(idx);
+        idx
+    }
+
+    fn part(&self, idx: u32) -> &str {
+        let span = self.spans[idx as usize];
+        let start = (span >> Self::LEN_BITS) as usize;
+        let len = (span & ((1 << Self::LEN_BITS) - 1)) as usize;
+        &self.buffer[start..start + len]
+    }
+
+    pub(crate) fn get(&self, key: InfoSetKey) -> Option<String> {
+        self.names
+            .get(&key)
+            .map(|&[prefix, history]| [self.part(prefix), self.part(history)].concat())
+    }
+}
+
 /// Implementation of the CFR algorithm. It works on types that implement the trait `Game`.
 ///
 /// ```ignore
@@ -255,13 +574,169 @@ impl FromStr for CfrMethod {
 /// ```
 #[derive(Debug, Clone)]
 pub struct Cfr {
-    nodes: HashMap<String, Node>,
+    pub(crate) nodes: HashMap<InfoSetKey, Node>,
+    /// String form of the information set of every node, saved when the node is created.
+    pub(crate) info_sets: InfoSetNames,
+    /// Source of the randomness of the sampling methods. It is the generator behind `StdRng`,
+    /// used directly so that its state can be saved. See `Cfr::rng_state`.
+    pub(crate) rng: ChaCha12Rng,
+    pub(crate) pruning: Option<RegretPruning>,
+    pub(crate) warm_start: WarmStart,
+    /// Iterations of every discount block of `CfrMethod::Dcfr` and `CfrMethod::LinearCfr`.
+    pub(crate) discount_interval: usize,
 }
 
 impl Cfr {
     pub fn new() -> Self {
         Self {
             nodes: HashMap::new(),
+            info_sets: InfoSetNames::default(),
+            rng: ChaCha12Rng::from_entropy(),
+            pruning: None,
+            warm_start: WarmStart::default(),
+            discount_interval: 1,
+        }
+    }
+
+    /// Seeds the random number generator, so that trainings with the same seed produce the same
+    /// nodes. Parallel trainings are not reproducible, because the order in which the threads
+    /// update the nodes is not deterministic.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            ..self
+        }
+    }
+
+    /// Seeds again the random number generator. See `Cfr::with_seed`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+
+    /// Current state of the random number generator, to continue the same random sequence
+    /// after resuming a training. See `Cfr::set_rng_state`.
+    pub fn rng_state(&self) -> RngState {
+        RngState {
+            seed: self.rng.get_seed(),
+            stream: self.rng.get_stream(),
+            word_pos: self.rng.get_word_pos(),
+        }
+    }
+
+    /// Restores a state of the random number generator saved with `Cfr::rng_state`.
+    pub fn set_rng_state(&mut self, state: &RngState) {
+        self.rng = ChaCha12Rng::from_seed(state.seed);
+        self.rng.set_stream(state.stream);
+        self.rng.set_word_pos(state.word_pos);
+    }
+
+    /// Enables regret-based pruning in the following trainings. See `RegretPruning`.
+    pub fn with_pruning(self, pruning: RegretPruning) -> Self {
+        Self {
+            pruning: Some(pruning),
+            ..self
+        }
+    }
+
+    /// Enables or disables regret-based pruning. See `Cfr::with_pruning`.
+    pub fn set_pruning(&mut self, pruning: Option<RegretPruning>) {
+        self.pruning = pruning;
+    }
+
+    /// Discounts the nodes of `CfrMethod::Dcfr` and `CfrMethod::LinearCfr` once every `interval`
+    /// iterations instead of after every iteration. Discounting traverses all the nodes, so with
+    /// sampling methods and large games it is worth grouping the iterations in blocks.
+    pub fn with_discount_interval(self, interval: usize) -> Self {
+        assert!(interval > 0, "El intervalo de descuento debe ser positivo.");
+        Self {
+            discount_interval: interval,
+            ..self
+        }
+    }
+
+    /// Changes the discount interval. See `Cfr::with_discount_interval`.
+    pub fn set_discount_interval(&mut self, interval: usize) {
+        assert!(interval > 0, "El intervalo de descuento debe ser positivo.");
+        self.discount_interval = interval;
+    }
+
+    /// Initializes the nodes with the strategies of a previous training, for instance of a
+    /// similar game. When a node is created during the training, if `strategies` has a strategy
+    /// for its information set with the same number of actions, it is used as its current
+    /// strategy and added to its average strategy with weight `weight`. The higher the weight,
+    /// the more iterations it takes to move away from it. Information sets missing from
+    /// `strategies` start with the uniform strategy, and those of `strategies` that are never
+    /// reached are ignored.
+    pub fn with_warm_start(
+        self,
+        strategies: impl IntoIterator<Item = (String, Vec<f64>)>,
+        weight: f64,
+    ) -> Self {
+        let strategies = strategies
+            .into_iter()
+            .map(|(info_set, strategy)| (InfoSetHasher::hash_str(&info_set), strategy))
+            .collect();
+        Self {
+            warm_start: WarmStart { strategies, weight },
+            ..self
+        }
+    }
+
+    /// Creates a CFR instance from previously trained nodes, for instance to resume a training
+    /// from a checkpoint.
+    pub fn from_nodes(nodes: HashMap<String, Node>) -> Self {
+        let mut cfr = Self::new();
+        for (info_set, node) in nodes {
+            let key = InfoSetHasher::hash_str(&info_set);
+            cfr.nodes.insert(key, node);
+            cfr.info_sets.insert(key, &info_set, info_set.len());
+        }
+        cfr
+    }
+
+    /// Returns the node of the information set of `player`, creating it if it does not exist.
+    fn node_entry<G: Game>(
+        &mut self,
+        game: &G,
+        player: usize,
+        num_actions: usize,
+    ) -> (InfoSetKey, &mut Node) {
+        let key = game.info_set_key(player);
+        self.insert_node(key, num_actions, || {
+            (game.info_set_str(player), game.info_set_prefix_len(player))
+        });
+        (key, self.nodes.get_mut(&key).unwrap())
+    }
+
+    /// Creates the node of the key if it does not exist. In debug builds it also checks that the
+    /// key matches the string form of the information set and that no two information sets
+    /// share the same key.
+    fn insert_node(
+        &mut self,
+        key: InfoSetKey,
+        num_actions: usize,
+        info_set_str: impl FnOnce() -> (String, usize),
+    ) {
+        match self.nodes.entry(key) {
+            Entry::Vacant(entry) => {
+                let (info_set_str, prefix_len) = info_set_str();
+                debug_assert_eq!(
+                    key,
+                    InfoSetHasher::hash_str(&info_set_str),
+                    "info_set_key does not match info_set_str for {info_set_str}"
+                );
+                entry.insert(self.warm_start.new_node(key, num_actions));
+                self.info_sets.insert(key, &info_set_str, prefix_len);
+            }
+            Entry::Occupied(_) if cfg!(debug_assertions) => {
+                let (info_set_str, _) = info_set_str();
+                assert_eq!(
+                    self.info_sets.get(key),
+                    Some(info_set_str),
+                    "Information set key collision"
+                );
+            }
+            Entry::Occupied(_) => {}
         }
     }
 
@@ -270,70 +745,141 @@ impl Cfr {
         game: &mut G,
         cfr_method: CfrMethod,
         iterations: usize,
-        mut iteration_callback: F,
+        iteration_callback: F,
     ) where
         G: Game + Clone,
         G::Action: Eq + Copy,
         F: FnMut(&usize, &[f64]),
     {
+        self.train_iterations(game, cfr_method, 0..iterations, iteration_callback);
+    }
+
+    /// Runs the iterations in the range `iterations`. Iteration indices are global, so the
+    /// discounting blocks and the CFR+ weights of a training split in several ranges are the same
+    /// as if it had been run at once. The utility passed to the callback is averaged over the
+    /// iterations of the range.
+    pub fn train_iterations<G, F>(
+        &mut self,
+        game: &mut G,
+        cfr_method: CfrMethod,
+        iterations: Range<usize>,
+        iteration_callback: F,
+    ) where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+        F: FnMut(&usize, &[f64]),
+    {
+        self.train_cancellable(
+            game,
+            cfr_method,
+            iterations,
+            &AtomicBool::new(false),
+            iteration_callback,
+        );
+    }
+
+    /// Same as `Cfr::train_iterations`, but stops before starting a new iteration once `cancel`
+    /// is set. Returns the index of the first iteration that has not been run, which is
+    /// `iterations.end` if the training was not cancelled.
+    pub fn train_cancellable<G, F>(
+        &mut self,
+        game: &mut G,
+        cfr_method: CfrMethod,
+        iterations: Range<usize>,
+        cancel: &AtomicBool,
+        mut iteration_callback: F,
+    ) -> usize
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+        F: FnMut(&usize, &[f64]),
+    {
+        let first_iteration = iterations.start;
         let mut util = vec![0.; G::N_PLAYERS];
         let round_size = match cfr_method {
-            CfrMethod::Cfr | CfrMethod::CfrPlus => 1,
-            CfrMethod::ChanceSampling | CfrMethod::ExternalSampling | CfrMethod::FsiCfr => 100_000,
+            // CFR sin descuento. CFR+ ya acota los regrets en cero y pondera la estrategia media,
+            // así que tampoco se le aplica el descuento por bloques.
+            CfrMethod::Cfr | CfrMethod::CfrPlus => None,
+            CfrMethod::Dcfr { .. } | CfrMethod::LinearCfr => Some(self.discount_interval),
+            CfrMethod::ChanceSampling
+            | CfrMethod::ExternalSampling
+            | CfrMethod::FsiCfr
+            | CfrMethod::OutcomeSampling { .. } => Some(100_000),
         };
-        for i in 0..iterations {
+        for i in iterations.clone() {
+            if cancel.load(Ordering::Relaxed) {
+                return i;
+            }
             game.reset();
+            let prune = self.pruning.and_then(|pruning| pruning.threshold(i));
 
             match cfr_method {
                 CfrMethod::Cfr => {
                     for (player_idx, u) in util.iter_mut().enumerate() {
-                        *u += self.cfr(game, player_idx, 1., 1.);
+                        *u += self.cfr(game, player_idx, 1., 1., prune);
                     }
                 }
                 CfrMethod::CfrPlus => {
-                    todo!();
+                    // Ponderación lineal de la estrategia media: la iteración t pesa t.
+                    let weight = (i + 1) as f64;
+                    for (player_idx, u) in util.iter_mut().enumerate() {
+                        let mut updated = HashSet::new();
+                        *u += self.cfr_plus(game, player_idx, 1., 1., weight, &mut updated);
+                        for key in updated {
+                            if let Some(node) = self.nodes.get_mut(&key) {
+                                node.update_strategy_plus();
+                            }
+                        }
+                    }
                 }
-                CfrMethod::ChanceSampling => {
+                CfrMethod::ChanceSampling | CfrMethod::Dcfr { .. } | CfrMethod::LinearCfr => {
                     for (player_idx, u) in util.iter_mut().enumerate() {
-                        *u += self.chance_sampling(game, player_idx, 1., 1.);
+                        *u += self.chance_sampling(game, player_idx, 1., 1., prune);
                     }
                 }
                 CfrMethod::ExternalSampling => {
                     for (player_idx, u) in util.iter_mut().enumerate() {
-                        *u += self.external_sampling(game, player_idx);
+                        *u += self.external_sampling(game, player_idx, prune);
+                    }
+                }
+                CfrMethod::OutcomeSampling { epsilon } => {
+                    let mut reach = vec![1.; G::N_PLAYERS];
+                    for (player_idx, u) in util.iter_mut().enumerate() {
+                        let (utility, tail_reach) =
+                            self.outcome_sampling(game, player_idx, &mut reach, 1., epsilon);
+                        *u += utility * tail_reach;
                     }
                 }
                 CfrMethod::FsiCfr => {
                     let mut game_graph = GameGraph::new(game.clone());
-                    game_graph.inflate();
+                    game_graph.inflate(&mut self.rng);
                     for (player_idx, u) in util.iter_mut().enumerate() {
                         *u += self.fsicfr(&mut game_graph, player_idx);
                     }
                 }
             }
-            if i > 0 {
-                if i.is_multiple_of(round_size) {
-                    let block = (i / round_size) as f64;
-                    self.discount(block / (block + 1.));
-                }
-                // if i.is_multiple_of(round_size * 10) {
-                //     let exp = self.exploitability(game);
-                //     println!("Exploitability: {exp}");
-                // }
+            // El bloque t termina tras la iteración t * round_size.
+            if let Some(round_size) = round_size
+                && (i + 1).is_multiple_of(round_size)
+            {
+                let block = ((i + 1) / round_size) as f64;
+                let (positive, negative, strategy) = cfr_method.discount_factors(block);
+                self.discount(positive, negative, strategy);
             }
-            iteration_callback(&i, &util.iter().map(|u| u / i as f64).collect::<Vec<f64>>());
+            let n = (i + 1 - first_iteration) as f64;
+            iteration_callback(&i, &util.iter().map(|u| u / n).collect::<Vec<f64>>());
         }
+        iterations.end
     }
 
-    fn discount(&mut self, weight: f64) {
-        for value in self.nodes.values_mut() {
-            value.regret_sum.iter_mut().for_each(|r| *r *= weight);
-            value.strategy_sum.iter_mut().for_each(|r| *r *= weight);
-        }
+    fn discount(&mut self, positive: f64, negative: f64, strategy: f64) {
+        self.nodes
+            .values_mut()
+            .for_each(|node| node.discount(positive, negative, strategy));
     }
 
     /// Chance sampling CFR algorithm.
-    fn cfr<G>(&mut self, game: &mut G, player: usize, pi: f64, po: f64) -> f64
+    fn cfr<G>(&mut self, game: &mut G, player: usize, pi: f64, po: f64, prune: Option<f64>) -> f64
     where
         G: Game + Clone,
         G::Action: Eq + Copy,
@@ -343,7 +889,7 @@ impl Cfr {
                 return game
                     .new_iter()
                     .map(|(mut new_game, prob)| {
-                        prob * self.cfr(&mut new_game, player, pi, po * prob)
+                        prob * self.cfr(&mut new_game, player, pi, po * prob, prune)
                     })
                     .sum();
             }
@@ -353,14 +899,75 @@ impl Cfr {
             }
         };
         let actions: Vec<<G as Game>::Action> = game.actions();
-        let info_set_str = game.info_set_str(current_player);
-        let node = match self.nodes.get_mut(&info_set_str) {
-            Some(node) => node,
-            None => self
-                .nodes
-                .entry(info_set_str.clone())
-                .or_insert_with(|| Node::new(actions.len())),
+        let (info_set_key, node) = self.node_entry(game, current_player, actions.len());
+        let strategy = node.strategy().clone();
+        let pruned = node.pruned_actions(prune.filter(|_| current_player == player));
+
+        let util: Vec<Option<f64>> = actions
+            .iter()
+            .zip(strategy.iter())
+            .zip(pruned)
+            .map(|((a, s), pruned)| {
+                if pruned {
+                    return None;
+                }
+                let mut new_game = game.clone();
+                new_game.act(*a);
+                Some(if current_player == player {
+                    self.cfr(&mut new_game, player, pi * s, po, prune)
+                } else {
+                    self.cfr(&mut new_game, player, pi, po * s, prune)
+                })
+            })
+            .collect();
+        let node_util = expected_util(&util, &strategy);
+
+        if let Some(node) = self.nodes.get_mut(&info_set_key)
+            && current_player == player
+        {
+            update_regrets(&mut node.regret_sum, &util, node_util, po);
+            node.update_strategy_sum(pi);
+            node.update_strategy();
+        }
+
+        node_util
+    }
+
+    /// CFR+ algorithm. It traverses the whole tree like vanilla CFR, but the strategy is kept
+    /// fixed during the traversal: regrets are floored at zero and the strategy is updated with
+    /// `Node::update_strategy_plus` once the traversal of each player finishes, so updates
+    /// alternate between players. The keys of the nodes of `player` whose regrets change are
+    /// added to `updated`, so that only those are updated. The average strategy is weighted by
+    /// `weight`, the current iteration.
+    fn cfr_plus<G>(
+        &mut self,
+        game: &mut G,
+        player: usize,
+        pi: f64,
+        po: f64,
+        weight: f64,
+        updated: &mut HashSet<InfoSetKey>,
+    ) -> f64
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        let current_player = match game.current_player() {
+            NodeType::Chance => {
+                return game
+                    .new_iter()
+                    .map(|(mut new_game, prob)| {
+                        prob * self.cfr_plus(&mut new_game, player, pi, po * prob, weight, updated)
+                    })
+                    .sum();
+            }
+            NodeType::Player(current_player) => current_player,
+            NodeType::Terminal => {
+                return game.utility(player);
+            }
         };
+        let actions: Vec<<G as Game>::Action> = game.actions();
+        let (info_set_key, node) = self.node_entry(game, current_player, actions.len());
         let strategy = node.strategy().clone();
 
         let util: Vec<f64> = actions
@@ -370,29 +977,37 @@ impl Cfr {
                 let mut new_game = game.clone();
                 new_game.act(*a);
                 if current_player == player {
-                    self.cfr(&mut new_game, player, pi * s, po)
+                    self.cfr_plus(&mut new_game, player, pi * s, po, weight, updated)
                 } else {
-                    self.cfr(&mut new_game, player, pi, po * s)
+                    self.cfr_plus(&mut new_game, player, pi, po * s, weight, updated)
                 }
             })
             .collect();
-        let node_util = util.iter().zip(strategy.iter()).map(|(u, s)| u * s).sum();
+        let node_util: f64 = util.iter().zip(strategy.iter()).map(|(u, s)| u * s).sum();
 
-        if let Some(node) = self.nodes.get_mut(&info_set_str)
+        if let Some(node) = self.nodes.get_mut(&info_set_key)
             && current_player == player
         {
             node.regret_sum
                 .iter_mut()
                 .zip(util.iter())
                 .for_each(|(r, u)| *r += po * (u - node_util));
-            node.update_strategy_sum(pi);
-            node.update_strategy();
+            node.update_strategy_sum(weight * pi);
+            updated.insert(info_set_key);
         }
 
         node_util
     }
+
     /// Chance sampling CFR algorithm.
-    fn chance_sampling<G>(&mut self, game: &mut G, player: usize, pi: f64, po: f64) -> f64
+    fn chance_sampling<G>(
+        &mut self,
+        game: &mut G,
+        player: usize,
+        pi: f64,
+        po: f64,
+        prune: Option<f64>,
+    ) -> f64
     where
         G: Game + Clone,
         G::Action: Eq + Copy,
@@ -400,8 +1015,8 @@ impl Cfr {
         let current_player = match game.current_player() {
             NodeType::Chance => {
                 let mut new_game = game.clone();
-                new_game.new_random();
-                return self.chance_sampling(&mut new_game, player, pi, po);
+                new_game.new_random(&mut self.rng);
+                return self.chance_sampling(&mut new_game, player, pi, po, prune);
             }
             NodeType::Player(current_player) => current_player,
             NodeType::Terminal => {
@@ -409,38 +1024,33 @@ impl Cfr {
             }
         };
         let actions: Vec<<G as Game>::Action> = game.actions();
-        let info_set_str = game.info_set_str(current_player);
-        let node = match self.nodes.get_mut(&info_set_str) {
-            Some(node) => node,
-            None => self
-                .nodes
-                .entry(info_set_str.clone())
-                .or_insert_with(|| Node::new(actions.len())),
-        };
+        let (info_set_key, node) = self.node_entry(game, current_player, actions.len());
         let strategy = node.strategy().clone();
+        let pruned = node.pruned_actions(prune.filter(|_| current_player == player));
 
-        let util: Vec<f64> = actions
+        let util: Vec<Option<f64>> = actions
             .iter()
             .zip(strategy.iter())
-            .map(|(a, s)| {
+            .zip(pruned)
+            .map(|((a, s), pruned)| {
+                if pruned {
+                    return None;
+                }
                 let mut new_game = game.clone();
                 new_game.act(*a);
-                if current_player == player {
-                    self.chance_sampling(&mut new_game, player, pi * s, po)
+                Some(if current_player == player {
+                    self.chance_sampling(&mut new_game, player, pi * s, po, prune)
                 } else {
-                    self.chance_sampling(&mut new_game, player, pi, po * s)
-                }
+                    self.chance_sampling(&mut new_game, player, pi, po * s, prune)
+                })
             })
             .collect();
-        let node_util = util.iter().zip(strategy.iter()).map(|(u, s)| u * s).sum();
+        let node_util = expected_util(&util, &strategy);
 
-        if let Some(node) = self.nodes.get_mut(&info_set_str)
+        if let Some(node) = self.nodes.get_mut(&info_set_key)
             && current_player == player
         {
-            node.regret_sum
-                .iter_mut()
-                .zip(util.iter())
-                .for_each(|(r, u)| *r += po * (u - node_util));
+            update_regrets(&mut node.regret_sum, &util, node_util, po);
             node.update_strategy_sum(pi);
             node.update_strategy();
         }
@@ -449,7 +1059,7 @@ impl Cfr {
     }
 
     /// External sampling CFR algorithm.
-    fn external_sampling<G>(&mut self, game: &mut G, player: usize) -> f64
+    fn external_sampling<G>(&mut self, game: &mut G, player: usize, prune: Option<f64>) -> f64
     where
         G: Game + Clone,
         G::Action: Eq + Copy,
@@ -457,60 +1067,126 @@ impl Cfr {
         let current_player = match game.current_player() {
             NodeType::Chance => {
                 let mut new_game = game.clone();
-                new_game.new_random();
-                return self.external_sampling(&mut new_game, player);
+                new_game.new_random(&mut self.rng);
+                return self.external_sampling(&mut new_game, player, prune);
             }
             NodeType::Player(current_player) => current_player,
             NodeType::Terminal => {
                 return game.utility(player);
             }
         };
-        let info_set_str = game.info_set_str(current_player);
         let actions: Vec<<G as Game>::Action> = game.actions();
         if current_player == player {
-            let util: Vec<f64> = actions
+            let (_, node) = self.node_entry(game, current_player, actions.len());
+            let pruned = node.pruned_actions(prune);
+            let util: Vec<Option<f64>> = actions
                 .iter()
-                .map(|accion| {
-                    let mut new_game = game.clone();
-                    new_game.act(*accion);
-                    self.external_sampling(&mut new_game, player)
+                .zip(pruned)
+                .map(|(accion, pruned)| {
+                    (!pruned).then(|| {
+                        let mut new_game = game.clone();
+                        new_game.act(*accion);
+                        self.external_sampling(&mut new_game, player, prune)
+                    })
                 })
                 .collect();
-            let node = match self.nodes.get_mut(&info_set_str) {
-                Some(node) => node,
-                None => self
-                    .nodes
-                    .entry(info_set_str.clone())
-                    .or_insert_with(|| Node::new(actions.len())),
-            };
+            let (_, node) = self.node_entry(game, current_player, actions.len());
             let strategy = node.update_strategy();
 
-            let node_util = util.iter().zip(strategy.iter()).map(|(u, s)| u * s).sum();
-            node.regret_sum
-                .iter_mut()
-                .zip(util.iter())
-                .for_each(|(r, u)| *r += u - node_util);
+            let node_util = expected_util(&util, strategy);
+            update_regrets(&mut node.regret_sum, &util, node_util, 1.);
             node_util
         } else {
-            let node = match self.nodes.get_mut(&info_set_str) {
-                Some(node) => node,
-                None => self
-                    .nodes
-                    .entry(info_set_str.clone())
-                    .or_insert_with(|| Node::new(actions.len())),
-            };
-
+            let (key, node) = self.node_entry(game, current_player, actions.len());
             node.update_strategy();
             node.update_strategy_sum(1.);
-            let s = node.get_random_action();
+            let s = self.nodes[&key].get_random_action(&mut self.rng);
             let accion = actions.get(s).unwrap();
 
             let mut new_game = game.clone();
             new_game.act(*accion);
-            self.external_sampling(&mut new_game, player)
+            self.external_sampling(&mut new_game, player, prune)
         }
     }
 
+    /// Outcome sampling CFR algorithm. `reach` holds the probability with which each player plays
+    /// to the current history, its own contribution to the reach, and `q` is the probability of
+    /// sampling the current history. `reach` is restored before returning.
+    /// Returns the utility of the sampled terminal divided by the probability of sampling it and
+    /// the tail reach, the probability of going from the current history to that terminal when
+    /// every player follows the current strategy.
+    fn outcome_sampling<G>(
+        &mut self,
+        game: &mut G,
+        player: usize,
+        reach: &mut [f64],
+        q: f64,
+        epsilon: f64,
+    ) -> (f64, f64)
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        let current_player = match game.current_player() {
+            NodeType::Chance => {
+                let mut new_game = game.clone();
+                new_game.new_random(&mut self.rng);
+                return self.outcome_sampling(&mut new_game, player, reach, q, epsilon);
+            }
+            NodeType::Player(current_player) => current_player,
+            NodeType::Terminal => {
+                return (game.utility(player) / q, 1.);
+            }
+        };
+        let actions: Vec<<G as Game>::Action> = game.actions();
+        let (info_set_key, node) = self.node_entry(game, current_player, actions.len());
+        let strategy = node.update_strategy().clone();
+        let sample_probs: Vec<f64> = if current_player == player {
+            strategy
+                .iter()
+                .map(|s| epsilon / actions.len() as f64 + (1. - epsilon) * s)
+                .collect()
+        } else {
+            strategy.clone()
+        };
+        let a = WeightedIndex::new(&sample_probs)
+            .unwrap()
+            .sample(&mut self.rng);
+
+        let mut new_game = game.clone();
+        new_game.act(actions[a]);
+        let own_reach = reach[current_player];
+        reach[current_player] *= strategy[a];
+        let (util, tail_reach) =
+            self.outcome_sampling(&mut new_game, player, reach, q * sample_probs[a], epsilon);
+        reach[current_player] = own_reach;
+
+        let node = self.nodes.get_mut(&info_set_key).unwrap();
+        if current_player == player {
+            let po: f64 = reach
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != player)
+                .map(|(_, r)| r)
+                .product();
+            let w = util * po;
+            node.regret_sum.iter_mut().enumerate().for_each(|(i, r)| {
+                if i == a {
+                    *r += w * tail_reach * (1. - strategy[a]);
+                } else {
+                    *r -= w * tail_reach * strategy[a];
+                }
+            });
+        } else {
+            // Promedio ponderado estocásticamente: la contribución a la estrategia media es la
+            // probabilidad con la que el propio jugador llega a la historia, corregida con la
+            // de haberla muestreado.
+            node.update_strategy_sum(own_reach / q);
+        }
+
+        (util, tail_reach * strategy[a])
+    }
+
     fn fsicfr<G>(
         &mut self,
         game_graph: &mut GameGraph<G, CfrData>,
@@ -531,14 +1207,14 @@ impl Cfr {
                     let info_set_str = game_node
                         .info_set_str()
                         .expect("InfoSet must be valid in non terminal nodes.");
-                    let node = match self.nodes.get(info_set_str) {
-                        Some(node) => node,
-                        None => self
-                            .nodes
-                            .entry(info_set_str.to_string())
-                            .or_insert_with(|| Node::new(game.actions().len())),
-                    };
-                    let strategy = node.strategy();
+                    let key = InfoSetHasher::hash_str(info_set_str);
+                    self.insert_node(key, game.actions().len(), || {
+                        (
+                            info_set_str.to_owned(),
+                            game.info_set_prefix_len(current_player),
+                        )
+                    });
+                    let strategy = self.nodes[&key].strategy();
                     for (i, s) in strategy.iter().enumerate() {
                         let child_idx = game_graph.node(idx).children()[i];
                         let indices = [idx, child_idx];
@@ -577,7 +1253,10 @@ impl Cfr {
                         .node(idx)
                         .info_set_str()
                         .expect("InfoSet must be valid in non terminal nodes.");
-                    let node = self.nodes.get_mut(info_set_str).unwrap();
+                    let node = self
+                        .nodes
+                        .get_mut(&InfoSetHasher::hash_str(info_set_str))
+                        .unwrap();
                     let strategy = node.strategy();
 
                     let utility: Vec<f64> = game_graph
@@ -622,11 +1301,26 @@ impl Cfr {
         G: Game + Clone,
         G::Action: Eq + Copy,
     {
-        match game.current_player() {
+        self.expected_utility_cancellable(game, &AtomicBool::new(false))
+            .unwrap()
+    }
+
+    /// Same as `Cfr::expected_utility`, but it gives up the traversal and returns `None` once
+    /// `cancel` is set.
+    pub fn expected_utility_cancellable<G>(&self, game: &G, cancel: &AtomicBool) -> Option<Vec<f64>>
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let utility = match game.current_player() {
             NodeType::Chance => {
                 let mut utility = vec![0.; G::N_PLAYERS];
                 for (game, prob) in game.new_iter() {
-                    for (u, v) in utility.iter_mut().zip(self.expected_utility(&game)) {
+                    let child_utility = self.expected_utility_cancellable(&game, cancel)?;
+                    for (u, v) in utility.iter_mut().zip(child_utility) {
                         *u += prob * v;
                     }
                 }
@@ -634,11 +1328,7 @@ impl Cfr {
             }
             NodeType::Player(current_player) => {
                 let actions = game.actions();
-                let info_set_str = game.info_set_str(current_player);
-                let strategy = match self.nodes.get(&info_set_str) {
-                    Some(node) => node.get_average_strategy(),
-                    None => vec![1. / actions.len() as f64; actions.len()],
-                };
+                let strategy = self.average_strategy(game, current_player, actions.len());
                 let mut utility = vec![0.; G::N_PLAYERS];
                 for (action, prob) in actions.iter().zip(strategy) {
                     // Una acción con probabilidad nula no aporta nada a la suma, así que podar su
@@ -649,7 +1339,8 @@ impl Cfr {
                     }
                     let mut game = game.clone();
                     game.act(*action);
-                    for (u, v) in utility.iter_mut().zip(self.expected_utility(&game)) {
+                    let child_utility = self.expected_utility_cancellable(&game, cancel)?;
+                    for (u, v) in utility.iter_mut().zip(child_utility) {
                         *u += prob * v;
                     }
                 }
@@ -658,183 +1349,545 @@ impl Cfr {
             NodeType::Terminal => {
                 Vec::from_iter((0..G::N_PLAYERS).map(|player_idx| game.clone().utility(player_idx)))
             }
+        };
+        Some(utility)
+    }
+
+    /// Average strategy at the information set of `player`, or the uniform strategy if it has
+    /// not been visited during training.
+    pub(crate) fn average_strategy<G: Game>(
+        &self,
+        game: &G,
+        player: usize,
+        num_actions: usize,
+    ) -> Vec<f64> {
+        match self.nodes.get(&game.info_set_key(player)) {
+            Some(node) => node.get_average_strategy(),
+            None => vec![1. / num_actions as f64; num_actions],
         }
     }
 
-    pub fn exploitability<G>(&mut self, game: &mut G) -> f64
-    where
-        G: Game + Clone,
-        G::Action: Eq + Copy,
-    {
-        let info_sets = self.info_sets(game);
-        let mut br_strategies = HashMap::new();
-        br_strategies.reserve(self.nodes().len());
+    /// Iterates over the trained nodes and the string form of their information sets.
+    pub fn nodes(&self) -> impl Iterator<Item = (String, &Node)> {
+        self.nodes
+            .iter()
+            .map(|(key, node)| (self.info_sets.get(*key).unwrap(), node))
+    }
 
-        (0..G::N_PLAYERS)
-            .map(|player| self.best_response_value(game, player, &info_sets, &mut br_strategies))
-            .sum()
+    /// Node of the information set, if it has been visited during training.
+    pub fn node(&self, info_set: &str) -> Option<&Node> {
+        self.nodes.get(&InfoSetHasher::hash_str(info_set))
     }
 
-    pub fn best_response_value<G>(
-        &mut self,
-        game: &mut G,
-        player: usize,
-        info_sets: &HashMap<String, Vec<(G, f64)>>,
-        br_strategies: &mut HashMap<String, usize>,
-    ) -> f64
-    where
-        G: Game + Clone,
-        G::Action: Eq + Copy,
-    {
-        match game.current_player() {
-            NodeType::Chance => game
-                .new_iter()
-                .map(|(mut game, prob)| {
-                    prob * self.best_response_value(&mut game, player, info_sets, br_strategies)
-                })
-                .sum(),
-            NodeType::Player(current_player) => {
-                let actions = game.actions();
-                let info_set_str = game.info_set_str(current_player);
-                if player == current_player {
-                    let action_idx = match br_strategies.get(&info_set_str) {
-                        Some(action_idx) => action_idx,
-                        None => {
-                            let mut action_values = vec![0.; game.actions().len()];
-                            if let Some(games) = info_sets.get(&info_set_str) {
-                                games.iter().for_each(|(game, po)| {
-                                    game.actions().iter().enumerate().for_each(|(idx, action)| {
-                                        let mut new_game = game.clone();
-                                        new_game.act(*action);
-                                        let br = self.best_response_value(
-                                            &mut new_game,
-                                            player,
-                                            info_sets,
-                                            br_strategies,
-                                        );
-                                        action_values[idx] += po * br;
-                                    });
-                                });
-                            }
-                            let br_action = action_values
-                                .iter()
-                                .enumerate()
-                                .max_by(|(_, a), (_, b)| a.total_cmp(b))
-                                .map(|(idx, _)| idx)
-                                .unwrap();
-
-                            br_strategies.entry(info_set_str).or_insert(br_action)
-                        }
-                    };
-                    let best_action = actions[*action_idx];
-                    let mut game = game.clone();
-                    game.act(best_action);
-                    self.best_response_value(&mut game, player, info_sets, br_strategies)
-                } else {
-                    let node = self
-                        .nodes
-                        .entry(info_set_str)
-                        .or_insert_with(|| Node::new(actions.len()));
-                    let strategy = node.get_average_strategy();
-                    std::iter::zip(actions, strategy)
-                        .map(|(action, prob)| {
-                            if prob == 0. {
-                                return 0.;
-                            }
-                            let mut game = game.clone();
-                            game.act(action);
-                            prob * self.best_response_value(
-                                &mut game,
-                                player,
-                                info_sets,
-                                br_strategies,
-                            )
-                        })
-                        .sum()
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn update_strategy(&mut self) {
+        self.nodes.values_mut().for_each(|n| {
+            n.update_strategy();
+        });
+    }
+}
+
+impl Default for Cfr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Utility of a node given the utilities of its actions and the strategy. Pruned actions, with
+/// no utility, are not played by the strategy.
+pub(crate) fn expected_util(util: &[Option<f64>], strategy: &[f64]) -> f64 {
+    util.iter()
+        .zip(strategy)
+        .map(|(u, s)| u.unwrap_or(0.) * s)
+        .sum()
+}
+
+/// Adds the regrets of the actions weighted by `weight`, skipping the pruned ones.
+pub(crate) fn update_regrets(
+    regret_sum: &mut [f64],
+    util: &[Option<f64>],
+    node_util: f64,
+    weight: f64,
+) {
+    regret_sum.iter_mut().zip(util).for_each(|(r, u)| {
+        if let Some(u) = u {
+            *r += weight * (u - node_util);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum RpsAction {
+        Rock,
+        Paper,
+        Scissors,
+    }
+
+    /// Piedra, papel o tijera en el que ganar con piedra paga el doble. El equilibrio deja de ser
+    /// la estrategia uniforme, que es el punto de partida de CFR.
+    #[derive(Debug, Clone)]
+    struct BiasedRps {
+        history: Vec<RpsAction>,
+    }
+
+    impl Game for BiasedRps {
+        type Action = RpsAction;
+        const N_PLAYERS: usize = 2;
+
+        fn utility(&mut self, player: usize) -> f64 {
+            let payoff = match (self.history[0], self.history[1]) {
+                (RpsAction::Rock, RpsAction::Scissors) => 2.,
+                (RpsAction::Scissors, RpsAction::Rock) => -2.,
+                (RpsAction::Paper, RpsAction::Rock) | (RpsAction::Scissors, RpsAction::Paper) => 1.,
+                (RpsAction::Rock, RpsAction::Paper) | (RpsAction::Paper, RpsAction::Scissors) => {
+                    -1.
                 }
+                _ => 0.,
+            };
+            if player == 0 { payoff } else { -payoff }
+        }
+
+        fn info_set_str(&self, player: usize) -> String {
+            player.to_string()
+        }
+
+        fn history_str(&self) -> String {
+            format!("{:?}", self.history)
+        }
+
+        // El segundo jugador no ve la acción del primero.
+        fn public_state_key(&self) -> InfoSetKey {
+            self.history.len() as InfoSetKey
+        }
+
+        fn actions(&self) -> Vec<Self::Action> {
+            vec![RpsAction::Rock, RpsAction::Paper, RpsAction::Scissors]
+        }
+
+        fn current_player(&self) -> NodeType {
+            match self.history.len() {
+                0 | 1 => NodeType::Player(self.history.len()),
+                _ => NodeType::Terminal,
             }
-            NodeType::Terminal => game.utility(player),
+        }
+
+        fn act(&mut self, a: Self::Action) {
+            self.history.push(a);
+        }
+
+        fn new_random<R: Rng + ?Sized>(&mut self, _rng: &mut R) {}
+
+        fn reset(&mut self) {
+            self.history.clear();
+        }
+
+        fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
+            std::iter::empty()
         }
     }
 
-    fn info_sets<G>(&mut self, game: &mut G) -> HashMap<String, Vec<(G, f64)>>
+    fn exploitability_after<G>(game: &mut G, cfr_method: CfrMethod, iterations: usize) -> f64
     where
         G: Game + Clone,
         G::Action: Eq + Copy,
     {
-        let mut info_sets = HashMap::new();
-        info_sets.reserve(self.nodes().len());
+        let mut cfr = Cfr::new();
+        cfr.train(game, cfr_method, iterations, |_, _| {});
+        game.reset();
+        cfr.exploitability(game)
+    }
 
-        for player in 0..G::N_PLAYERS {
-            self.info_sets_player(game, player, 1., &mut info_sets);
+    #[test]
+    fn uniform_exploitability() {
+        // Contra la estrategia uniforme la mejor respuesta juega piedra y gana 1/3 de media.
+        let mut game = BiasedRps { history: vec![] };
+        let cfr = Cfr::new();
+        assert!((cfr.best_response_value(&game, 0) - 1. / 3.).abs() < 1e-12);
+        assert!((cfr.exploitability(&mut game) - 2. / 3.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn cfr_plus_rps() {
+        let mut game = BiasedRps { history: vec![] };
+        for iterations in [200, 1000] {
+            let cfr = exploitability_after(&mut game, CfrMethod::Cfr, iterations);
+            let cfr_plus = exploitability_after(&mut game, CfrMethod::CfrPlus, iterations);
+            assert!(
+                cfr_plus < cfr,
+                "{iterations} iterations. CFR+: {cfr_plus}, CFR: {cfr}"
+            );
         }
+    }
 
-        info_sets
+    #[test]
+    fn parse_parametrized_methods() {
+        assert!(matches!(
+            "dcfr".parse::<CfrMethod>(),
+            Ok(CfrMethod::Dcfr {
+                alpha: 1.5,
+                beta: 0.,
+                gamma: 2.
+            })
+        ));
+        assert!(matches!(
+            "dcfr:1:0.5:3".parse::<CfrMethod>(),
+            Ok(CfrMethod::Dcfr {
+                alpha: 1.,
+                beta: 0.5,
+                gamma: 3.
+            })
+        ));
+        assert!(matches!(
+            "linear-cfr".parse::<CfrMethod>(),
+            Ok(CfrMethod::LinearCfr)
+        ));
+        assert!("dcfr:1:0.5".parse::<CfrMethod>().is_err());
+        assert!("dcfr:a:b:c".parse::<CfrMethod>().is_err());
+        assert!(matches!(
+            "outcome-sampling:0.4".parse::<CfrMethod>(),
+            Ok(CfrMethod::OutcomeSampling { epsilon: 0.4 })
+        ));
+        assert!("outcome-sampling:1.5".parse::<CfrMethod>().is_err());
     }
 
-    fn info_sets_player<G>(
-        &mut self,
-        game: &mut G,
-        player: usize,
-        po: f64,
-        info_sets: &mut HashMap<String, Vec<(G, f64)>>,
-    ) where
-        G: Game + Clone,
-        G::Action: Eq + Copy,
-    {
-        match game.current_player() {
-            NodeType::Chance => {
-                game.new_iter().for_each(|(mut game, prob)| {
-                    self.info_sets_player(&mut game, player, po * prob, info_sets);
-                });
+    #[test]
+    fn dcfr_discount() {
+        let mut node = Node::new(2);
+        node.regret_sum = vec![4., -4.];
+        node.strategy_sum = vec![1., 3.];
+        let mut cfr = Cfr::from_nodes(HashMap::from([("0".to_owned(), node)]));
+
+        let approx_eq = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-12);
+        let (positive, negative, strategy) = CfrMethod::DCFR_DEFAULT.discount_factors(1.);
+        assert!(approx_eq(
+            &[positive, negative, strategy],
+            &[0.5, 0.5, 0.25]
+        ));
+        let (positive, negative, strategy) = CfrMethod::DCFR_DEFAULT.discount_factors(4.);
+        assert!(approx_eq(
+            &[positive, negative, strategy],
+            &[8. / 9., 0.5, 0.64]
+        ));
+        cfr.discount(positive, negative, strategy);
+
+        let node = cfr.node("0").unwrap();
+        assert!(approx_eq(&node.regret_sum, &[32. / 9., -2.]));
+        assert!(approx_eq(&node.strategy_sum, &[0.64, 1.92]));
+
+        let linear = CfrMethod::LinearCfr.discount_factors(3.);
+        assert_eq!(linear, (0.75, 0.75, 0.75));
+        let dcfr_linear = CfrMethod::Dcfr {
+            alpha: 1.,
+            beta: 1.,
+            gamma: 1.,
+        }
+        .discount_factors(3.);
+        assert_eq!(linear, dcfr_linear);
+    }
+
+    #[test]
+    fn discount_interval() {
+        // La iteración t de Linear CFR pesa t en la estrategia media. Con bloques de dos
+        // iteraciones, las dos primeras pesan igual.
+        let strategy_weight = |cfr: Cfr| cfr.node("0").unwrap().strategy_sum.iter().sum::<f64>();
+        let mut game = BiasedRps { history: vec![] };
+        let mut cfr = Cfr::new();
+        cfr.train(&mut game, CfrMethod::LinearCfr, 3, |_, _| {});
+        assert!((strategy_weight(cfr) - (1. + 2. + 3.) / 4.).abs() < 1e-12);
+
+        let mut cfr = Cfr::new().with_discount_interval(2);
+        cfr.train(&mut game, CfrMethod::LinearCfr, 3, |_, _| {});
+        assert!((strategy_weight(cfr) - (1. + 1. + 2.) / 2.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn outcome_sampling_rps() {
+        let mut game = BiasedRps { history: vec![] };
+        let method = CfrMethod::OUTCOME_SAMPLING_DEFAULT;
+        let exploitability = exploitability_after(&mut game, method, 20_000);
+        // La estrategia uniforme de partida tiene una explotabilidad de 2/3.
+        assert!(exploitability < 0.1, "Exploitability: {exploitability}");
+    }
+
+    /// Juego de tres jugadores sin utilidad: el jugador 1 elige y el 2 elige sin verlo. El jugador
+    /// 0 no juega.
+    #[derive(Debug, Clone)]
+    struct ThreePlayers {
+        history: Vec<RpsAction>,
+    }
+
+    impl Game for ThreePlayers {
+        type Action = RpsAction;
+        const N_PLAYERS: usize = 3;
+
+        fn utility(&mut self, _player: usize) -> f64 {
+            0.
+        }
+
+        fn info_set_str(&self, player: usize) -> String {
+            player.to_string()
+        }
+
+        fn history_str(&self) -> String {
+            format!("{:?}", self.history)
+        }
+
+        fn actions(&self) -> Vec<Self::Action> {
+            vec![RpsAction::Rock, RpsAction::Paper]
+        }
+
+        fn current_player(&self) -> NodeType {
+            match self.history.len() {
+                0 | 1 => NodeType::Player(self.history.len() + 1),
+                _ => NodeType::Terminal,
             }
-            NodeType::Player(current_player) => {
-                if player == current_player {
-                    let info_set_str = game.info_set_str(current_player);
-                    let info_set = info_sets
-                        .entry(info_set_str)
-                        .or_insert_with(|| Vec::with_capacity(500));
-                    info_set.push((game.clone(), po));
-                }
-                let actions = game.actions();
-                if player == current_player {
-                    for action in actions {
-                        let mut next_game = game.clone();
-                        next_game.act(action);
-                        self.info_sets_player(&mut next_game, player, po, info_sets);
-                    }
-                } else {
-                    let n_actions = actions.len();
-                    let info_set_str = game.info_set_str(current_player);
-                    let node = self
-                        .nodes
-                        .entry(info_set_str)
-                        .or_insert_with(|| Node::new(n_actions));
-                    let strategy = node.get_average_strategy();
-                    for (action, prob) in std::iter::zip(actions, strategy) {
-                        let mut next_game = game.clone();
-                        next_game.act(action);
-                        self.info_sets_player(&mut next_game, player, po * prob, info_sets);
-                    }
+        }
+
+        fn act(&mut self, a: Self::Action) {
+            self.history.push(a);
+        }
+
+        fn new_random<R: Rng + ?Sized>(&mut self, _rng: &mut R) {}
+
+        fn reset(&mut self) {
+            self.history.clear();
+        }
+
+        fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
+            std::iter::empty()
+        }
+    }
+
+    #[test]
+    fn outcome_sampling_own_reach() {
+        // Sin utilidad la estrategia sigue siendo la uniforme. El jugador 2 llega a sus dos
+        // historias con probabilidad 1, así que cada vez que se muestrea una de las dos su
+        // estrategia media pesa 1 / (1/2), aunque el jugador 1 haya elegido con probabilidad 1/2.
+        let mut game = ThreePlayers { history: vec![] };
+        let mut cfr = Cfr::new().with_seed(1);
+        cfr.train(
+            &mut game,
+            CfrMethod::OutcomeSampling { epsilon: 0.6 },
+            1,
+            |_, _| {},
+        );
+        let strategy_weight =
+            |info_set| cfr.node(info_set).unwrap().strategy_sum.iter().sum::<f64>();
+        // La estrategia media del jugador 1 se acumula al actualizar a los jugadores 0 y 2, y la
+        // del 2 al actualizar a los jugadores 0 y 1.
+        assert!((strategy_weight("1") - 2.).abs() < 1e-12);
+        assert!((strategy_weight("2") - 4.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn seeded_training() {
+        let game = BiasedRps { history: vec![] };
+        for method in [
+            CfrMethod::ExternalSampling,
+            CfrMethod::OUTCOME_SAMPLING_DEFAULT,
+        ] {
+            let train = |seed| {
+                let mut cfr = Cfr::new().with_seed(seed);
+                cfr.train(&mut game.clone(), method, 200, |_, _| {});
+                cfr
+            };
+            let (cfr, same_seed, other_seed) = (train(1), train(1), train(2));
+            for (info_set, node) in cfr.nodes() {
+                assert_eq!(
+                    node.regret_sum,
+                    same_seed.node(&info_set).unwrap().regret_sum
+                );
+            }
+            assert!(cfr.nodes().any(|(info_set, node)| node.regret_sum
+                != other_seed.node(&info_set).unwrap().regret_sum));
+        }
+    }
+
+    #[test]
+    fn info_set_names() {
+        let mut names = InfoSetNames::default();
+        names.insert(1, "0,G,", 2);
+        names.insert(2, "", 0);
+        names.insert(3, "1,G,p", 2);
+        names.insert(5, "0,G,p", 2);
+        names.insert(1, "otro", 2);
+        assert_eq!(names.get(1).as_deref(), Some("0,G,"));
+        assert_eq!(names.get(2).as_deref(), Some(""));
+        assert_eq!(names.get(3).as_deref(), Some("1,G,p"));
+        assert_eq!(names.get(5).as_deref(), Some("0,G,p"));
+        assert_eq!(names.get(4), None);
+        assert_eq!(names.buffer, "0,G,1,G,p");
+    }
+
+    #[test]
+    fn resume_with_rng_state() {
+        let mut game = BiasedRps { history: vec![] };
+        let mut cfr = Cfr::new().with_seed(5);
+        cfr.train(&mut game, CfrMethod::ExternalSampling, 200, |_, _| {});
+
+        let mut first_half = Cfr::new().with_seed(5);
+        first_half.train(&mut game, CfrMethod::ExternalSampling, 100, |_, _| {});
+        let mut resumed = Cfr::from_nodes(
+            first_half
+                .nodes()
+                .map(|(info_set, node)| (info_set, node.clone()))
+                .collect(),
+        );
+        resumed.set_rng_state(&first_half.rng_state());
+        resumed.train_iterations(&mut game, CfrMethod::ExternalSampling, 100..200, |_, _| {});
+        for (info_set, node) in cfr.nodes() {
+            assert_eq!(node.regret_sum, resumed.node(&info_set).unwrap().regret_sum);
+        }
+    }
+
+    #[test]
+    fn train_cancellable() {
+        let mut game = BiasedRps { history: vec![] };
+        let mut cfr = Cfr::new();
+        cfr.train(&mut game, CfrMethod::Cfr, 11, |_, _| {});
+
+        let mut cancelled = Cfr::new();
+        let cancel = AtomicBool::new(false);
+        let end =
+            cancelled.train_cancellable(&mut game, CfrMethod::Cfr, 0..100, &cancel, |i, _| {
+                if *i == 10 {
+                    cancel.store(true, Ordering::Relaxed);
                 }
+            });
+        assert_eq!(end, 11);
+        for (info_set, node) in cfr.nodes() {
+            assert_eq!(
+                node.get_average_strategy(),
+                cancelled.node(&info_set).unwrap().get_average_strategy()
+            );
+        }
+    }
+
+    #[test]
+    fn cancel_evaluation() {
+        let mut game = BiasedRps { history: vec![] };
+        let mut cfr = Cfr::new();
+        cfr.train(&mut game, CfrMethod::Cfr, 100, |_, _| {});
+
+        let running = AtomicBool::new(false);
+        assert_eq!(
+            cfr.expected_utility_cancellable(&game, &running),
+            Some(cfr.expected_utility(&game))
+        );
+        assert_eq!(
+            cfr.exploitability_cancellable(&mut game, &running),
+            Some(cfr.exploitability(&mut game))
+        );
+        let cancel = AtomicBool::new(true);
+        assert_eq!(cfr.expected_utility_cancellable(&game, &cancel), None);
+        assert_eq!(cfr.exploitability_cancellable(&mut game, &cancel), None);
+    }
+
+    #[test]
+    fn train_iterations_resume() {
+        let mut game = BiasedRps { history: vec![] };
+        for method in [CfrMethod::Cfr, CfrMethod::CfrPlus] {
+            let mut cfr = Cfr::new();
+            cfr.train(&mut game, method, 100, |_, _| {});
+
+            let mut resumed = Cfr::new();
+            resumed.train_iterations(&mut game, method, 0..40, |_, _| {});
+            let mut resumed = Cfr::from_nodes(
+                resumed
+                    .nodes()
+                    .map(|(info_set, node)| (info_set, node.clone()))
+                    .collect(),
+            );
+            resumed.train_iterations(&mut game, method, 40..100, |_, _| {});
+
+            for (info_set, node) in cfr.nodes() {
+                let resumed_node = resumed.node(&info_set).unwrap();
+                assert_eq!(node.regret_sum, resumed_node.regret_sum);
+                assert_eq!(
+                    node.get_average_strategy(),
+                    resumed_node.get_average_strategy()
+                );
             }
-            NodeType::Terminal => {}
         }
     }
 
-    pub fn nodes(&self) -> &HashMap<String, Node> {
-        &self.nodes
+    #[test]
+    fn regret_pruning() {
+        let mut node = Node::new(3);
+        node.regret_sum = vec![5., -10., -0.5];
+        assert_eq!(node.pruned_actions(Some(-1.)), vec![false, true, false]);
+        assert_eq!(node.pruned_actions(None), vec![false; 3]);
+        // Sin regrets positivos la estrategia es uniforme y no se poda nada.
+        node.regret_sum = vec![-5., -10., -0.5];
+        assert_eq!(node.pruned_actions(Some(-1.)), vec![false; 3]);
+
+        let pruning = RegretPruning {
+            threshold: -20.,
+            warmup: 10,
+            full_pass_every: 5,
+        };
+        assert_eq!(pruning.threshold(3), None);
+        assert_eq!(pruning.threshold(15), None);
+        assert_eq!(pruning.threshold(16), Some(-20.));
+
+        // Se fuerza un regret muy negativo para papel en el nodo del primer jugador. Con la poda
+        // activa ese regret no cambia, y en los recorridos completos sí.
+        let pruning = RegretPruning {
+            warmup: 0,
+            full_pass_every: 2,
+            ..pruning
+        };
+        let mut game = BiasedRps { history: vec![] };
+        for method in [CfrMethod::ChanceSampling, CfrMethod::ExternalSampling] {
+            for (iteration, pruned) in [(1, true), (2, false)] {
+                let mut cfr = Cfr::new().with_seed(1).with_pruning(pruning);
+                cfr.train(&mut game, method, 1, |_, _| {});
+                let node = cfr.nodes.get_mut(&InfoSetHasher::hash_str("0")).unwrap();
+                node.regret_sum = vec![10., -100., 0.];
+                node.update_strategy();
+                cfr.train_iterations(&mut game, method, iteration..iteration + 1, |_, _| {});
+                let regret = cfr.node("0").unwrap().regret_sum[1];
+                assert_eq!(regret == -100., pruned, "{method:?}, iteración {iteration}");
+            }
+        }
     }
 
-    pub fn update_strategy(&mut self) {
-        self.nodes.values_mut().for_each(|n| {
-            n.update_strategy();
-        });
+    #[test]
+    fn warm_start() {
+        let mut game = BiasedRps { history: vec![] };
+        let strategies = [
+            ("0".to_owned(), vec![1., 0., 0.]),
+            // Cambia el número de acciones: se ignora.
+            ("1".to_owned(), vec![0.5, 0.5]),
+            ("2".to_owned(), vec![0., 1., 0.]),
+        ];
+        let mut cfr = Cfr::new().with_warm_start(strategies, 1000.);
+        cfr.train(&mut game, CfrMethod::ChanceSampling, 1, |_, _| {});
+
+        assert_eq!(cfr.num_nodes(), 2);
+        let strategy = cfr.node("0").unwrap().get_average_strategy();
+        assert!(strategy[0] > 0.99, "{strategy:?}");
+        assert_eq!(cfr.node("1").unwrap().regret_sum.len(), 3);
     }
-}
 
-impl Default for Cfr {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn info_set_hasher() {
+        use std::fmt::Write;
+
+        let key = InfoSetHasher::hash_str("38:37,RRR5,Mnpppo");
+        let mut hasher = InfoSetHasher::new();
+        hasher.write_str("38:37,").write_str("RRR5,");
+        let mus = 'n';
+        let _ = write!(hasher, "M{mus}ppp");
+        hasher.write_str("o");
+        assert_eq!(hasher.finish(), key);
+        assert_ne!(InfoSetHasher::hash_str("38:37,RRR5,Mnpppp"), key);
     }
 }
@@ -3,7 +3,7 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum GameError {
     #[error(
-        "Invalid CFR method: {0}. Accepted methods are: cfr, cfr-plus, external-sampling and chance-sampling."
+        "Invalid CFR method: {0}. Accepted methods are: cfr, cfr-plus, external-sampling, chance-sampling, fsi-cfr, linear-cfr, dcfr and outcome-sampling. The last two accept parameters, e.g. dcfr:1.5:0:2 or outcome-sampling:0.6."
     )]
     InvalidCfrMethod(String),
 }
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, VecDeque, hash_map::Entry};
 
 use arrayvec::{ArrayString, ArrayVec};
+use rand::Rng;
 
 use crate::Game;
 
@@ -75,10 +76,11 @@ where
         }
     }
 
-    pub fn inflate(&mut self) {
+    /// Expands the graph from the root. Each chance node gets a single child sampled with `rng`.
+    pub fn inflate<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         let mut game_list = VecDeque::from([0]);
         while let Some(parent_idx) = game_list.pop_front() {
-            self.next_nodes(parent_idx, &mut game_list);
+            self.next_nodes(parent_idx, &mut game_list, rng);
         }
     }
 
@@ -102,12 +104,17 @@ where
         self.game_nodes.len()
     }
 
-    fn next_nodes(&mut self, idx: usize, new_nodes: &mut VecDeque<usize>) {
+    fn next_nodes<R: Rng + ?Sized>(
+        &mut self,
+        idx: usize,
+        new_nodes: &mut VecDeque<usize>,
+        rng: &mut R,
+    ) {
         let game = &self.game_nodes[idx].game;
         match game.current_player() {
             NodeType::Chance => {
                 let mut new_game = self.game_nodes[idx].game.clone();
-                new_game.new_random();
+                new_game.new_random(rng);
 
                 if let Some(child_idx) = self.append_child(idx, new_game) {
                     new_nodes.push_back(child_idx);
@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use super::{
+    Cfr, CfrMethod, Game, InfoSetHasher, InfoSetKey, Node, NodeType,
+    cfr::{InfoSetNames, WarmStart, expected_util, update_regrets},
+};
+
+/// Node table shared by the training threads. Nodes are split into shards, each behind its own
+/// lock, so threads only contend when they access nodes of the same shard.
+struct NodeTable {
+    shards: Vec<Mutex<HashMap<InfoSetKey, Node>>>,
+    /// Only locked when a node is created and, in debug builds, when keys are checked.
+    info_sets: Mutex<InfoSetNames>,
+    warm_start: WarmStart,
+}
+
+impl NodeTable {
+    fn new(cfr: &mut Cfr, num_shards: usize) -> Self {
+        let shards: Vec<Mutex<HashMap<InfoSetKey, Node>>> = (0..num_shards)
+            .map(|_| Mutex::new(HashMap::new()))
+            .collect();
+        for (key, node) in cfr.nodes.drain() {
+            let idx = Self::shard_idx(key, num_shards);
+            shards[idx].lock().unwrap().insert(key, node);
+        }
+        Self {
+            shards,
+            info_sets: Mutex::new(std::mem::take(&mut cfr.info_sets)),
+            warm_start: std::mem::take(&mut cfr.warm_start),
+        }
+    }
+
+    fn shard_idx(key: InfoSetKey, num_shards: usize) -> usize {
+        // The key is already a hash of the info set.
+        (key % num_shards as InfoSetKey) as usize
+    }
+
+    /// Runs `f` on the node of the info set of `player`, creating it if it does not exist. Like
+    /// `Cfr`, debug builds check that the key matches the text representation.
+    fn with_node<G: Game, R>(
+        &self,
+        game: &G,
+        player: usize,
+        num_actions: usize,
+        f: impl FnOnce(&mut Node) -> R,
+    ) -> R {
+        let key = game.info_set_key(player);
+        let idx = Self::shard_idx(key, self.shards.len());
+        let mut shard = self.shards[idx].lock().unwrap();
+        let node = shard.entry(key).or_insert_with(|| {
+            let info_set = game.info_set_str(player);
+            debug_assert_eq!(key, InfoSetHasher::hash_str(&info_set));
+            let prefix_len = game.info_set_prefix_len(player);
+            self.info_sets
+                .lock()
+                .unwrap()
+                .insert(key, &info_set, prefix_len);
+            self.warm_start.new_node(key, num_actions)
+        });
+        if cfg!(debug_assertions) {
+            assert_eq!(
+                self.info_sets.lock().unwrap().get(key),
+                Some(game.info_set_str(player)),
+                "Information set key collision"
+            );
+        }
+        f(node)
+    }
+
+    fn discount(&self, positive: f64, negative: f64, strategy: f64) {
+        for shard in &self.shards {
+            shard
+                .lock()
+                .unwrap()
+                .values_mut()
+                .for_each(|node| node.discount(positive, negative, strategy));
+        }
+    }
+
+    fn into_cfr(self, cfr: &mut Cfr) {
+        cfr.warm_start = self.warm_start;
+        cfr.info_sets = self.info_sets.into_inner().unwrap();
+        for shard in self.shards {
+            cfr.nodes.extend(shard.into_inner().unwrap());
+        }
+    }
+}
+
+impl Cfr {
+    /// Number of shards of the node table per training thread.
+    const SHARDS_PER_THREAD: usize = 16;
+
+    /// Trains in parallel with `threads` threads sharing the node table. Each thread takes
+    /// iterations from a shared counter until the range `iterations` is complete, so the callback
+    /// receives the iterations in the order they finish. Only chance sampling and external
+    /// sampling can be trained in parallel; other methods, or `threads <= 1`, are trained with
+    /// `Cfr::train_cancellable`.
+    ///
+    /// Once `cancel` is set, threads finish their current iteration and do not start another one.
+    /// Returns the index of the first iteration that has not been run, which is `iterations.end`
+    /// if the training was not cancelled.
+    pub fn train_parallel<G, F>(
+        &mut self,
+        game: &mut G,
+        cfr_method: CfrMethod,
+        iterations: Range<usize>,
+        threads: usize,
+        cancel: &AtomicBool,
+        mut iteration_callback: F,
+    ) -> usize
+    where
+        G: Game + Clone + Send,
+        G::Action: Eq + Copy,
+        F: FnMut(&usize, &[f64]),
+    {
+        if threads <= 1
+            || !matches!(
+                cfr_method,
+                CfrMethod::ChanceSampling | CfrMethod::ExternalSampling
+            )
+        {
+            return self.train_cancellable(
+                game,
+                cfr_method,
+                iterations,
+                cancel,
+                iteration_callback,
+            );
+        }
+        let round_size = 100_000;
+        let node_table = NodeTable::new(self, threads * Self::SHARDS_PER_THREAD);
+        let next_iteration = AtomicUsize::new(iterations.start);
+        let pruning = self.pruning;
+        let (tx, rx) = mpsc::channel::<(usize, Vec<f64>)>();
+
+        thread::scope(|s| {
+            for _ in 0..threads {
+                let tx = tx.clone();
+                let mut game = game.clone();
+                let mut rng = StdRng::seed_from_u64(self.rng.r#gen());
+                let node_table = &node_table;
+                let next_iteration = &next_iteration;
+                s.spawn(move || {
+                    loop {
+                        // Checked before taking an iteration, so that every iteration taken is
+                        // completed and the iterations run are a contiguous range.
+                        if cancel.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let i = next_iteration.fetch_add(1, Ordering::Relaxed);
+                        if i >= iterations.end {
+                            break;
+                        }
+                        game.reset();
+                        let prune = pruning.and_then(|pruning| pruning.threshold(i));
+                        let util: Vec<f64> = (0..G::N_PLAYERS)
+                            .map(|player_idx| match cfr_method {
+                                CfrMethod::ExternalSampling => external_sampling(
+                                    node_table, &mut game, player_idx, prune, &mut rng,
+                                ),
+                                _ => chance_sampling(
+                                    node_table, &mut game, player_idx, 1., 1., prune, &mut rng,
+                                ),
+                            })
+                            .collect();
+                        if (i + 1).is_multiple_of(round_size) {
+                            let block = ((i + 1) / round_size) as f64;
+                            let (positive, negative, strategy) = cfr_method.discount_factors(block);
+                            node_table.discount(positive, negative, strategy);
+                        }
+                        if tx.send((i, util)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+
+            let mut util = vec![0.; G::N_PLAYERS];
+            for (n, (i, iteration_util)) in rx.into_iter().enumerate() {
+                util.iter_mut()
+                    .zip(iteration_util)
+                    .for_each(|(u, iu)| *u += iu);
+                let n = (n + 1) as f64;
+                iteration_callback(&i, &util.iter().map(|u| u / n).collect::<Vec<f64>>());
+            }
+        });
+
+        node_table.into_cfr(self);
+        next_iteration.into_inner().min(iterations.end)
+    }
+}
+
+/// Chance sampling on the shared node table. The strategy of each node is read when the node is
+/// visited and updated on the way back, so other threads may have changed it in between.
+fn chance_sampling<G>(
+    node_table: &NodeTable,
+    game: &mut G,
+    player: usize,
+    pi: f64,
+    po: f64,
+    prune: Option<f64>,
+    rng: &mut StdRng,
+) -> f64
+where
+    G: Game + Clone,
+    G::Action: Eq + Copy,
+{
+    let current_player = match game.current_player() {
+        NodeType::Chance => {
+            let mut new_game = game.clone();
+            new_game.new_random(rng);
+            return chance_sampling(node_table, &mut new_game, player, pi, po, prune, rng);
+        }
+        NodeType::Player(current_player) => current_player,
+        NodeType::Terminal => {
+            return game.utility(player);
+        }
+    };
+    let actions: Vec<<G as Game>::Action> = game.actions();
+    let (strategy, pruned) = node_table.with_node(game, current_player, actions.len(), |node| {
+        let pruned = node.pruned_actions(prune.filter(|_| current_player == player));
+        (node.strategy().clone(), pruned)
+    });
+
+    let util: Vec<Option<f64>> = actions
+        .iter()
+        .zip(strategy.iter())
+        .zip(pruned)
+        .map(|((a, s), pruned)| {
+            if pruned {
+                return None;
+            }
+            let mut new_game = game.clone();
+            new_game.act(*a);
+            Some(if current_player == player {
+                chance_sampling(node_table, &mut new_game, player, pi * s, po, prune, rng)
+            } else {
+                chance_sampling(node_table, &mut new_game, player, pi, po * s, prune, rng)
+            })
+        })
+        .collect();
+    let node_util = expected_util(&util, &strategy);
+
+    if current_player == player {
+        node_table.with_node(game, current_player, actions.len(), |node| {
+            update_regrets(&mut node.regret_sum, &util, node_util, po);
+            node.update_strategy_sum(pi);
+            node.update_strategy();
+        });
+    }
+
+    node_util
+}
+
+/// External sampling on the shared node table.
+fn external_sampling<G>(
+    node_table: &NodeTable,
+    game: &mut G,
+    player: usize,
+    prune: Option<f64>,
+    rng: &mut StdRng,
+) -> f64
+where
+    G: Game + Clone,
+    G::Action: Eq + Copy,
+{
+    let current_player = match game.current_player() {
+        NodeType::Chance => {
+            let mut new_game = game.clone();
+            new_game.new_random(rng);
+            return external_sampling(node_table, &mut new_game, player, prune, rng);
+        }
+        NodeType::Player(current_player) => current_player,
+        NodeType::Terminal => {
+            return game.utility(player);
+        }
+    };
+    let actions: Vec<<G as Game>::Action> = game.actions();
+    if current_player == player {
+        let pruned = node_table.with_node(game, current_player, actions.len(), |node| {
+            node.pruned_actions(prune)
+        });
+        let util: Vec<Option<f64>> = actions
+            .iter()
+            .zip(pruned)
+            .map(|(accion, pruned)| {
+                (!pruned).then(|| {
+                    let mut new_game = game.clone();
+                    new_game.act(*accion);
+                    external_sampling(node_table, &mut new_game, player, prune, rng)
+                })
+            })
+            .collect();
+        node_table.with_node(game, current_player, actions.len(), |node| {
+            // Other threads may have changed the regrets during the traversal, so a pruned action
+            // could now have a positive probability. Its utility is unknown and it is ignored in
+            // this iteration.
+            let strategy = node.update_strategy();
+            let node_util = expected_util(&util, strategy);
+            update_regrets(&mut node.regret_sum, &util, node_util, 1.);
+            node_util
+        })
+    } else {
+        let s = node_table.with_node(game, current_player, actions.len(), |node| {
+            node.update_strategy();
+            node.update_strategy_sum(1.);
+            node.get_random_action(rng)
+        });
+        let accion = actions.get(s).unwrap();
+
+        let mut new_game = game.clone();
+        new_game.act(*accion);
+        external_sampling(node_table, &mut new_game, player, prune, rng)
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use itertools::Either;
+use rand::Rng;
+
+use super::{Game, InfoSetKey, NodeType};
+
+/// Wraps a game replacing its initial chance node with a fixed sample of its outcomes.
+///
+/// Traversing every deal of the larger games is not feasible, so the best response is computed
+/// on the subgame made of `samples` random deals, each with the same probability. The chance
+/// nodes after the first one are still enumerated by the wrapped game.
+///
+/// The exploitability of the sampled game is only an upper-biased proxy of the exploitability of
+/// the full game, not an unbiased estimate: when an information set is reached by few of the
+/// sampled deals, the best response learns the hidden cards of its opponents and wins more than
+/// it would against the real deal distribution. The bias shrinks as `samples` grows compared to
+/// the number of deals that share an information set. Games small enough to traverse should use
+/// the exact `Cfr::exploitability`.
+///
+/// The sample is drawn once, so evaluations of the same `SampledGame` at different points of
+/// the training are comparable.
+#[derive(Debug, Clone)]
+pub struct SampledGame<G> {
+    game: G,
+    samples: Arc<[G]>,
+    at_root: bool,
+}
+
+impl<G> SampledGame<G>
+where
+    G: Game + Clone,
+{
+    /// Draws `samples` outcomes of the initial chance node of `game` with `rng`.
+    pub fn new<R: Rng + ?Sized>(game: &G, samples: usize, rng: &mut R) -> Self {
+        let samples = (0..samples)
+            .map(|_| {
+                let mut game = game.clone();
+                game.reset();
+                game.new_random(rng);
+                game
+            })
+            .collect();
+        Self {
+            game: game.clone(),
+            samples,
+            at_root: true,
+        }
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    fn wrap(&self, game: G) -> Self {
+        Self {
+            game,
+            samples: self.samples.clone(),
+            at_root: false,
+        }
+    }
+}
+
+impl<G> Game for SampledGame<G>
+where
+    G: Game + Clone,
+{
+    type Action = G::Action;
+    const N_PLAYERS: usize = G::N_PLAYERS;
+
+    fn utility(&mut self, player: usize) -> f64 {
+        self.game.utility(player)
+    }
+
+    fn info_set_str(&self, player: usize) -> String {
+        self.game.info_set_str(player)
+    }
+
+    fn info_set_key(&self, player: usize) -> InfoSetKey {
+        self.game.info_set_key(player)
+    }
+
+    fn history_str(&self) -> String {
+        self.game.history_str()
+    }
+
+    fn public_state_key(&self) -> InfoSetKey {
+        self.game.public_state_key()
+    }
+
+    fn actions(&self) -> Vec<Self::Action> {
+        self.game.actions()
+    }
+
+    fn current_player(&self) -> NodeType {
+        if self.at_root {
+            NodeType::Chance
+        } else {
+            self.game.current_player()
+        }
+    }
+
+    fn act(&mut self, a: Self::Action) {
+        self.game.act(a);
+    }
+
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.at_root {
+            let idx = rng.gen_range(0..self.samples.len());
+            self.game = self.samples[idx].clone();
+            self.at_root = false;
+        } else {
+            self.game.new_random(rng);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.game.reset();
+        self.at_root = true;
+    }
+
+    fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
+        if self.at_root {
+            let prob = 1. / self.samples.len() as f64;
+            Either::Left(
+                self.samples
+                    .iter()
+                    .map(move |game| (self.wrap(game.clone()), prob)),
+            )
+        } else {
+            Either::Right(
+                self.game
+                    .new_iter()
+                    .map(|(game, prob)| (self.wrap(game), prob)),
+            )
+        }
+    }
+}
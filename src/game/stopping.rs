@@ -0,0 +1,152 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Conditions that end a training run before its iteration limit.
+///
+/// The exploitability criteria are checked whenever the exploitability is evaluated, so they
+/// have no effect if the training never evaluates it.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub struct StoppingCriteria {
+    /// Maximum wall-clock time of the training.
+    pub time_limit: Option<Duration>,
+    /// The training stops once the exploitability is at or below this value.
+    pub target_exploitability: Option<f64>,
+    /// The training stops after this number of consecutive evaluations without improving the
+    /// lowest exploitability found so far.
+    pub patience: Option<usize>,
+}
+
+/// Reason why a training run stopped.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub enum StopReason {
+    Iterations,
+    TimeLimit,
+    TargetExploitability,
+    NoImprovement,
+    /// The training was cancelled before meeting any other condition.
+    Interrupted,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            StopReason::Iterations => "iteration limit reached",
+            StopReason::TimeLimit => "time limit reached",
+            StopReason::TargetExploitability => "target exploitability reached",
+            StopReason::NoImprovement => "no improvement in exploitability",
+            StopReason::Interrupted => "interrupted",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// Keeps track of a training run to decide when it has to stop according to some
+/// [`StoppingCriteria`].
+#[derive(Debug, Clone)]
+pub struct StopTracker {
+    criteria: StoppingCriteria,
+    start: Instant,
+    best_exploitability: f64,
+    evaluations_without_improvement: usize,
+}
+
+impl StopTracker {
+    /// Starts tracking a training run. The time limit is measured from this call.
+    pub fn new(criteria: &StoppingCriteria) -> Self {
+        Self {
+            criteria: criteria.clone(),
+            start: Instant::now(),
+            best_exploitability: f64::INFINITY,
+            evaluations_without_improvement: 0,
+        }
+    }
+
+    /// Returns `StopReason::TimeLimit` if the time limit has been exceeded.
+    pub fn check_time(&self) -> Option<StopReason> {
+        self.criteria
+            .time_limit
+            .is_some_and(|limit| self.start.elapsed() >= limit)
+            .then_some(StopReason::TimeLimit)
+    }
+
+    /// Records a new evaluation of the exploitability and returns the reason to stop, if any of
+    /// the exploitability criteria is met.
+    pub fn record_exploitability(&mut self, exploitability: f64) -> Option<StopReason> {
+        if exploitability < self.best_exploitability {
+            self.best_exploitability = exploitability;
+            self.evaluations_without_improvement = 0;
+        } else {
+            self.evaluations_without_improvement += 1;
+        }
+        if self
+            .criteria
+            .target_exploitability
+            .is_some_and(|target| exploitability <= target)
+        {
+            Some(StopReason::TargetExploitability)
+        } else if self
+            .criteria
+            .patience
+            .is_some_and(|patience| self.evaluations_without_improvement >= patience)
+        {
+            Some(StopReason::NoImprovement)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_tracker() {
+        let mut tracker = StopTracker::new(&StoppingCriteria {
+            time_limit: None,
+            target_exploitability: Some(0.1),
+            patience: Some(2),
+        });
+        assert_eq!(tracker.check_time(), None);
+        assert_eq!(tracker.record_exploitability(1.), None);
+        assert_eq!(tracker.record_exploitability(1.2), None);
+        assert_eq!(tracker.record_exploitability(0.5), None);
+        assert_eq!(tracker.record_exploitability(0.6), None);
+        assert_eq!(
+            tracker.record_exploitability(0.7),
+            Some(StopReason::NoImprovement)
+        );
+        assert_eq!(
+            tracker.record_exploitability(0.05),
+            Some(StopReason::TargetExploitability)
+        );
+
+        let tracker = StopTracker::new(&StoppingCriteria {
+            time_limit: Some(Duration::ZERO),
+            ..Default::default()
+        });
+        assert_eq!(tracker.check_time(), Some(StopReason::TimeLimit));
+    }
+}
@@ -1,13 +1,27 @@
-use std::path::{Path, PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use chrono::Utc;
 use musolver::{
-    Cfr, CfrMethod,
-    mus::Lance,
-    solver::{GameConfig, GameType, SolverError, Strategy, Trainer, TrainerConfig},
+    Cfr, CfrMethod, RegretPruning, StopReason, StoppingCriteria,
+    mus::{Lance, Sena, Variante},
+    solver::{
+        BetAbstraction, BetSize, Checkpoint, CheckpointConfig, DiscardAbstraction, GameConfig,
+        GameType, Lbr, Signals, SolverError, Strategy, StrategyReport, Trainer, TrainerConfig,
+        TrainingResult, UtilityTarget,
+    },
 };
 
-use clap::{Parser, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
+use rand::{SeedableRng, rngs::StdRng};
 
 #[derive(Parser, Clone, Debug, ValueEnum)]
 enum MusVariant {
@@ -16,11 +30,19 @@ enum MusVariant {
 }
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
-    /// Número de iteraciones de CFR.
-    #[arg(short, long)]
-    iter: usize,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Número máximo de iteraciones de CFR por marcador. Puede omitirse si se indica un límite de
+    /// tiempo. Una explotabilidad objetivo no basta, porque puede no alcanzarse nunca.
+    #[arg(short, long, required_unless_present_any = [
+        "resume",
+        "time_limit_minutes",
+        "total_time_limit_minutes",
+    ])]
+    iter: Option<usize>,
 
     /// Lance a simular. Si no se pasa este parámetro se simula la partida completa.
     #[arg(short, long, value_enum)]
@@ -31,7 +53,9 @@ struct Args {
     #[arg(short, long, value_parser = parse_tantos)]
     tantos: Option<[u8; 2]>,
 
-    /// Variante de CFR a utilizar. Por defecto: chance-sampling
+    /// Variante de CFR a utilizar. Por defecto: chance-sampling. Los parámetros de DCFR y outcome
+    /// sampling pueden indicarse separados por dos puntos, por ejemplo dcfr:1.5:0:2 o
+    /// outcome-sampling:0.6.
     #[arg(short, long, value_enum)]
     method: Option<CfrMethod>,
 
@@ -46,7 +70,7 @@ struct Args {
     abstract_game: bool,
 
     /// Se calcula la estrategia asumiendo que cada pareja conoce las dos manos y solo hay una
-    /// acción por pareja.
+    /// acción por pareja. Con --lance solo se admite two-hands.
     #[arg(long)]
     variant: Option<MusVariant>,
 
@@ -54,6 +78,219 @@ struct Args {
     /// acota el árbol de juego, que sin él sería infinito. Por defecto: 1
     #[arg(long, default_value_t = 1)]
     max_mus_rounds: u8,
+
+    /// Tantos con los que se gana el chico. Por defecto: 40
+    #[arg(long, default_value_t = 40, value_parser = clap::value_parser!(u8).range(1..))]
+    max_tantos: u8,
+
+    /// Envites permitidos en la fase de envites, separados por comas. Cada envite es un número de
+    /// tantos o un porcentaje de los tantos que quedan por ganar, por ejemplo 3,4,50%. Por
+    /// defecto se envidan 2, 5 o 10 tantos según la apuesta en curso.
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["bet_abstraction", "action_tree"])]
+    envites: Option<Vec<BetSize>>,
+
+    /// Fichero JSON con la abstracción de envites completa: envites según la apuesta en curso,
+    /// por lance y número máximo de envites por lance.
+    #[arg(long, conflicts_with = "action_tree")]
+    bet_abstraction: Option<PathBuf>,
+
+    /// Fichero JSON con el árbol de envites de cada lance, como config/simple_action_tree.json.
+    /// Sustituye a las reglas de envites: solo se juegan las secuencias del árbol.
+    #[arg(long)]
+    action_tree: Option<PathBuf>,
+
+    /// Descartes que se consideran en la fase de descartes: kings para descartar todo salvo los
+    /// reyes, all para todos los descartes posibles o una lista de lances y cartas que se
+    /// conservan, por ejemplo grande:2,pares:2,juego:3. Por defecto: kings
+    #[arg(long, default_value = "kings")]
+    discards: DiscardAbstraction,
+
+    /// Qué se maximiza en las partidas completas: tantos para la diferencia de tantos al acabar
+    /// el chico, o los chicos por vaca y las vacas del encuentro, por ejemplo 2:2, para la
+    /// probabilidad de ganar el encuentro. Por defecto: tantos
+    #[arg(long, default_value = "tantos")]
+    utility_target: UtilityTarget,
+
+    /// Variante de reglas: a ocho reyes, en la que los treses son reyes y los doses ases, o a
+    /// cuatro reyes. Por defecto: ocho-reyes
+    #[arg(long, value_enum, default_value_t = Variante::OchoReyes)]
+    variante: Variante,
+
+    /// Señas que pueden hacerse los compañeros al empezar los envites, separadas por comas:
+    /// dos-reyes, tres-reyes, dos-pitos, medias, duples, 31 o ciego. Solo con la partida de
+    /// cuatro jugadores. Por defecto no hay señas.
+    #[arg(long, value_delimiter = ',')]
+    senas: Vec<Sena>,
+
+    /// Probabilidad de que los rivales vean cada seña. Por defecto: 0
+    #[arg(long, default_value_t = 0., value_parser = parse_probabilidad)]
+    prob_senas_rivales: f64,
+
+    /// Número de hilos de entrenamiento. Solo se aprovechan con chance-sampling y
+    /// external-sampling. Por defecto: 1
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+
+    /// Guarda un checkpoint con el estado completo del entrenamiento cada este número de
+    /// iteraciones. El checkpoint se escribe en el directorio de salida, o sobre el fichero
+    /// indicado en --resume.
+    #[arg(long)]
+    checkpoint_every: Option<usize>,
+
+    /// Guarda un checkpoint cada este número de minutos.
+    #[arg(long)]
+    checkpoint_minutes: Option<u64>,
+
+    /// Estima la explotabilidad de la estrategia cada este número de iteraciones sobre repartos
+    /// muestreados. La estimación está sesgada hacia arriba. Los valores se guardan en un fichero
+    /// CSV junto a la estrategia.
+    #[arg(long)]
+    eval_every: Option<usize>,
+
+    /// Número de repartos sobre los que se calcula la mejor respuesta al estimar la
+    /// explotabilidad. Con menos repartos la estimación está más sesgada hacia arriba. En
+    /// lance-game-two-hands también estima la utilidad esperada de cada marcador. Por defecto: 1000
+    #[arg(long, default_value_t = 1000)]
+    eval_samples: usize,
+
+    /// Tiempo máximo de entrenamiento de cada marcador, en minutos.
+    #[arg(long)]
+    time_limit_minutes: Option<f64>,
+
+    /// Tiempo máximo del entrenamiento completo, en minutos. Al agotarse se exportan las
+    /// estrategias de los marcadores ya entrenados.
+    #[arg(long)]
+    total_time_limit_minutes: Option<f64>,
+
+    /// Termina el entrenamiento de cada marcador al alcanzar esta explotabilidad. Requiere
+    /// --eval-every.
+    #[arg(long, requires = "eval_every")]
+    target_exploitability: Option<f64>,
+
+    /// Termina el entrenamiento de cada marcador tras este número de evaluaciones seguidas sin
+    /// mejorar la explotabilidad. Requiere --eval-every.
+    #[arg(long, requires = "eval_every")]
+    patience: Option<usize>,
+
+    /// Semilla de los generadores aleatorios. Con la misma semilla y un solo hilo se obtiene la
+    /// misma estrategia. Por defecto se elige una al azar, que se guarda con la estrategia.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Con dcfr y linear-cfr, se descuentan los regrets y la estrategia media cada este número de
+    /// iteraciones. Descontar recorre todos los nodos, así que en partidas grandes conviene
+    /// agrupar las iteraciones. Por defecto: 100
+    #[arg(
+        long,
+        default_value_t = TrainerConfig::DEFAULT_DISCOUNT_INTERVAL,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+    )]
+    discount_every: usize,
+
+    /// Activa la poda por regret: no se exploran las acciones cuyo regret acumulado esté por
+    /// debajo de este valor, que debe ser negativo.
+    #[arg(long, allow_hyphen_values = true)]
+    prune_threshold: Option<f64>,
+
+    /// Iteraciones iniciales de cada marcador en las que no se poda. Por defecto: 1000
+    #[arg(long, default_value_t = 1000, requires = "prune_threshold")]
+    prune_warmup: usize,
+
+    /// Cada este número de iteraciones se recorre el árbol completo sin podar, para que las
+    /// acciones podadas puedan recuperarse. Por defecto: 20
+    #[arg(
+        long,
+        default_value_t = 20,
+        requires = "prune_threshold",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+    )]
+    prune_full_every: usize,
+
+    /// Empieza el entrenamiento desde una estrategia exportada (.json o .rkyv) o un checkpoint
+    /// (.ckpt) en lugar de desde la estrategia uniforme. Los conjuntos de información que ya no
+    /// existen se ignoran y los nuevos empiezan con la estrategia uniforme.
+    #[arg(long, conflicts_with = "resume")]
+    init_from: Option<PathBuf>,
+
+    /// Peso de la estrategia inicial en la estrategia media, en número de visitas. Cuanto mayor,
+    /// más iteraciones hacen falta para alejarse de ella. Por defecto: 100
+    #[arg(long, default_value_t = 100., requires = "init_from")]
+    init_weight: f64,
+
+    /// Continúa el entrenamiento guardado en un checkpoint. La configuración de la partida y del
+    /// entrenamiento se toman del checkpoint y se ignoran las indicadas por línea de comandos.
+    #[arg(long)]
+    resume: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Muestra la cobertura y la calidad de una estrategia: conjuntos de información por fase,
+    /// nodos sin entrenar, entropía, acciones más jugadas, agresividad por mano y la tabla de
+    /// utilidades.
+    Report {
+        /// Ruta a la estrategia (.rkyv o .json).
+        strategy: PathBuf,
+
+        /// Partidas que se simulan para las estadísticas de cada fase.
+        #[arg(long, default_value_t = 10000)]
+        deals: usize,
+
+        /// Semilla de las partidas simuladas. Por defecto, aleatoria.
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Escribe el informe en JSON.
+        #[arg(long)]
+        json: bool,
+
+        /// Acota la explotabilidad con una mejor respuesta local (LBR) que juega este número de
+        /// repartos en cada posición. Solo en partidas completas con un jugador en cada posición.
+        #[arg(long)]
+        lbr: Option<usize>,
+
+        /// Marcador con el que se juegan los repartos de LBR.
+        #[arg(long, value_parser = parse_tantos, default_value = "0:0")]
+        lbr_tantos: [u8; 2],
+
+        /// Mundos con los que el agente de LBR estima el rango de los rivales.
+        #[arg(long, default_value_t = Lbr::DEFAULT_WORLDS)]
+        lbr_worlds: usize,
+    },
+}
+
+/// Repartos, marcador y mundos de la evaluación con LBR del informe.
+struct LbrOptions {
+    deals: usize,
+    tantos: [u8; 2],
+    worlds: usize,
+}
+
+fn report(
+    strategy_path: &Path,
+    deals: usize,
+    seed: Option<u64>,
+    json: bool,
+    lbr: Option<LbrOptions>,
+) {
+    let strategy = Strategy::from_file(strategy_path).expect("Error cargando la estrategia.");
+    let mut rng = StdRng::seed_from_u64(seed.unwrap_or_else(rand::random));
+    let mut report = StrategyReport::new(&strategy, deals, &mut rng);
+    if let Some(lbr) = lbr {
+        let estimate = Lbr::new(&strategy)
+            .with_worlds(lbr.worlds)
+            .evaluate_strategy(lbr.tantos, lbr.deals, &mut rng)
+            .expect("Error evaluando la estrategia con LBR.");
+        report = report.with_lbr(estimate);
+    }
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Error serializando el informe.")
+        );
+    } else {
+        print!("{report}");
+    }
 }
 
 fn parse_tantos(s: &str) -> Result<[u8; 2], String> {
@@ -73,57 +310,238 @@ fn parse_tantos(s: &str) -> Result<[u8; 2], String> {
     }
 }
 
+fn parse_probabilidad(s: &str) -> Result<f64, String> {
+    let probabilidad: f64 = s.parse().map_err(|_| format!("{s} no es un número."))?;
+    if (0. ..=1.).contains(&probabilidad) {
+        Ok(probabilidad)
+    } else {
+        Err(format!("La probabilidad debe estar entre 0 y 1 ({s})."))
+    }
+}
+
+/// Termina el programa con un error de clap para las combinaciones de argumentos que no pueden
+/// comprobarse al leer cada argumento por separado.
+fn argument_error(kind: ErrorKind, message: impl std::fmt::Display) -> ! {
+    Args::command().error(kind, message).exit()
+}
+
+/// Carga la estrategia de la que parte el entrenamiento, que puede ser una estrategia exportada o
+/// un checkpoint.
+fn load_init_cfr(path: &Path, weight: f64) -> Result<Cfr, SolverError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("ckpt") => Ok(Checkpoint::from_file(path)?.warm_start(weight)),
+        _ => Ok(Cfr::from_strategy(&Strategy::from_file(path)?, weight)),
+    }
+}
+
+fn minutes_to_duration(minutes: f64) -> Duration {
+    Duration::from_secs_f64(minutes * 60.)
+}
+
 fn main() {
     let args = Args::parse();
+    if let Some(Command::Report {
+        strategy,
+        deals,
+        seed,
+        json,
+        lbr,
+        lbr_tantos,
+        lbr_worlds,
+    }) = &args.command
+    {
+        let lbr = lbr.map(|deals| LbrOptions {
+            deals,
+            tantos: *lbr_tantos,
+            worlds: *lbr_worlds,
+        });
+        report(strategy, *deals, *seed, *json, lbr);
+        return;
+    }
 
-    let tantos = args.tantos.unwrap_or_default();
-    let method = args.method.unwrap_or(CfrMethod::ChanceSampling);
     let mut output_path = PathBuf::from(args.output.unwrap_or_else(|| "output/".to_string()));
+    let run_name = format!("{}", Utc::now().format("%Y-%m-%d %H%M"));
 
-    let trainer_config = TrainerConfig {
-        iterations: args.iter,
-        method,
-    };
-    let game_config = GameConfig {
-        abstract_game: args.abstract_game,
-        max_mus_rounds: args.max_mus_rounds,
-        game_type: match (args.lance, args.variant) {
-            (Some(lance), _) => GameType::LanceGame(lance),
-            (None, None) => GameType::MusGame,
-            (None, Some(MusVariant::TwoHands)) => GameType::MusGameTwoHands,
-            (None, Some(MusVariant::TwoPlayers)) => GameType::MusGameTwoPlayers,
-        },
+    let (mut trainer, trainer_config, game_config, checkpoint_path, tantos) = match args.resume {
+        Some(checkpoint_path) => {
+            let checkpoint =
+                Checkpoint::from_file(&checkpoint_path).expect("Error cargando checkpoint.");
+            println!(
+                "Reanudando entrenamiento en {}:{}, iteración {}.",
+                checkpoint.tantos[0], checkpoint.tantos[1], checkpoint.iteration
+            );
+            let trainer_config = checkpoint.trainer_config.clone();
+            let game_config = checkpoint.game_config.clone();
+            let tantos = checkpoint.target;
+            (
+                Trainer::new().resume_from(checkpoint),
+                trainer_config,
+                game_config,
+                checkpoint_path,
+                tantos,
+            )
+        }
+        None => {
+            let tantos = args.tantos.unwrap_or_default();
+            if tantos[0] >= args.max_tantos || tantos[1] >= args.max_tantos {
+                argument_error(
+                    ErrorKind::ValueValidation,
+                    format!(
+                        "El marcador inicial debe ser menor que los tantos del chico ({}).",
+                        args.max_tantos
+                    ),
+                );
+            }
+            let trainer_config = TrainerConfig {
+                iterations: args.iter.unwrap_or(usize::MAX),
+                method: args.method.unwrap_or(CfrMethod::ChanceSampling),
+                threads: args.threads,
+                eval_every: args.eval_every,
+                eval_samples: args.eval_samples,
+                stopping: StoppingCriteria {
+                    time_limit: args.time_limit_minutes.map(minutes_to_duration),
+                    target_exploitability: args.target_exploitability,
+                    patience: args.patience,
+                },
+                total_time_limit: args.total_time_limit_minutes.map(minutes_to_duration),
+                seed: args.seed.unwrap_or_else(rand::random),
+                pruning: args.prune_threshold.map(|threshold| RegretPruning {
+                    threshold,
+                    warmup: args.prune_warmup,
+                    full_pass_every: args.prune_full_every,
+                }),
+                discount_interval: args.discount_every,
+            };
+            let bet_abstraction = match (args.envites, args.bet_abstraction, args.action_tree) {
+                (Some(envites), _, _) => BetAbstraction::uniform(envites),
+                (None, Some(path), _) => BetAbstraction::from_file(path)
+                    .expect("Error cargando la abstracción de envites."),
+                (None, None, Some(path)) => BetAbstraction::from_action_tree(path)
+                    .expect("Error cargando el árbol de envites."),
+                (None, None, None) => BetAbstraction::default(),
+            };
+            let game_config = GameConfig {
+                abstract_game: args.abstract_game,
+                max_mus_rounds: args.max_mus_rounds,
+                max_tantos: args.max_tantos,
+                bet_abstraction,
+                discard_abstraction: args.discards,
+                utility_target: args.utility_target,
+                variante: args.variante,
+                signals: (!args.senas.is_empty()).then_some(Signals {
+                    vocabulary: args.senas,
+                    opponent_probability: args.prob_senas_rivales,
+                }),
+                game_type: match (args.lance, args.variant) {
+                    (Some(lance), None) => GameType::LanceGame(lance),
+                    (Some(lance), Some(MusVariant::TwoHands)) => GameType::LanceGameTwoHands(lance),
+                    (Some(_), Some(MusVariant::TwoPlayers)) => argument_error(
+                        ErrorKind::ArgumentConflict,
+                        "Los lances sueltos solo se juegan con cuatro jugadores.",
+                    ),
+                    (None, None) => GameType::MusGame,
+                    (None, Some(MusVariant::TwoHands)) => GameType::MusGameTwoHands,
+                    (None, Some(MusVariant::TwoPlayers)) => GameType::MusGameTwoPlayers,
+                },
+            };
+            if game_config.signals.is_some() && !matches!(game_config.game_type, GameType::MusGame)
+            {
+                argument_error(
+                    ErrorKind::ArgumentConflict,
+                    "Las señas solo se admiten en la partida de cuatro jugadores.",
+                );
+            }
+            let mut trainer = Trainer::new().with_tantos(tantos);
+            if let Some(init_path) = args.init_from {
+                let cfr = load_init_cfr(&init_path, args.init_weight)
+                    .expect("Error cargando la estrategia inicial.");
+                println!("Estrategia inicial: {}", init_path.display());
+                trainer = trainer.init_from(cfr);
+            }
+            (
+                trainer,
+                trainer_config,
+                game_config,
+                output_path.join("checkpoint.ckpt"),
+                tantos,
+            )
+        }
     };
+    if args.checkpoint_every.is_some() || args.checkpoint_minutes.is_some() {
+        if let Some(parent) = checkpoint_path.parent() {
+            fs::create_dir_all(parent).expect("Error creando el directorio de checkpoints.");
+        }
+        trainer = trainer.with_checkpoints(CheckpointConfig {
+            path: checkpoint_path,
+            every_iterations: args.checkpoint_every,
+            every: args
+                .checkpoint_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+        });
+    }
+
+    if trainer_config.eval_every.is_some() {
+        fs::create_dir_all(&output_path).expect("Error creando el directorio de salida.");
+        let mut convergence_log_path = output_path.join(&run_name);
+        convergence_log_path.set_extension("csv");
+        trainer = trainer.with_convergence_log(convergence_log_path);
+    }
+
+    // La primera interrupción termina la iteración en curso, abandona la evaluación o el cálculo
+    // de la utilidad esperada si están en marcha y exporta la estrategia parcial. La segunda
+    // termina el proceso inmediatamente.
+    let cancel = Arc::new(AtomicBool::new(false));
+    let handler_cancel = cancel.clone();
+    ctrlc::set_handler(move || {
+        if handler_cancel.swap(true, Ordering::Relaxed) {
+            process::exit(130);
+        }
+        println!("Interrumpiendo el entrenamiento. Pulsa Ctrl-C de nuevo para salir sin guardar.");
+    })
+    .expect("Error instalando el manejador de señales.");
+    trainer = trainer.with_cancel_flag(cancel);
+
     println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
     println!(
         "Simulando: {}",
         match game_config.game_type {
             GameType::LanceGame(lance) => format!("{lance:?}"),
             GameType::MusGame => "Partida completa".into(),
-            GameType::LanceGameTwoHands(_) => todo!(),
+            GameType::LanceGameTwoHands(lance) => format!("{lance:?} (two hands)"),
             GameType::MusGameTwoHands => "Partida completa (two hands)".into(),
             GameType::MusGameTwoPlayers => "Partida completa (two players)".into(),
         }
     );
     println!("Tantos iniciales: {}:{}", tantos[0], tantos[1]);
+    println!("Semilla: {}", trainer_config.seed);
+    if let Some(pruning) = trainer_config.pruning {
+        println!(
+            "Poda por regret: umbral {}, desde la iteración {}, recorrido completo cada {} iteraciones",
+            pruning.threshold, pruning.warmup, pruning.full_pass_every
+        );
+    }
 
-    let trainer = Trainer::new().with_tantos(tantos);
-    let cfr = trainer.train(&game_config, &trainer_config);
-    let curr_time = Utc::now();
-    output_path.push(format!("{}", curr_time.format("%Y-%m-%d %H%M")));
+    let result = trainer.train(&game_config, &trainer_config);
+    if result.stop_reason == StopReason::Interrupted {
+        println!("Entrenamiento interrumpido. Se exporta la estrategia parcial.");
+    }
+    output_path.push(run_name);
     println!("Exportando estrategias a {output_path:?}...");
-    export_cfr(&output_path, &cfr, &trainer_config, &game_config)
+    export_cfr(&output_path, &result, &trainer_config, &game_config)
         .expect("Error exportando estrategias.");
 }
 
 pub fn export_cfr(
     path: &Path,
-    cfr: &Cfr,
+    result: &TrainingResult,
     trainer_config: &TrainerConfig,
     game_config: &GameConfig,
 ) -> Result<(), SolverError> {
     let mut estrategia_path = PathBuf::from(path);
     estrategia_path.set_extension("rkyv");
-    let strategy = Strategy::new(cfr, trainer_config, game_config);
+    let strategy = Strategy::new(&result.cfr, trainer_config, game_config)
+        .with_exploitability(result.exploitability)
+        .with_stop_reasons(result.stop_reasons.clone())
+        .with_utility_table(result.utility_table.clone());
     strategy.to_rkyv(estrategia_path)
 }
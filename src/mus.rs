@@ -1,5 +1,6 @@
 //! Funcionalidad básica del juego del mus. Contiene estructuras para representar cartas, manos,
-//! barajas, iteradores de manos, lances y partidas de mus.
+//! barajas, iteradores de manos, lances, partidas de mus, encuentros a vacas y chicos y las
+//! variantes de reglas a ocho y cuatro reyes y las señas entre compañeros.
 mod carta;
 pub use carta::*;
 
@@ -18,7 +19,16 @@ pub use lance::*;
 mod partida_mus;
 pub use partida_mus::*;
 
+mod encuentro;
+pub use encuentro::*;
+
 mod baraja;
 pub use baraja::*;
 
+mod variante;
+pub use variante::*;
+
+mod sena;
+pub use sena::*;
+
 pub mod arena;
@@ -1,12 +1,15 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use async_trait::async_trait;
 use rand::{Rng, distributions::WeightedIndex, prelude::Distribution};
 
 use crate::{
     Game,
-    mus::{Accion, CuatroJugadores, DosJugadores, FaseEnvites, ModalidadMus},
-    solver::{LanceGame, Strategy},
+    mus::{Accion, CuatroJugadores, DosJugadores, FaseEnvites, Mano, ModalidadMus},
+    solver::{Fallback, LanceGame, Strategy},
 };
 
 #[async_trait]
@@ -51,6 +54,10 @@ pub struct AgenteMusolver {
     strategy: Strategy,
     initial_score: [u8; 2],
     history: Arc<Mutex<Vec<Accion>>>,
+    fallbacks: Vec<Fallback>,
+    /// Decisiones tomadas con cada alternativa. `None` cuenta las que encuentran el conjunto de
+    /// información en la estrategia. Lo comparten las copias del agente.
+    hits: Arc<Mutex<HashMap<Option<Fallback>, usize>>>,
 }
 
 impl AgenteMusolver {
@@ -59,6 +66,43 @@ impl AgenteMusolver {
             strategy,
             initial_score: [0, 0],
             history,
+            fallbacks: Vec::new(),
+            hits: Default::default(),
+        }
+    }
+
+    /// Alternativas a las que se recurre cuando la estrategia no tiene el conjunto de
+    /// información. Sin alternativas, se pasa.
+    pub fn with_fallbacks(self, fallbacks: Vec<Fallback>) -> Self {
+        Self { fallbacks, ..self }
+    }
+
+    /// Número de decisiones tomadas con cada alternativa, o con la estrategia si es `None`.
+    pub fn hits(&self) -> HashMap<Option<Fallback>, usize> {
+        self.hits.lock().unwrap().clone()
+    }
+
+    fn elegir_accion(&self, manos: &[Mano], history: &[Accion]) -> Accion {
+        let lookup = self.strategy.actions_with_fallback(
+            manos,
+            self.initial_score,
+            history,
+            &self.fallbacks,
+        );
+        match lookup {
+            Ok(lookup) => {
+                *self
+                    .hits
+                    .lock()
+                    .unwrap()
+                    .entry(lookup.fallback)
+                    .or_default() += 1;
+                Self::accion_aleatoria(&lookup.actions, &lookup.probabilities)
+            }
+            Err(err) => {
+                println!("ERROR: {err}. {history:?}. Se pasa por defecto.");
+                Accion::Paso
+            }
         }
     }
 
@@ -76,17 +120,7 @@ impl Agent<DosJugadores> for AgenteMusolver {
         if history.len() < 2 {
             self.initial_score = *partida_mus.tantos();
         }
-        let action_probability =
-            self.strategy
-                .actions(partida_mus.manos(), self.initial_score, &history);
-        if let Some((actions, probabilities)) = action_probability {
-            Self::accion_aleatoria(&actions, &probabilities)
-        } else {
-            println!(
-                "ERROR: La lista de acciones no está en el árbol. {history:?}. Se pasa por defecto."
-            );
-            Accion::Paso
-        }
+        self.elegir_accion(partida_mus.manos(), &history)
     }
 }
 
@@ -97,16 +131,6 @@ impl Agent<CuatroJugadores> for AgenteMusolver {
         if history.len() < 2 {
             self.initial_score = *partida_mus.tantos();
         }
-        let action_probability =
-            self.strategy
-                .actions(partida_mus.manos(), self.initial_score, &history);
-        if let Some((actions, probabilities)) = action_probability {
-            Self::accion_aleatoria(&actions, &probabilities)
-        } else {
-            println!(
-                "ERROR: La lista de acciones no está en el árbol. {history:?}. Se pasa por defecto."
-            );
-            Accion::Paso
-        }
+        self.elegir_accion(partida_mus.manos(), &history)
     }
 }
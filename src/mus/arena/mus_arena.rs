@@ -1,7 +1,9 @@
 use crate::mus::{
-    Accion, Baraja, CuatroJugadores, DosJugadores, FaseEnvites, Lance, Mano, ModalidadMus, Turno,
+    Accion, CuatroJugadores, DosJugadores, FaseEnvites, Lance, Mano, ModalidadMus, Turno, Variante,
 };
 
+use rand::{SeedableRng, rngs::StdRng};
+
 use super::{Agent, Kibitzer};
 
 /// Events generated by MusArena during a game.
@@ -37,19 +39,28 @@ pub struct MusArena<T: ModalidadMus> {
     order: Vec<usize>,
     scoreboard: [u8; 2],
     init_scoreboard: [u8; 2],
+    max_tantos: u8,
+    variante: Variante,
+    rng: StdRng,
 }
 
 impl<T: ModalidadMus> MusArena<T> {
     pub async fn start(&mut self) {
         self.order.rotate_left(1);
-        if self.scoreboard[0] == 40 || self.scoreboard[1] == 40 {
+        if self.scoreboard[0] == self.max_tantos || self.scoreboard[1] == self.max_tantos {
             self.scoreboard = self.init_scoreboard;
         }
         let mut scoreboard = [
             self.scoreboard[self.order[0] % 2],
             self.scoreboard[self.order[1] % 2],
         ];
-        self.partida_mus = Self::new_partida(self.lance, scoreboard);
+        self.partida_mus = Self::new_partida(
+            self.lance,
+            scoreboard,
+            self.max_tantos,
+            self.variante,
+            &mut self.rng,
+        );
         self.record_action(MusAction::GameStart {
             hand: self.order[0],
             scoreboard: self.scoreboard,
@@ -120,15 +131,49 @@ impl<T: ModalidadMus> MusArena<T> {
         new_scoreboard
     }
 
-    fn new_partida(lance: Option<Lance>, scoreboard: [u8; 2]) -> FaseEnvites<T> {
-        let mut baraja = Baraja::baraja_mus();
+    /// Reparte las cartas con el generador `seed`, de forma que dos arenas con la misma semilla
+    /// reparten las mismas manos.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            ..self
+        }
+    }
+
+    /// Juega chicos a `max_tantos` tantos en lugar de a 40.
+    pub fn with_max_tantos(self, max_tantos: u8) -> Self {
+        Self { max_tantos, ..self }
+    }
+
+    /// Reparte con la baraja de la variante `variante` en lugar de la de ocho reyes.
+    pub fn with_variante(self, variante: Variante) -> Self {
+        Self { variante, ..self }
+    }
+
+    fn new_partida(
+        lance: Option<Lance>,
+        scoreboard: [u8; 2],
+        max_tantos: u8,
+        variante: Variante,
+        rng: &mut StdRng,
+    ) -> FaseEnvites<T> {
         match lance {
-            None => FaseEnvites::<T>::new(T::repartir_manos(&mut baraja), scoreboard),
+            None => {
+                let mut baraja = variante.baraja();
+                baraja.barajar(rng);
+                FaseEnvites::<T>::con_max_tantos(
+                    T::repartir_manos(&mut baraja),
+                    scoreboard,
+                    max_tantos,
+                )
+            }
             Some(lance) => loop {
-                baraja.barajar();
+                let mut baraja = variante.baraja();
+                baraja.barajar(rng);
                 let manos = T::repartir_manos(&mut baraja);
-                let posible_partida_mus =
-                    FaseEnvites::<T>::new_partida_lance(lance, manos, scoreboard);
+                let posible_partida_mus = FaseEnvites::<T>::partida_lance_con_max_tantos(
+                    lance, manos, scoreboard, max_tantos,
+                );
                 if let Some(partida_mus) = posible_partida_mus {
                     return partida_mus;
                 }
@@ -139,28 +184,48 @@ impl<T: ModalidadMus> MusArena<T> {
 
 impl MusArena<DosJugadores> {
     pub fn new(init_scoreboard: [u8; 2], lance: Option<Lance>) -> Self {
+        let mut rng = StdRng::from_entropy();
         MusArena {
             agents: vec![],
             kibitzers: vec![],
-            partida_mus: Self::new_partida(lance, init_scoreboard),
+            partida_mus: Self::new_partida(
+                lance,
+                init_scoreboard,
+                FaseEnvites::<DosJugadores>::MAX_TANTOS,
+                Variante::default(),
+                &mut rng,
+            ),
             order: vec![0, 1],
             lance,
             scoreboard: init_scoreboard,
             init_scoreboard,
+            max_tantos: FaseEnvites::<DosJugadores>::MAX_TANTOS,
+            variante: Variante::default(),
+            rng,
         }
     }
 }
 
 impl MusArena<CuatroJugadores> {
     pub fn new(init_scoreboard: [u8; 2], lance: Option<Lance>) -> Self {
+        let mut rng = StdRng::from_entropy();
         MusArena {
             agents: vec![],
             kibitzers: vec![],
-            partida_mus: Self::new_partida(lance, init_scoreboard),
+            partida_mus: Self::new_partida(
+                lance,
+                init_scoreboard,
+                FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+                Variante::default(),
+                &mut rng,
+            ),
             order: vec![0, 1, 2, 3],
             lance,
             scoreboard: init_scoreboard,
             init_scoreboard,
+            max_tantos: FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            variante: Variante::default(),
+            rng,
         }
     }
 }
@@ -2,10 +2,14 @@ use std::collections::VecDeque;
 
 use crate::mus::Carta;
 use arrayvec::ArrayVec;
+use rand::Rng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 
-use super::Mano;
+use super::{Mano, Variante};
+
+/// Número de cartas de cada valor que quedan por repartir. Tiene capacidad para todos los valores
+/// de la baraja española, aunque en el mus a ocho reyes solo se usan ocho.
+pub type FrecuenciasBaraja = ArrayVec<(Carta, u8), 10>;
 
 /// Baraja española de cartas.
 #[derive(Clone, Debug)]
@@ -23,37 +27,35 @@ impl Baraja {
         (Carta::As, 8),
     ];
 
+    pub const FREC_BARAJA_CUATRO_REYES: [(Carta, u8); 10] = [
+        (Carta::Rey, 4),
+        (Carta::Caballo, 4),
+        (Carta::Sota, 4),
+        (Carta::Siete, 4),
+        (Carta::Seis, 4),
+        (Carta::Cinco, 4),
+        (Carta::Cuatro, 4),
+        (Carta::TresCuatroReyes, 4),
+        (Carta::DosCuatroReyes, 4),
+        (Carta::As, 4),
+    ];
+
     /// Devuelve una nueva baraja vacía.
     pub fn new() -> Self {
         Baraja(VecDeque::with_capacity(40), 0)
     }
 
-    /// Devuelve una baraj de mus. Incluye ocho ases y ocho reyes, y no incluye ni doses ni treses.
+    /// Devuelve una baraja de mus a ocho reyes sin barajar. Incluye ocho ases y ocho reyes, y no
+    /// incluye ni doses ni treses.
     pub fn baraja_mus() -> Baraja {
-        let mut b = Baraja::new();
-        for _ in 0..8 {
-            b.insertar(Carta::As);
-            b.insertar(Carta::Rey);
-        }
-        for _ in 0..4 {
-            b.insertar(Carta::Caballo);
-            b.insertar(Carta::Sota);
-            b.insertar(Carta::Siete);
-            b.insertar(Carta::Seis);
-            b.insertar(Carta::Cinco);
-            b.insertar(Carta::Cuatro);
-        }
-        b.barajar();
-        b.1 = 40;
-        b
+        Variante::OchoReyes.baraja()
     }
 
-    /// Genera cuatro manos a partir de las primeras dieciseis cartas de la baraja en el momento de
-    /// la llamada a la función. Esta funcion no baraja las cartas y tampoco las elimina de la
-    /// baraja.
+    /// Genera `N` manos a partir de las primeras `4 * N` cartas de la baraja en el momento de la
+    /// llamada a la función. Esta funcion no baraja las cartas.
     pub fn repartir_manos<const N: usize>(&mut self) -> [Mano; N] {
         let mut c = self.0.drain(0..4 * N);
-        self.1 -= 16;
+        self.1 -= 4 * N;
         core::array::from_fn(|_| {
             let mut m = ArrayVec::<Carta, 4>::new();
             for _ in 0..4 {
@@ -69,9 +71,9 @@ impl Baraja {
         self.1 += 1;
     }
 
-    /// Baraja las cartas. Utiliza el algoritmo shuffle del crate rand.
-    pub fn barajar(&mut self) {
-        self.0.make_contiguous().shuffle(&mut thread_rng());
+    /// Baraja las cartas con el generador `rng`. Utiliza el algoritmo shuffle del crate rand.
+    pub fn barajar<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.0.make_contiguous().shuffle(rng);
     }
 
     /// Elimina una carta de la baraja y la devuelve. En caso de que sea una baraja vacía devuelve
@@ -81,26 +83,62 @@ impl Baraja {
         self.0.remove(0)
     }
 
+    /// Quita una copia de `carta` de las cartas por repartir. Devuelve false si no queda ninguna.
+    pub fn extraer(&mut self, carta: Carta) -> bool {
+        let Some(idx) = self.0.range(..self.1).position(|c| *c == carta) else {
+            return false;
+        };
+        self.0.remove(idx);
+        self.1 -= 1;
+        true
+    }
+
+    /// Coloca `cartas` encima de la baraja para que sean las siguientes en repartirse. Las cartas
+    /// se extraen antes de las que quedan por repartir; si alguna no está, devuelve false y la
+    /// baraja queda sin las cartas que sí se han podido extraer.
+    pub fn colocar_encima(&mut self, cartas: &[Carta]) -> bool {
+        if !cartas.iter().all(|carta| self.extraer(*carta)) {
+            return false;
+        }
+        for carta in cartas.iter().rev() {
+            self.0.push_front(*carta);
+            self.1 += 1;
+        }
+        true
+    }
+
     /// Devuelve un slice de las primeras n cartas de la baraja.
     pub fn primeras_n_cartas(&mut self, n: usize) -> &[Carta] {
         &self.0.make_contiguous()[0..n]
     }
 
-    pub fn descartar_mano(&mut self, mano: &mut Mano, descartes: [bool; 4]) {
+    pub fn descartar_mano<R: Rng + ?Sized>(
+        &mut self,
+        mano: &mut Mano,
+        descartes: [bool; 4],
+        rng: &mut R,
+    ) {
         let nuevas = self.descartar(
             mano.iter()
                 .enumerate()
                 .filter_map(|(idx, carta)| descartes[idx].then_some(*carta)),
+            rng,
         );
         mano.reemplazar(descartes, nuevas);
     }
 
-    pub fn descartar(&mut self, descartes: impl Iterator<Item = Carta>) -> ArrayVec<Carta, 4> {
+    /// Añade los descartes al final de la baraja y reparte otras tantas cartas. Si no quedan
+    /// cartas suficientes se barajan los descartes con `rng` para seguir repartiendo.
+    pub fn descartar<R: Rng + ?Sized>(
+        &mut self,
+        descartes: impl Iterator<Item = Carta>,
+        rng: &mut R,
+    ) -> ArrayVec<Carta, 4> {
         let antes = self.0.len();
         self.0.extend(descartes);
         let num_descartes = self.0.len() - antes;
         if num_descartes > self.1 {
-            self.0.make_contiguous()[self.1..].shuffle(&mut thread_rng());
+            self.0.make_contiguous()[self.1..].shuffle(rng);
             self.1 = self.0.len();
         }
         self.1 -= num_descartes;
@@ -122,11 +160,24 @@ mod tests {
     fn test_descartar() {
         let mut baraja = Baraja::new();
         let mut mano = Mano::new([Carta::As, Carta::As, Carta::As, Carta::Tres]);
-        baraja.descartar_mano(&mut mano, [true, true, true, true]);
+        let mut rng = rand::thread_rng();
+        baraja.descartar_mano(&mut mano, [true, true, true, true], &mut rng);
         assert_eq!(mano.to_string(), "3111");
 
         baraja.insertar(Carta::Caballo);
-        baraja.descartar_mano(&mut mano, [false, false, true, true]);
+        baraja.descartar_mano(&mut mano, [false, false, true, true], &mut rng);
         assert_eq!(mano.to_string(), "3C11");
     }
+
+    #[test]
+    fn test_colocar_encima() {
+        let mut baraja = Baraja::baraja_mus();
+        assert!(baraja.colocar_encima(&[Carta::Sota, Carta::As]));
+        assert_eq!(baraja.repartir(), Some(Carta::Sota));
+        assert_eq!(baraja.repartir(), Some(Carta::As));
+        for _ in 0..3 {
+            assert!(baraja.extraer(Carta::Sota));
+        }
+        assert!(!baraja.colocar_encima(&[Carta::Sota]));
+    }
 }
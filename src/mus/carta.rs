@@ -1,6 +1,10 @@
 use super::mus_error::*;
 use std::hash::Hash;
 
+/// Cartas de la baraja española sin ochos ni nueves. Se comparan por su valor en el mus a ocho
+/// reyes, en el que los treses son reyes y los doses ases. El mus a cuatro reyes, en el que cada
+/// carta vale lo que indica, juega sus treses y doses como [`Carta::TresCuatroReyes`] y
+/// [`Carta::DosCuatroReyes`]. Ver [`Variante`](super::Variante).
 #[derive(Eq, Debug, Copy, Clone)]
 pub enum Carta {
     As = 0,
@@ -13,12 +17,18 @@ pub enum Carta {
     Sota = 7,
     Caballo = 8,
     Rey = 9,
+    /// Dos del mus a cuatro reyes, que vale dos.
+    DosCuatroReyes = 10,
+    /// Tres del mus a cuatro reyes, que vale tres.
+    TresCuatroReyes = 11,
 }
 
 impl Carta {
     pub fn valor(&self) -> u8 {
         match self {
             Carta::As | Carta::Dos => 1,
+            Carta::DosCuatroReyes => 2,
+            Carta::TresCuatroReyes => 3,
             Carta::Cuatro => 4,
             Carta::Cinco => 5,
             Carta::Seis => 6,
@@ -52,14 +62,28 @@ impl Carta {
         Carta::Caballo,
         Carta::Rey,
     ];
+
+    /// Cartas distintas del mus a cuatro reyes, de menor a mayor.
+    pub const CARTAS_CUATRO_REYES: [Carta; 10] = [
+        Carta::As,
+        Carta::DosCuatroReyes,
+        Carta::TresCuatroReyes,
+        Carta::Cuatro,
+        Carta::Cinco,
+        Carta::Seis,
+        Carta::Siete,
+        Carta::Sota,
+        Carta::Caballo,
+        Carta::Rey,
+    ];
 }
 
 impl From<&Carta> for char {
     fn from(other: &Carta) -> char {
         match other {
             Carta::As => '1',
-            Carta::Dos => '2',
-            Carta::Tres => '3',
+            Carta::Dos | Carta::DosCuatroReyes => '2',
+            Carta::Tres | Carta::TresCuatroReyes => '3',
             Carta::Cuatro => '4',
             Carta::Cinco => '5',
             Carta::Seis => '6',
@@ -146,6 +170,24 @@ mod tests {
         assert!(Carta::Caballo < Carta::Tres);
     }
 
+    #[test]
+    fn comparar_valor_cuatro_reyes() {
+        assert!(Carta::As < Carta::DosCuatroReyes);
+        assert!(Carta::DosCuatroReyes < Carta::TresCuatroReyes);
+        assert!(Carta::TresCuatroReyes < Carta::Cuatro);
+        assert_ne!(Carta::TresCuatroReyes, Carta::Tres);
+        assert_eq!(char::from(&Carta::TresCuatroReyes), '3');
+        for carta in Carta::CARTAS_CUATRO_REYES {
+            assert_eq!(
+                Carta::CARTAS_CUATRO_REYES
+                    .iter()
+                    .filter(|c| **c == carta)
+                    .count(),
+                1
+            );
+        }
+    }
+
     #[test]
     fn try_from_char() {
         let chars = ['A', '1', '2', '3', '4', '5', '6', '7', 'S', 'C', 'R'];
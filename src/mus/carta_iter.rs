@@ -2,9 +2,7 @@ use std::ops::Range;
 
 use itertools::{CombinationsWithReplacement, Itertools};
 
-use crate::mus::Baraja;
-
-use super::Carta;
+use super::{Carta, FrecuenciasBaraja, Variante};
 
 /// Mayor n con número combinatorio precalculado. Cubre de sobra la baraja de mus (40 cartas), que
 /// es el caso que recorren los iteradores de reparto millones de veces. Ampliarlo tiene un coste:
@@ -150,32 +148,35 @@ impl<const K: usize> Iterator for CombinationsWithReplacementProb<K> {
     }
 }
 
-pub struct DistribucionCartaIter<const N: usize, const M: usize> {
-    cartas: [(Carta, u8); M],
+pub struct DistribucionCartaIter<const N: usize> {
+    cartas: FrecuenciasBaraja,
     iter: CombinationsWithReplacementProb<N>,
 }
 
 /// Iterador de manos de cartas de mus.
-impl<const N: usize, const M: usize> DistribucionCartaIter<N, M> {
+impl<const N: usize> DistribucionCartaIter<N> {
     /// Crea un nuevo iterador a partir de una distribución de cartas y el número de cartas que se
     /// desean tener ne la mano. La distribución se indica con un vector de pares (Carta, u8),
     /// donde el entero indica el número de cartas disponibles de ese valor.
-    pub fn new(cartas: [(Carta, u8); M]) -> Self {
+    pub fn new(cartas: &[(Carta, u8)]) -> Self {
         let frequencies: Vec<usize> = cartas.iter().map(|(_, f)| *f as usize).collect();
         let iter = CombinationsWithReplacementProb::new(frequencies);
-        Self { cartas, iter }
+        Self {
+            cartas: cartas.iter().copied().collect(),
+            iter,
+        }
     }
 
     pub fn current_frequencies(&self) -> &[usize] {
         &self.iter.current_frequencies
     }
 
-    pub fn cartas(&self) -> [(Carta, u8); M] {
-        self.cartas
+    pub fn cartas(&self) -> FrecuenciasBaraja {
+        self.cartas.clone()
     }
 }
 
-impl<const N: usize, const M: usize> Iterator for DistribucionCartaIter<N, M> {
+impl<const N: usize> Iterator for DistribucionCartaIter<N> {
     type Item = ([Carta; N], f64);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -185,20 +186,21 @@ impl<const N: usize, const M: usize> Iterator for DistribucionCartaIter<N, M> {
     }
 }
 
-pub struct DistribucionDobleCartaIter<const N: usize, const M: usize> {
-    cartas: [(Carta, u8); M],
+pub struct DistribucionDobleCartaIter<const N: usize> {
+    cartas: FrecuenciasBaraja,
     mano_actual1: Option<([Carta; N], f64)>,
     iter1: CombinationsWithReplacementProb<N>,
     iter2: CombinationsWithReplacementProb<N>,
 }
 ///
 /// Iterador de pares de manos de mus.
-impl<const N: usize, const M: usize> DistribucionDobleCartaIter<N, M> {
+impl<const N: usize> DistribucionDobleCartaIter<N> {
     /// Crea un nuevo iterador a partir de una distribución de cartas y el número de cartas que se
     /// desean tener en cada una de las manos. La distribución se indica con un vector de pares (Carta, u8),
     /// donde el entero indica el número de cartas disponibles de ese valor.
-    pub fn new(cartas: [(Carta, u8); M]) -> Self {
+    pub fn new(cartas: &[(Carta, u8)]) -> Self {
         let frecuencias: Vec<usize> = cartas.iter().map(|(_, f)| *f as usize).collect();
+        let cartas: FrecuenciasBaraja = cartas.iter().copied().collect();
         let mut iter1 = CombinationsWithReplacementProb::new(frecuencias);
         let idx1 = iter1.next();
         match &idx1 {
@@ -239,12 +241,12 @@ impl<const N: usize, const M: usize> DistribucionDobleCartaIter<N, M> {
         &self.iter2.current_frequencies
     }
 
-    pub fn cartas(&self) -> [(Carta, u8); M] {
-        self.cartas
+    pub fn cartas(&self) -> FrecuenciasBaraja {
+        self.cartas.clone()
     }
 }
 
-impl<const N: usize, const M: usize> Iterator for DistribucionDobleCartaIter<N, M> {
+impl<const N: usize> Iterator for DistribucionDobleCartaIter<N> {
     type Item = ([Carta; N], [Carta; N], f64);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -263,7 +265,35 @@ impl<const N: usize, const M: usize> Iterator for DistribucionDobleCartaIter<N,
     }
 }
 
-type DoubleDeal = ([Carta; 4], [Carta; 4], f64, [(Carta, u8); 8]);
+/// Número de repartos distintos de `num_manos` manos de cuatro cartas a partir de la distribución
+/// `cartas`. Se cuentan por programación dinámica sobre los valores de las cartas: cada estado
+/// guarda en base 5 cuántas cartas lleva cada mano.
+fn num_repartos(cartas: &[(Carta, u8)], num_manos: u32) -> usize {
+    let num_estados = 5usize.pow(num_manos);
+    let mut formas = vec![0; num_estados];
+    formas[0] = 1;
+    for (_, frecuencia) in cartas {
+        let mut siguientes = vec![0; num_estados];
+        for (origen, n) in formas.iter().enumerate().filter(|(_, n)| **n > 0) {
+            for (destino, siguiente) in siguientes.iter_mut().enumerate().skip(origen) {
+                let (mut o, mut d) = (origen, destino);
+                let mut usadas = Some(0);
+                for _ in 0..num_manos {
+                    usadas = usadas.filter(|_| d % 5 >= o % 5).map(|u| u + d % 5 - o % 5);
+                    o /= 5;
+                    d /= 5;
+                }
+                if usadas.is_some_and(|u| u <= *frecuencia as usize) {
+                    *siguiente += n;
+                }
+            }
+        }
+        formas = siguientes;
+    }
+    formas[num_estados - 1]
+}
+
+type DoubleDeal = ([Carta; 4], [Carta; 4], f64, FrecuenciasBaraja);
 pub struct RepartoMusDosJugadoresIter {
     inner: RepartoDosManosMusIter,
     remaining: usize,
@@ -271,9 +301,14 @@ pub struct RepartoMusDosJugadoresIter {
 
 impl RepartoMusDosJugadoresIter {
     pub fn new() -> Self {
+        Self::con_variante(Variante::default())
+    }
+
+    /// Repartos de dos manos con la baraja de la variante `variante`.
+    pub fn con_variante(variante: Variante) -> Self {
         Self {
-            inner: RepartoDosManosMusIter::new(Baraja::FREC_BARAJA_MUS),
-            remaining: 104_820,
+            inner: RepartoDosManosMusIter::new(variante.frecuencias()),
+            remaining: num_repartos(variante.frecuencias(), 2),
         }
     }
 }
@@ -312,7 +347,7 @@ type QuadrupleDeal = (
     [Carta; 4],
     [Carta; 4],
     f64,
-    [(Carta, u8); 8],
+    FrecuenciasBaraja,
 );
 
 pub struct RepartoMusIter {
@@ -325,14 +360,19 @@ pub struct RepartoMusIter {
 
 impl RepartoMusIter {
     pub fn new() -> Self {
-        let mut outer = RepartoDosManosMusIter::new(Baraja::FREC_BARAJA_MUS);
+        Self::con_variante(Variante::default())
+    }
+
+    /// Repartos de cuatro manos con la baraja de la variante `variante`.
+    pub fn con_variante(variante: Variante) -> Self {
+        let mut outer = RepartoDosManosMusIter::new(variante.frecuencias());
         let actual = outer.next().map(|(mano1, mano2, prob, dist)| {
-            (mano1, mano2, prob, RepartoDosManosMusIter::new(dist))
+            (mano1, mano2, prob, RepartoDosManosMusIter::new(&dist))
         });
         Self {
             outer,
             actual,
-            remaining: 7_355_552_285,
+            remaining: num_repartos(variante.frecuencias(), 4),
         }
     }
 }
@@ -356,7 +396,7 @@ impl Iterator for RepartoMusIter {
             // Se han agotado las dos últimas manos para este reparto inicial:
             // avanzamos al siguiente reparto de las dos primeras manos.
             self.actual = self.outer.next().map(|(mano1, mano2, prob, dist)| {
-                (mano1, mano2, prob, RepartoDosManosMusIter::new(dist))
+                (mano1, mano2, prob, RepartoDosManosMusIter::new(&dist))
             });
         }
     }
@@ -368,13 +408,13 @@ impl Iterator for RepartoMusIter {
 
 impl ExactSizeIterator for RepartoMusIter {}
 
-struct RepartoDosManosMusIter(DistribucionDobleCartaIter<4, 8>);
+struct RepartoDosManosMusIter(DistribucionDobleCartaIter<4>);
 
 impl RepartoDosManosMusIter {
-    fn new(frequencies: [(Carta, u8); 8]) -> Self {
+    fn new(frequencies: &[(Carta, u8)]) -> Self {
         Self(DistribucionDobleCartaIter::new(frequencies))
     }
-    fn cartas(&self) -> [(Carta, u8); 8] {
+    fn cartas(&self) -> FrecuenciasBaraja {
         self.0.cartas()
     }
 }
@@ -394,20 +434,20 @@ impl Iterator for RepartoDosManosMusIter {
     }
 }
 
-pub struct RepartoDescarteMusIter<const N: usize>(DistribucionCartaIter<N, 8>);
+pub struct RepartoDescarteMusIter<const N: usize>(DistribucionCartaIter<N>);
 
 impl<const N: usize> RepartoDescarteMusIter<N> {
-    pub fn new(frequencies: [(Carta, u8); 8]) -> Self {
+    pub fn new(frequencies: &[(Carta, u8)]) -> Self {
         Self(DistribucionCartaIter::new(frequencies))
     }
 
-    pub fn cartas(&self) -> [(Carta, u8); 8] {
+    pub fn cartas(&self) -> FrecuenciasBaraja {
         self.0.cartas()
     }
 }
 
 impl<const N: usize> Iterator for RepartoDescarteMusIter<N> {
-    type Item = ([Carta; N], f64, [(Carta, u8); 8]);
+    type Item = ([Carta; N], f64, FrecuenciasBaraja);
 
     fn next(&mut self) -> Option<Self::Item> {
         let (mano, prob) = self.0.next()?;
@@ -466,13 +506,13 @@ mod tests {
             (Carta::Tres, 1),
             (Carta::Cuatro, 1),
         ];
-        let it = DistribucionCartaIter::<4, 4>::new(cartas);
+        let it = DistribucionCartaIter::<4>::new(&cartas);
         assert_eq!(it.count(), 1);
 
         let cartas = [(Carta::As, 2), (Carta::Cuatro, 1)];
-        let it = DistribucionCartaIter::<2, 2>::new(cartas);
+        let it = DistribucionCartaIter::<2>::new(&cartas);
         assert_eq!(it.count(), 2);
-        let mut it = DistribucionCartaIter::new(cartas);
+        let mut it = DistribucionCartaIter::new(&cartas);
         assert_eq!(it.next().unwrap(), ([Carta::As, Carta::As], 1. / 3.));
         assert_eq!(it.next().unwrap(), ([Carta::As, Carta::Cuatro], 2. / 3.));
     }
@@ -485,13 +525,13 @@ mod tests {
             (Carta::Tres, 1),
             (Carta::Cuatro, 1),
         ];
-        let it = DistribucionDobleCartaIter::<2, 4>::new(cartas);
+        let it = DistribucionDobleCartaIter::<2>::new(&cartas);
         assert_eq!(it.count(), 6);
 
         let cartas = [(Carta::As, 2), (Carta::Cuatro, 2)];
-        let it = DistribucionDobleCartaIter::<2, 2>::new(cartas);
+        let it = DistribucionDobleCartaIter::<2>::new(&cartas);
         assert_eq!(it.count(), 3);
-        let mut it = DistribucionDobleCartaIter::new(cartas);
+        let mut it = DistribucionDobleCartaIter::new(&cartas);
         assert_eq!(
             it.next().unwrap(),
             (
@@ -505,7 +545,7 @@ mod tests {
     #[test]
     fn test_current_frequencies() {
         let cartas = [(Carta::As, 2), (Carta::Cuatro, 2)];
-        let mut it = DistribucionDobleCartaIter::<2, 2>::new(cartas);
+        let mut it = DistribucionDobleCartaIter::<2>::new(&cartas);
         it.next();
         assert_eq!(it.current_frequencies(), &[0, 0]);
     }
@@ -517,5 +557,12 @@ mod tests {
         let reparto = RepartoMusDosJugadoresIter::new();
         let total_probability = reparto.fold(0., |accum, (_, _, prob, _)| accum + prob);
         assert!((total_probability - 1.).abs() < 1e-9);
+        assert_eq!(RepartoMusIter::new().len(), 7_355_552_285);
+
+        let reparto = RepartoMusDosJugadoresIter::con_variante(Variante::CuatroReyes);
+        assert_eq!(reparto.len(), reparto.count());
+        let reparto = RepartoMusDosJugadoresIter::con_variante(Variante::CuatroReyes);
+        let total_probability = reparto.fold(0., |accum, (_, _, prob, _)| accum + prob);
+        assert!((total_probability - 1.).abs() < 1e-9);
     }
 }
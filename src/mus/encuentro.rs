@@ -0,0 +1,199 @@
+use super::MusError;
+
+/// Reglas de un encuentro de mus. Un chico se gana al llegar a `tantos`, una vaca al ganar
+/// `chicos` chicos y el encuentro al ganar `vacas` vacas. Un encuentro al mejor de tres vacas
+/// de tres chicos se juega con `chicos = 2` y `vacas = 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReglasEncuentro {
+    pub tantos: u8,
+    pub chicos: u8,
+    pub vacas: u8,
+}
+
+impl Default for ReglasEncuentro {
+    fn default() -> Self {
+        Self {
+            tantos: 40,
+            chicos: 2,
+            vacas: 2,
+        }
+    }
+}
+
+/// Marcador de un encuentro de mus: los tantos del chico en curso y los chicos y vacas que
+/// lleva ganados cada pareja. Al ganar un chico se empieza el siguiente desde cero, y al ganar
+/// una vaca se ponen a cero los chicos.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encuentro {
+    reglas: ReglasEncuentro,
+    tantos: [u8; 2],
+    chicos: [u8; 2],
+    vacas: [u8; 2],
+}
+
+impl Encuentro {
+    pub fn new(reglas: ReglasEncuentro) -> Result<Self, MusError> {
+        if reglas.tantos == 0 || reglas.chicos == 0 || reglas.vacas == 0 {
+            return Err(MusError::ReglasNoValidas);
+        }
+        Ok(Self {
+            reglas,
+            tantos: [0, 0],
+            chicos: [0, 0],
+            vacas: [0, 0],
+        })
+    }
+
+    pub fn reglas(&self) -> &ReglasEncuentro {
+        &self.reglas
+    }
+
+    /// Tantos de cada pareja en el chico en curso.
+    pub fn tantos(&self) -> &[u8; 2] {
+        &self.tantos
+    }
+
+    /// Chicos de cada pareja en la vaca en curso.
+    pub fn chicos(&self) -> &[u8; 2] {
+        &self.chicos
+    }
+
+    pub fn vacas(&self) -> &[u8; 2] {
+        &self.vacas
+    }
+
+    /// Devuelve la pareja que ha ganado el encuentro, o None si aún no ha terminado.
+    pub fn ganador(&self) -> Option<usize> {
+        self.vacas.iter().position(|v| *v == self.reglas.vacas)
+    }
+
+    /// Anota tantos a una pareja. Si llega a los tantos del chico, lo gana aunque se pase.
+    pub fn anotar(&mut self, pareja: usize, tantos: u8) -> Result<(), MusError> {
+        if self.ganador().is_some() {
+            return Err(MusError::EncuentroTerminado);
+        }
+        self.tantos[pareja] = self.tantos[pareja].saturating_add(tantos);
+        if self.tantos[pareja] >= self.reglas.tantos {
+            self.ganar_chico(pareja);
+        }
+        Ok(())
+    }
+
+    fn ganar_chico(&mut self, pareja: usize) {
+        self.tantos = [0, 0];
+        self.chicos[pareja] += 1;
+        if self.chicos[pareja] == self.reglas.chicos {
+            self.chicos = [0, 0];
+            self.vacas[pareja] += 1;
+        }
+    }
+
+    /// Probabilidad de que `pareja` gane el encuentro si gana cada chico con probabilidad
+    /// `p_chico`, independientemente de los demás. No tiene en cuenta los tantos del chico en
+    /// curso: es la probabilidad al empezar un chico con el marcador actual de chicos y vacas.
+    pub fn probabilidad_victoria(&self, pareja: usize, p_chico: f64) -> f64 {
+        let rival = 1 - pareja;
+        if let Some(ganador) = self.ganador() {
+            return if ganador == pareja { 1. } else { 0. };
+        }
+        let p_vaca_actual = probabilidad_serie(
+            [self.chicos[pareja], self.chicos[rival]],
+            self.reglas.chicos,
+            p_chico,
+        );
+        let p_vaca = probabilidad_serie([0, 0], self.reglas.chicos, p_chico);
+        let mut vacas_ganando = [self.vacas[pareja], self.vacas[rival]];
+        let mut vacas_perdiendo = vacas_ganando;
+        vacas_ganando[0] += 1;
+        vacas_perdiendo[1] += 1;
+        p_vaca_actual * probabilidad_serie(vacas_ganando, self.reglas.vacas, p_vaca)
+            + (1. - p_vaca_actual) * probabilidad_serie(vacas_perdiendo, self.reglas.vacas, p_vaca)
+    }
+
+    /// Lo que sube la probabilidad de ganar el encuentro al ganar el chico en curso frente a
+    /// perderlo, suponiendo que los chicos siguientes se ganan con probabilidad 1/2. Es el mismo
+    /// valor para las dos parejas.
+    pub fn valor_chico(&self) -> f64 {
+        let mut ganando = self.clone();
+        ganando.ganar_chico(0);
+        let mut perdiendo = self.clone();
+        perdiendo.ganar_chico(1);
+        ganando.probabilidad_victoria(0, 0.5) - perdiendo.probabilidad_victoria(0, 0.5)
+    }
+}
+
+/// Probabilidad de llegar antes que el rival a `objetivo` victorias si se lleva `ganados[0]`
+/// frente a `ganados[1]` del rival y se gana cada punto con probabilidad `p`.
+fn probabilidad_serie(ganados: [u8; 2], objetivo: u8, p: f64) -> f64 {
+    let n = objetivo as usize;
+    // prob[a][b] es la probabilidad de ganar la serie con a victorias frente a b.
+    let mut prob = vec![vec![0.; n + 1]; n + 1];
+    for a in (0..=n).rev() {
+        for b in (0..=n).rev() {
+            prob[a][b] = if a == n {
+                1.
+            } else if b == n {
+                0.
+            } else {
+                p * prob[a + 1][b] + (1. - p) * prob[a][b + 1]
+            };
+        }
+    }
+    prob[(ganados[0] as usize).min(n)][(ganados[1] as usize).min(n)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encuentro() {
+        let mut encuentro = Encuentro::new(ReglasEncuentro::default()).unwrap();
+        encuentro.anotar(0, 30).unwrap();
+        encuentro.anotar(1, 35).unwrap();
+        assert_eq!(encuentro.tantos(), &[30, 35]);
+        encuentro.anotar(1, 10).unwrap();
+        assert_eq!(encuentro.tantos(), &[0, 0]);
+        assert_eq!(encuentro.chicos(), &[0, 1]);
+
+        encuentro.anotar(0, 40).unwrap();
+        encuentro.anotar(0, 40).unwrap();
+        assert_eq!(encuentro.chicos(), &[0, 0]);
+        assert_eq!(encuentro.vacas(), &[1, 0]);
+        assert_eq!(encuentro.ganador(), None);
+
+        encuentro.anotar(0, 40).unwrap();
+        encuentro.anotar(0, 40).unwrap();
+        assert_eq!(encuentro.ganador(), Some(0));
+        assert!(matches!(
+            encuentro.anotar(1, 5),
+            Err(MusError::EncuentroTerminado)
+        ));
+
+        assert!(matches!(
+            Encuentro::new(ReglasEncuentro {
+                chicos: 0,
+                ..Default::default()
+            }),
+            Err(MusError::ReglasNoValidas)
+        ));
+    }
+
+    #[test]
+    fn test_probabilidad_victoria() {
+        let mut encuentro = Encuentro::new(ReglasEncuentro::default()).unwrap();
+        assert!((encuentro.probabilidad_victoria(0, 0.5) - 0.5).abs() < 1e-12);
+        // Ganar el primer chico deja la vaca en 3/4 y el encuentro en 5/8, y perderlo en 3/8.
+        assert!((encuentro.valor_chico() - 0.25).abs() < 1e-12);
+
+        // Con una vaca y un chico ganados, solo se pierde la vaca con dos chicos seguidos del
+        // rival, y aun así queda la tercera vaca.
+        encuentro.anotar(0, 40).unwrap();
+        encuentro.anotar(0, 40).unwrap();
+        encuentro.anotar(0, 40).unwrap();
+        let p = encuentro.probabilidad_victoria(0, 0.5);
+        assert!((p - (1. - 0.5 * 0.25)).abs() < 1e-12);
+        assert!((encuentro.probabilidad_victoria(1, 0.5) - (1. - p)).abs() < 1e-12);
+        assert!((encuentro.probabilidad_victoria(0, 1.) - 1.).abs() < 1e-12);
+    }
+}
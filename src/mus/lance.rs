@@ -291,8 +291,8 @@ impl Lance {
     pub fn hay_lance(&self, manos: &[Mano]) -> bool {
         match self {
             Lance::Grande | Lance::Chica => true,
-            Lance::Pares => manos.iter().map(|m| m.hay_pares()).any(|b| b),
-            Lance::Juego => manos.iter().map(|m| m.hay_juego()).any(|b| b),
+            Lance::Pares => manos.iter().any(|m| m.hay_pares()),
+            Lance::Juego => manos.iter().any(|m| m.hay_juego()),
             Lance::Punto => !Lance::Juego.hay_lance(manos),
         }
     }
@@ -364,6 +364,7 @@ pub struct EstadoLance<T> {
     tantos_mano: [u8; 2],
     tipo_partida: T,
     idx_turno: u8,
+    num_envites: u8,
 }
 
 #[derive(Debug, Clone)]
@@ -420,6 +421,7 @@ impl EstadoLance<CuatroJugadores> {
             jugador_mejor_mano,
             tantos_mano,
             idx_turno,
+            num_envites: 0,
             tipo_partida: CuatroJugadores {
                 idx_parejas,
                 accion_pareja: None,
@@ -522,6 +524,7 @@ impl EstadoLance<DosJugadores> {
             jugador_mejor_mano,
             tantos_mano,
             idx_turno: 0,
+            num_envites: 0,
             tipo_partida: DosJugadores {},
         }
     }
@@ -586,7 +589,7 @@ impl EstadoLance<DosJugadores> {
             self.turno = None;
             return Ok(None);
         }
-        self.turno = Some(Turno::Jugador(self.idx_turno as u8));
+        self.turno = Some(Turno::Jugador(self.idx_turno));
         Ok(self.turno)
     }
 }
@@ -607,6 +610,7 @@ impl<T> EstadoLance<T> {
         self.bote[0] = self.bote[1];
         self.bote[1] = nuevo_bote;
         self.ultimo_envite = self.idx_turno;
+        self.num_envites += 1;
         Ok(())
     }
 
@@ -667,6 +671,11 @@ impl<T> EstadoLance<T> {
     pub fn apuesta_maxima(&self) -> u8 {
         self.apuesta_maxima
     }
+
+    /// Devuelve el número de envites hechos en el lance, contando el órdago.
+    pub fn num_envites(&self) -> u8 {
+        self.num_envites
+    }
 }
 
 #[cfg(test)]
@@ -10,4 +10,10 @@ pub enum MusError {
 
     #[error("Acción no válida")]
     AccionNoValida,
+
+    #[error("Reglas de encuentro no válidas")]
+    ReglasNoValidas,
+
+    #[error("El encuentro ya ha terminado")]
+    EncuentroTerminado,
 }
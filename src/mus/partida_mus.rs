@@ -11,6 +11,7 @@ use crate::mus::CuatroJugadores;
 use crate::mus::DosJugadores;
 use crate::mus::Lance;
 use crate::mus::Mano;
+use crate::mus::Sena;
 
 use super::Apuesta;
 use super::EstadoLance;
@@ -18,7 +19,21 @@ use super::MusError;
 use super::Turno;
 
 /// Acciones posibles durante una partida de mus.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialOrd,
+    Ord,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
 pub enum Accion {
     Paso,
     Quiero,
@@ -28,6 +43,10 @@ pub enum Accion {
     Mus,
     NoMus,
     Descartar([bool; 4]),
+
+    /// Seña a la pareja. La partida no las admite: son una fase de las partidas de los
+    /// solvers, que deciden quién las ve.
+    Sena(Sena),
 }
 
 impl Display for Accion {
@@ -43,6 +62,7 @@ impl Display for Accion {
                 "d{}",
                 d.iter().map(|b| if *b { 1 } else { 0 }).sum::<i32>()
             )),
+            Accion::Sena(sena) => write!(f, "s{}", sena.codigo()),
         }
     }
 }
@@ -148,8 +168,17 @@ pub struct PartidaMus<T: ModalidadMus> {
 
 impl<T: ModalidadMus> PartidaMus<T> {
     pub fn new(manos: T::N, tantos: [u8; 2]) -> Self {
+        Self::con_max_tantos(manos, tantos, FaseEnvites::<T>::MAX_TANTOS)
+    }
+
+    /// Crea una partida en la que el chico se gana al llegar a `max_tantos` en lugar de a los
+    /// 40 tantos habituales.
+    pub fn con_max_tantos(manos: T::N, tantos: [u8; 2], max_tantos: u8) -> Self {
         Self {
-            fase: Fase::Mus(FaseMus::<T>::new(manos, tantos)),
+            fase: Fase::Mus(FaseMus {
+                max_tantos,
+                ..FaseMus::<T>::new(manos, tantos)
+            }),
         }
     }
 
@@ -161,7 +190,8 @@ impl<T: ModalidadMus> PartidaMus<T> {
                     return Ok(turno);
                 }
                 let manos = fase_mus.manos.clone();
-                let fase_envites = FaseEnvites::<T>::new(manos, fase_mus.tantos);
+                let fase_envites =
+                    FaseEnvites::<T>::con_max_tantos(manos, fase_mus.tantos, fase_mus.max_tantos);
                 let turno = fase_envites.turno();
                 self.fase = Fase::Envites(fase_envites);
                 Ok(turno)
@@ -247,6 +277,7 @@ pub struct FaseMus<T: ModalidadMus> {
     turno: Option<Turno>,
     sub_fase: SubfaseMus,
     tantos: [u8; 2],
+    max_tantos: u8,
 }
 
 #[derive(Debug, Clone)]
@@ -263,6 +294,7 @@ impl<T: ModalidadMus> FaseMus<T> {
             turno: Some(T::turno_inicial_mus()),
             sub_fase: SubfaseMus::Mus,
             tantos,
+            max_tantos: FaseEnvites::<T>::MAX_TANTOS,
         }
     }
 
@@ -359,11 +391,13 @@ pub struct FaseEnvites<T: ModalidadMus> {
     manos: T::N,
     lances: ArrayVec<(Lance, Option<ResultadoLance>), 4>,
     tantos: [u8; 2],
+    max_tantos: u8,
     idx_lance: usize,
     estado_lance: Option<EstadoLance<T>>,
 }
 
 impl<T: ModalidadMus> FaseEnvites<T> {
+    /// Tantos con los que se gana el chico si no se indican otros.
     pub const MAX_TANTOS: u8 = 40;
 
     /// Crea una partida de mus con las manos recibidas como parámetro. Las manos deben estar en un
@@ -373,6 +407,11 @@ impl<T: ModalidadMus> FaseEnvites<T> {
     /// Recibe también los tantos con los que comienzan la partida
     /// cada una de las parejas.
     pub fn new(manos: T::N, tantos: [u8; 2]) -> Self {
+        Self::con_max_tantos(manos, tantos, Self::MAX_TANTOS)
+    }
+
+    /// Como [`FaseEnvites::new`], pero el chico se gana al llegar a `max_tantos`.
+    pub fn con_max_tantos(manos: T::N, tantos: [u8; 2], max_tantos: u8) -> Self {
         let mut lances = ArrayVec::new();
         lances.push((Lance::Grande, None));
         lances.push((Lance::Chica, None));
@@ -389,6 +428,7 @@ impl<T: ModalidadMus> FaseEnvites<T> {
             lances,
             idx_lance: 0,
             tantos,
+            max_tantos,
             estado_lance: None,
         };
         let e = p.crear_estado_lance(Lance::Grande);
@@ -404,6 +444,16 @@ impl<T: ModalidadMus> FaseEnvites<T> {
     /// Esto puede ocurrir por ejemplo si se desea crear una partida para el lance de pares
     /// con cuatro manos sin jugadas de pares, o que solo una de las parejas tiene pares.
     pub fn new_partida_lance(lance: Lance, manos: T::N, tantos: [u8; 2]) -> Option<Self> {
+        Self::partida_lance_con_max_tantos(lance, manos, tantos, Self::MAX_TANTOS)
+    }
+
+    /// Como [`FaseEnvites::new_partida_lance`], pero el chico se gana al llegar a `max_tantos`.
+    pub fn partida_lance_con_max_tantos(
+        lance: Lance,
+        manos: T::N,
+        tantos: [u8; 2],
+        max_tantos: u8,
+    ) -> Option<Self> {
         let mut lances = ArrayVec::<(Lance, Option<_>), 4>::new();
         lances.push((lance, None));
         let mut p = Self {
@@ -411,6 +461,7 @@ impl<T: ModalidadMus> FaseEnvites<T> {
             lances,
             idx_lance: 0,
             tantos,
+            max_tantos,
             estado_lance: None,
         };
         let e = p.crear_estado_lance(lance);
@@ -421,8 +472,8 @@ impl<T: ModalidadMus> FaseEnvites<T> {
 
     fn crear_estado_lance(&self, l: Lance) -> EstadoLance<T> {
         let tantos_restantes = [
-            Self::MAX_TANTOS - self.tantos[0],
-            Self::MAX_TANTOS - self.tantos[1],
+            self.max_tantos.saturating_sub(self.tantos[0]),
+            self.max_tantos.saturating_sub(self.tantos[1]),
         ];
         let mut e = T::nuevo_estado_lance(
             &l,
@@ -481,7 +532,7 @@ impl<T: ModalidadMus> FaseEnvites<T> {
             if let Some(g) = ganador {
                 match apuesta {
                     Apuesta::Tantos(t) => self.anotar_tantos(g, t),
-                    Apuesta::Ordago => self.anotar_tantos(g, Self::MAX_TANTOS),
+                    Apuesta::Ordago => self.anotar_tantos(g, self.max_tantos),
                 }
             }
         }
@@ -492,7 +543,7 @@ impl<T: ModalidadMus> FaseEnvites<T> {
         for l in lances {
             if let Some(r) = l.1 {
                 self.anotar_tantos(r.ganador, r.tantos);
-                if self.tantos[0] == Self::MAX_TANTOS || self.tantos[1] == Self::MAX_TANTOS {
+                if self.tantos[0] == self.max_tantos || self.tantos[1] == self.max_tantos {
                     break;
                 }
             }
@@ -510,11 +561,16 @@ impl<T: ModalidadMus> FaseEnvites<T> {
         &self.tantos
     }
 
+    /// Tantos con los que se gana el chico.
+    pub fn max_tantos(&self) -> u8 {
+        self.max_tantos
+    }
+
     fn anotar_tantos(&mut self, pareja: u8, tantos: u8) {
         let pareja = pareja as usize;
-        self.tantos[pareja] += tantos;
-        if self.tantos[pareja] >= Self::MAX_TANTOS {
-            self.tantos[pareja] = Self::MAX_TANTOS;
+        self.tantos[pareja] = self.tantos[pareja].saturating_add(tantos);
+        if self.tantos[pareja] >= self.max_tantos {
+            self.tantos[pareja] = self.max_tantos;
             self.tantos[1 - pareja] = 0;
             self.estado_lance = None;
         }
@@ -552,6 +608,13 @@ impl<T: ModalidadMus> FaseEnvites<T> {
             .map_or_else(|| 0, |estado_lance| estado_lance.apuesta_maxima())
     }
 
+    /// Devuelve el número de envites del lance en curso. Si la partida ha terminado devuelve 0.
+    pub fn num_envites(&self) -> u8 {
+        self.estado_lance
+            .as_ref()
+            .map_or_else(|| 0, |estado_lance| estado_lance.num_envites())
+    }
+
     /// Devuelve las manos de los jugadores.
     pub fn manos(&self) -> &T::N {
         &self.manos
@@ -581,10 +644,10 @@ mod tests {
     #[test]
     fn test_tanteo() {
         let manos = [
-            Mano::try_from("1234").unwrap(),
+            Mano::try_from("11R4").unwrap(),
             Mano::try_from("57SS").unwrap(),
-            Mano::try_from("3334").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("RRR4").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
 
         let mut partida = FaseEnvites::<CuatroJugadores>::new(manos, [0, 0]);
@@ -597,10 +660,10 @@ mod tests {
     #[test]
     fn test_tanteo2() {
         let manos = [
-            Mano::try_from("1234").unwrap(),
+            Mano::try_from("11R4").unwrap(),
             Mano::try_from("57SS").unwrap(),
-            Mano::try_from("3334").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("RRR4").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
 
         let mut partida = FaseEnvites::<CuatroJugadores>::new(manos, [0, 0]);
@@ -650,10 +713,10 @@ mod tests {
     #[test]
     fn test_punto_miedo() {
         let manos = [
-            Mano::try_from("1134").unwrap(),
+            Mano::try_from("11R4").unwrap(),
             Mano::try_from("571S").unwrap(),
-            Mano::try_from("3544").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("R544").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
         let mut partida =
             FaseEnvites::<CuatroJugadores>::new_partida_lance(Lance::Punto, manos, [0, 0]).unwrap();
@@ -664,10 +727,10 @@ mod tests {
         assert_eq!(partida.tantos(), &[2, 0]);
 
         let manos = [
-            Mano::try_from("1134").unwrap(),
+            Mano::try_from("11R4").unwrap(),
             Mano::try_from("571S").unwrap(),
-            Mano::try_from("3544").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("R544").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
         let mut partida =
             FaseEnvites::<CuatroJugadores>::new_partida_lance(Lance::Punto, manos, [0, 0]).unwrap();
@@ -683,10 +746,10 @@ mod tests {
     #[test]
     fn test_tanteo_limite() {
         let manos = [
-            Mano::try_from("1234").unwrap(),
+            Mano::try_from("11R4").unwrap(),
             Mano::try_from("57SS").unwrap(),
-            Mano::try_from("3334").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("RRR4").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
 
         // Grande
@@ -721,10 +784,10 @@ mod tests {
         assert_eq!(partida.tantos(), &[40, 0]);
 
         let manos = [
-            Mano::try_from("1234").unwrap(),
+            Mano::try_from("11R4").unwrap(),
             Mano::try_from("57SS").unwrap(),
-            Mano::try_from("3334").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("RRR4").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
 
         let mut partida = FaseEnvites::<CuatroJugadores>::new(manos, [29, 38]);
@@ -741,10 +804,10 @@ mod tests {
     #[test]
     fn test_ordago() {
         let manos = [
-            Mano::try_from("1234").unwrap(),
+            Mano::try_from("11R4").unwrap(),
             Mano::try_from("57SS").unwrap(),
-            Mano::try_from("3334").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("RRR4").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
         let mut partida = FaseEnvites::<CuatroJugadores>::new(manos, [0, 0]);
         let _ = partida.actuar(Accion::Ordago); // Pareja 0
@@ -760,13 +823,42 @@ mod tests {
         assert_eq!(partida.turno(), None);
     }
 
+    #[test]
+    fn test_max_tantos() {
+        let manos = [
+            Mano::try_from("11R4").unwrap(),
+            Mano::try_from("57SS").unwrap(),
+            Mano::try_from("RRR4").unwrap(),
+            Mano::try_from("157C").unwrap(),
+        ];
+
+        let mut partida =
+            FaseEnvites::<CuatroJugadores>::con_max_tantos(manos.clone(), [19, 28], 30);
+        let _ = partida.actuar(Accion::Envido(2)); // Pareja 0
+        let _ = partida.actuar(Accion::Envido(2)); // Pareja 0
+        let _ = partida.actuar(Accion::Envido(2)); // Pareja 1
+        let _ = partida.actuar(Accion::Envido(2));
+        let _ = partida.actuar(Accion::Paso); // Pareja 0
+        let _ = partida.actuar(Accion::Paso);
+        assert_eq!(partida.turno(), None);
+        assert_eq!(partida.tantos(), &[0, 30]); // La pareja 1 gana 2 tantos y se va.
+
+        let mut partida = FaseEnvites::<CuatroJugadores>::con_max_tantos(manos, [0, 0], 30);
+        let _ = partida.actuar(Accion::Ordago); // Pareja 0
+        let _ = partida.actuar(Accion::Ordago); // Pareja 0
+        let _ = partida.actuar(Accion::Quiero); // Pareja 1
+        let _ = partida.actuar(Accion::Quiero);
+        assert_eq!(partida.tantos(), &[30, 0]);
+        assert_eq!(partida.turno(), None);
+    }
+
     #[test]
     fn test_partida_lance() {
         let manos = [
             Mano::try_from("CC76").unwrap(),
             Mano::try_from("CCC1").unwrap(),
             Mano::try_from("1111").unwrap(),
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("157C").unwrap(),
         ];
         let mut partida_lance =
             FaseEnvites::<CuatroJugadores>::new_partida_lance(Lance::Juego, manos, [0, 0]);
@@ -778,7 +870,7 @@ mod tests {
         assert_eq!(partida_lance.as_ref().unwrap().lance_actual(), None);
         assert_eq!(partida_lance.as_ref().unwrap().tantos(), &[0, 3]);
         let manos = [
-            Mano::try_from("257C").unwrap(),
+            Mano::try_from("157C").unwrap(),
             Mano::try_from("CC76").unwrap(),
             Mano::try_from("CCC1").unwrap(),
             Mano::try_from("1111").unwrap(),
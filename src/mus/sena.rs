@@ -0,0 +1,126 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Juego, Mano, Pares};
+
+/// Señas con las que un jugador informa a su compañero de su mano. Son señas legales: solo se
+/// pueden hacer si la mano tiene la jugada que anuncian.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialOrd,
+    Ord,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub enum Sena {
+    /// Al menos dos reyes.
+    DosReyes,
+    /// Al menos tres reyes.
+    TresReyes,
+    /// Al menos dos ases.
+    DosPitos,
+    Medias,
+    Duples,
+    Treintayuna,
+    /// Sin pares ni juego.
+    Ciego,
+}
+
+impl Sena {
+    pub const SENAS: [Sena; 7] = [
+        Sena::DosReyes,
+        Sena::TresReyes,
+        Sena::DosPitos,
+        Sena::Medias,
+        Sena::Duples,
+        Sena::Treintayuna,
+        Sena::Ciego,
+    ];
+
+    /// Indica si la mano tiene la jugada que anuncia la seña.
+    pub fn corresponde(&self, mano: &Mano) -> bool {
+        let contar = |valor: u8| mano.iter().filter(|c| c.valor() == valor).count();
+        match self {
+            Sena::DosReyes => contar(12) >= 2,
+            Sena::TresReyes => contar(12) >= 3,
+            Sena::DosPitos => contar(1) >= 2,
+            Sena::Medias => matches!(mano.pares(), Some(Pares::Medias(_))),
+            Sena::Duples => matches!(mano.pares(), Some(Pares::Duples(_))),
+            Sena::Treintayuna => mano.juego() == Some(Juego::Treintayuna),
+            Sena::Ciego => !mano.hay_pares() && !mano.hay_juego(),
+        }
+    }
+
+    /// Carácter con el que se representa la seña en los conjuntos de información.
+    pub fn codigo(&self) -> char {
+        match self {
+            Sena::DosReyes => 'r',
+            Sena::TresReyes => 't',
+            Sena::DosPitos => 'a',
+            Sena::Medias => 'm',
+            Sena::Duples => 'd',
+            Sena::Treintayuna => 'j',
+            Sena::Ciego => 'c',
+        }
+    }
+}
+
+impl FromStr for Sena {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "dos-reyes" => Ok(Sena::DosReyes),
+            "tres-reyes" => Ok(Sena::TresReyes),
+            "dos-pitos" => Ok(Sena::DosPitos),
+            "medias" => Ok(Sena::Medias),
+            "duples" => Ok(Sena::Duples),
+            "31" => Ok(Sena::Treintayuna),
+            "ciego" => Ok(Sena::Ciego),
+            _ => Err(format!(
+                "Seña no válida ({s}). Debe ser dos-reyes, tres-reyes, dos-pitos, medias, duples, 31 o ciego."
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corresponde() {
+        let senas = |mano: &str| -> Vec<Sena> {
+            let mano: Mano = mano.parse().unwrap();
+            Sena::SENAS
+                .into_iter()
+                .filter(|sena| sena.corresponde(&mano))
+                .collect()
+        };
+        assert_eq!(
+            senas("RRR1"),
+            vec![
+                Sena::DosReyes,
+                Sena::TresReyes,
+                Sena::Medias,
+                Sena::Treintayuna
+            ]
+        );
+        assert_eq!(
+            senas("RR11"),
+            vec![Sena::DosReyes, Sena::DosPitos, Sena::Duples]
+        );
+        assert_eq!(senas("7541"), vec![Sena::Ciego]);
+        assert_eq!("31".parse(), Ok(Sena::Treintayuna));
+        assert!("cuatro-reyes".parse::<Sena>().is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use clap::ValueEnum;
+
+use super::{Baraja, Carta, Mano};
+
+/// Variante de reglas del mus. Determina la composición de la baraja y, con ella, qué cartas
+/// pueden aparecer en las manos y cómo se ordenan.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub enum Variante {
+    /// Mus a ocho reyes: los treses cuentan como reyes y los doses como ases.
+    #[default]
+    OchoReyes,
+    /// Mus a cuatro reyes: cada carta vale lo que indica, de modo que hay cuatro reyes, cuatro
+    /// treses, cuatro doses y cuatro ases.
+    CuatroReyes,
+}
+
+impl Variante {
+    /// Carta con la que se juega `carta` en esta variante.
+    pub fn equivalente(&self, carta: Carta) -> Carta {
+        match (self, carta) {
+            (Variante::OchoReyes, Carta::Tres) => Carta::Rey,
+            (Variante::OchoReyes, Carta::Dos) => Carta::As,
+            (Variante::CuatroReyes, Carta::Tres) => Carta::TresCuatroReyes,
+            (Variante::CuatroReyes, Carta::Dos) => Carta::DosCuatroReyes,
+            _ => carta,
+        }
+    }
+
+    /// Mano con cada carta sustituida por su equivalente. Las manos que no salen de la baraja de
+    /// la variante, como las que se leen de un texto, deben normalizarse para jugarlas a cuatro
+    /// reyes: sin normalizar, los treses de "RR33" se comparan como reyes.
+    pub fn normalizar(&self, mano: &Mano) -> Mano {
+        Mano::from_arrayvec(mano.iter().map(|carta| self.equivalente(*carta)).collect())
+    }
+
+    /// Cartas distintas que hay en la baraja, de menor a mayor.
+    pub fn cartas(&self) -> &'static [Carta] {
+        match self {
+            Variante::OchoReyes => &Carta::CARTAS_MUS,
+            Variante::CuatroReyes => &Carta::CARTAS_CUATRO_REYES,
+        }
+    }
+
+    /// Número de cartas de cada valor que hay en la baraja.
+    pub fn frecuencias(&self) -> &'static [(Carta, u8)] {
+        match self {
+            Variante::OchoReyes => &Baraja::FREC_BARAJA_MUS,
+            Variante::CuatroReyes => &Baraja::FREC_BARAJA_CUATRO_REYES,
+        }
+    }
+
+    /// Devuelve la baraja de la variante sin barajar: las cuarenta cartas de la baraja española,
+    /// cada una sustituida por su equivalente.
+    pub fn baraja(&self) -> Baraja {
+        let mut baraja = Baraja::new();
+        for carta in Carta::CARTAS {
+            for _ in 0..4 {
+                baraja.insertar(self.equivalente(carta));
+            }
+        }
+        baraja
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::mus::{Lance, Mano, Pares, RankingManos};
+
+    use super::*;
+
+    #[test]
+    fn test_baraja() {
+        for variante in [Variante::OchoReyes, Variante::CuatroReyes] {
+            let mut baraja = variante.baraja();
+            let cartas: Vec<Carta> = (0..40).map(|_| baraja.repartir().unwrap()).collect();
+            for (carta, frecuencia) in variante.frecuencias() {
+                assert!(variante.cartas().contains(carta));
+                let num = cartas.iter().filter(|c| *c == carta).count();
+                assert_eq!(num, *frecuencia as usize, "{variante:?} {carta:?}");
+            }
+            assert_eq!(variante.cartas().len(), variante.frecuencias().len());
+        }
+        assert_eq!(Variante::OchoReyes.equivalente(Carta::Tres), Carta::Rey);
+        assert_eq!(
+            Variante::CuatroReyes.equivalente(Carta::Tres),
+            Carta::TresCuatroReyes
+        );
+    }
+
+    #[test]
+    fn test_normalizar() {
+        let [rr33, rrrr, s321]: [Mano; 3] = ["RR33", "RRRR", "S321"].map(|m| m.parse().unwrap());
+        assert_eq!(Variante::OchoReyes.normalizar(&rr33), rrrr);
+        assert_eq!(Variante::OchoReyes.normalizar(&s321).to_string(), "RS11");
+        let rr33_cuatro_reyes = Variante::CuatroReyes.normalizar(&rr33);
+        assert_eq!(rr33_cuatro_reyes.to_string(), "RR33");
+        assert_ne!(rr33_cuatro_reyes, rrrr);
+    }
+
+    #[test]
+    fn test_cuatro_reyes() {
+        let [rr43, rr33, rr42, s321]: [Mano; 4] = ["RR43", "RR33", "RR42", "S321"]
+            .map(|m| Variante::CuatroReyes.normalizar(&m.parse().unwrap()));
+        // Los treses y los doses ya no son reyes ni ases.
+        assert_eq!(Lance::Grande.compara_manos(&rr43, &rr33), Ordering::Greater);
+        assert_eq!(Lance::Chica.compara_manos(&rr42, &rr43), Ordering::Greater);
+        assert_eq!(rr33.pares(), Some(Pares::Duples(1 << 3 | 1 << 12)));
+        assert_eq!(s321.pares(), None);
+        assert_eq!(s321.valor_puntos(), 16);
+    }
+}
@@ -5,12 +5,36 @@ pub use lance_game::*;
 mod mus_game;
 pub use mus_game::*;
 
+mod lbr;
+pub use lbr::*;
+
 mod trainer;
 pub use trainer::*;
 
 mod strategy;
 pub use strategy::*;
 
+mod fallback;
+pub use fallback::*;
+
+mod report;
+pub use report::*;
+
+mod checkpoint;
+pub use checkpoint::*;
+
+mod bet_abstraction;
+pub use bet_abstraction::*;
+
+mod discard_abstraction;
+pub use discard_abstraction::*;
+
+mod signals;
+pub use signals::*;
+
+mod utility_table;
+pub use utility_table::*;
+
 mod abstract_lance;
 pub use abstract_lance::*;
 
@@ -0,0 +1,619 @@
+use std::{fs, path::Path, str::FromStr, sync::LazyLock};
+
+use crate::{
+    ActionNode,
+    mus::{Accion, Apuesta, DosJugadores, EstadoLance, FaseEnvites, Lance, Turno},
+};
+
+use super::SolverError;
+
+/// Tamaño de un envite.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub enum BetSize {
+    /// Número fijo de tantos.
+    Tantos(u8),
+    /// Porcentaje de los tantos que quedan por ganar en la partida, redondeado y de al menos dos
+    /// tantos.
+    Resto(u8),
+}
+
+impl BetSize {
+    fn tantos(&self, restantes: u8) -> u8 {
+        match *self {
+            BetSize::Tantos(tantos) => tantos,
+            BetSize::Resto(porcentaje) => {
+                ((restantes as f64 * porcentaje as f64 / 100.).round() as u8).max(2)
+            }
+        }
+    }
+}
+
+impl FromStr for BetSize {
+    type Err = String;
+
+    /// Un número de tantos, como `3`, o un porcentaje de los tantos restantes, como `50%`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (valor, variante): (&str, fn(u8) -> Self) = match s.strip_suffix('%') {
+            Some(porcentaje) => (porcentaje, BetSize::Resto),
+            None => (s, BetSize::Tantos),
+        };
+        match valor.trim().parse() {
+            Ok(v) if v > 0 => Ok(variante(v)),
+            _ => Err(format!(
+                "Envite no válido ({s}). Debe ser un número de tantos, como 3, o un porcentaje de los tantos restantes, como 50%."
+            )),
+        }
+    }
+}
+
+/// Envites que pueden hacerse mientras la apuesta en curso no supere `max_bet` tantos.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub struct RaiseRule {
+    pub max_bet: u8,
+    pub sizes: Vec<BetSize>,
+}
+
+/// Envites de un lance.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub struct BetSizes {
+    /// Se aplica la primera regla cuyo `max_bet` sea mayor o igual que los tantos envidados hasta
+    /// el momento, incluidos los que aún no se han querido. Si no hay ninguna solo se puede
+    /// echar órdago.
+    pub rules: Vec<RaiseRule>,
+    /// Número máximo de envites del lance. Alcanzado, solo se puede pasar, querer o echar
+    /// órdago.
+    pub max_raises: Option<u8>,
+}
+
+/// Árbol de envites de un lance. Los jugadores del árbol son parejas: 0 es la pareja que habla
+/// primero en el lance y 1 la otra. Cuando los dos miembros de una pareja hablan, la acción del
+/// árbol es la de la pareja.
+pub type ActionTree = ActionNode<usize, Accion>;
+
+/// Abstracción de los envites: qué envites pueden hacerse en cada momento de la fase de envites.
+/// Con reglas, el órdago, el paso y el quiero siempre están disponibles cuando el reglamento los
+/// permite. Con un árbol de envites, solo las acciones del árbol.
+///
+/// Forma parte de la configuración de la partida porque cambia el árbol de juego: una estrategia
+/// solo sirve para la abstracción con la que se ha entrenado.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub struct BetAbstraction {
+    /// Envites de los lances que no tienen envites propios.
+    pub default: BetSizes,
+    /// Envites propios de algunos lances.
+    pub lances: Vec<(Lance, BetSizes)>,
+    /// Árbol de envites común a todos los lances. Si existe, sustituye a las reglas.
+    #[serde(default)]
+    pub action_tree: Option<ActionTree>,
+}
+
+impl Default for BetAbstraction {
+    /// Envites de 2, 5 y 10 tantos para abrir o subir un envite de 2, y de 10 para subir uno de
+    /// 4 o 5.
+    fn default() -> Self {
+        Self {
+            default: BetSizes {
+                rules: vec![
+                    RaiseRule {
+                        max_bet: 2,
+                        sizes: vec![BetSize::Tantos(2), BetSize::Tantos(5), BetSize::Tantos(10)],
+                    },
+                    RaiseRule {
+                        max_bet: 5,
+                        sizes: vec![BetSize::Tantos(10)],
+                    },
+                ],
+                max_raises: None,
+            },
+            lances: Vec::new(),
+            action_tree: None,
+        }
+    }
+}
+
+static DEFAULT_BET_ABSTRACTION: LazyLock<BetAbstraction> = LazyLock::new(BetAbstraction::default);
+
+impl BetAbstraction {
+    /// La abstracción de una partida, o la abstracción por defecto si no tiene. Permite que las
+    /// partidas guarden `None` sin reservar memoria para la abstracción por defecto.
+    pub(crate) fn or_default(bet_abstraction: Option<&Self>) -> &Self {
+        bet_abstraction.unwrap_or(&DEFAULT_BET_ABSTRACTION)
+    }
+
+    /// Abstracción con los mismos envites para cualquier apuesta en curso y en todos los lances.
+    pub fn uniform(sizes: Vec<BetSize>) -> Self {
+        Self {
+            default: BetSizes {
+                rules: vec![RaiseRule {
+                    max_bet: u8::MAX,
+                    sizes,
+                }],
+                max_raises: None,
+            },
+            lances: Vec::new(),
+            action_tree: None,
+        }
+    }
+
+    /// Abstracción que sigue el árbol de envites del fichero JSON `path`.
+    pub fn from_action_tree(path: impl AsRef<Path>) -> Result<Self, SolverError> {
+        let action_tree = ActionNode::from_file(path.as_ref()).map_err(|err| {
+            SolverError::InvalidActionTreePath(err, path.as_ref().display().to_string())
+        })?;
+        Self::default().with_action_tree(action_tree)
+    }
+
+    /// Sustituye las reglas por un árbol de envites, tras comprobar que cada secuencia del árbol
+    /// es una secuencia de envites válida de un lance. El marcador solo cambia los envites a
+    /// través de los tantos que quedan por ganar, así que también se comprueban las acciones que
+    /// da el árbol con cada límite de tantos.
+    pub fn with_action_tree(mut self, action_tree: ActionTree) -> Result<Self, SolverError> {
+        let lance = |apuesta_maxima| {
+            EstadoLance::<DosJugadores>::con_jugadores(
+                &Lance::Grande,
+                &[0, 1],
+                [0, 0],
+                0,
+                apuesta_maxima,
+            )
+        };
+        let max_tantos = FaseEnvites::<DosJugadores>::MAX_TANTOS;
+        validate_action_tree(&action_tree, &lance(max_tantos), &mut Vec::new())?;
+        for apuesta_maxima in 1..=max_tantos {
+            validate_apuesta_maxima(
+                &action_tree,
+                &lance(apuesta_maxima),
+                apuesta_maxima,
+                &mut Vec::new(),
+            )?;
+        }
+        self.action_tree = Some(action_tree);
+        Ok(self)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SolverError> {
+        let contents = fs::read_to_string(path.as_ref()).map_err(|err| {
+            SolverError::InvalidBetAbstractionPath(err, path.as_ref().display().to_string())
+        })?;
+        let mut bet_abstraction: Self =
+            serde_json::from_str(&contents).map_err(SolverError::ParseBetAbstractionError)?;
+        match bet_abstraction.action_tree.take() {
+            Some(action_tree) => bet_abstraction.with_action_tree(action_tree),
+            None => Ok(bet_abstraction),
+        }
+    }
+
+    fn bet_sizes(&self, lance: Lance) -> &BetSizes {
+        self.lances
+            .iter()
+            .find(|(l, _)| *l == lance)
+            .map_or(&self.default, |(_, sizes)| sizes)
+    }
+
+    /// Acciones legales en un lance con la apuesta en curso `ultima_apuesta`, tras `num_envites`
+    /// envites. `apuesta_maxima` son los tantos que quedan por ganar en la partida: los envites
+    /// que la alcanzan se sustituyen por el órdago.
+    ///
+    /// Con un árbol de envites, las acciones son las del nodo al que lleva `historial`, las
+    /// acciones de pareja del lance hasta el momento. Las partidas solo necesitan llevar el
+    /// historial si [`BetAbstraction::action_tree`] existe.
+    pub fn actions(
+        &self,
+        lance: Lance,
+        ultima_apuesta: Apuesta,
+        num_envites: u8,
+        apuesta_maxima: u8,
+        historial: &[Accion],
+    ) -> Vec<Accion> {
+        if let Some(action_tree) = &self.action_tree {
+            return Self::action_tree_actions(
+                action_tree,
+                ultima_apuesta,
+                apuesta_maxima,
+                historial,
+            )
+            .expect(
+                "El árbol de envites se comprueba al cargarlo: no termina antes que el lance.",
+            );
+        }
+        let tantos = match ultima_apuesta {
+            Apuesta::Ordago => return vec![Accion::Paso, Accion::Quiero],
+            Apuesta::Tantos(tantos) => tantos,
+        };
+        let mut acciones = if tantos == 0 {
+            vec![Accion::Paso]
+        } else {
+            vec![Accion::Paso, Accion::Quiero]
+        };
+        let bet_sizes = self.bet_sizes(lance);
+        let rule = bet_sizes
+            .rules
+            .iter()
+            .find(|rule| tantos <= rule.max_bet)
+            .filter(|_| {
+                bet_sizes
+                    .max_raises
+                    .is_none_or(|max_raises| num_envites < max_raises)
+            });
+        if let Some(rule) = rule {
+            let mut envites: Vec<u8> = rule
+                .sizes
+                .iter()
+                .map(|size| size.tantos(apuesta_maxima))
+                .filter(|envite| (tantos as usize + *envite as usize) < apuesta_maxima as usize)
+                .collect();
+            envites.sort_unstable();
+            envites.dedup();
+            acciones.extend(envites.into_iter().map(Accion::Envido));
+        }
+        acciones.push(Accion::Ordago);
+        acciones
+    }
+
+    /// Acciones del nodo del árbol al que lleva `historial`. Igual que con reglas, los envites que
+    /// alcanzan `apuesta_maxima` se sustituyen por el órdago, una sola vez aunque varios envites
+    /// lo alcancen. Si el órdago sustituye a un envite, el árbol no tiene nodo para él y solo
+    /// queda aceptarlo o no. Devuelve `None` si el árbol termina en `historial`.
+    fn action_tree_actions(
+        action_tree: &ActionTree,
+        ultima_apuesta: Apuesta,
+        apuesta_maxima: u8,
+        historial: &[Accion],
+    ) -> Option<Vec<Accion>> {
+        let node = historial
+            .iter()
+            .try_fold(action_tree, |node, accion| node.next_node(*accion));
+        let tantos = match (node, ultima_apuesta) {
+            (None, Apuesta::Ordago) => return Some(vec![Accion::Paso, Accion::Quiero]),
+            (_, Apuesta::Ordago) => 0,
+            (_, Apuesta::Tantos(tantos)) => tantos,
+        };
+        let acciones = node.and_then(ActionNode::actions)?;
+        let mut filtradas = Vec::with_capacity(acciones.len());
+        for accion in acciones {
+            let accion = match accion {
+                Accion::Envido(envite)
+                    if tantos as usize + envite as usize >= apuesta_maxima as usize =>
+                {
+                    Accion::Ordago
+                }
+                accion => accion,
+            };
+            if !filtradas.contains(&accion) {
+                filtradas.push(accion);
+            }
+        }
+        Some(filtradas)
+    }
+}
+
+/// Recorre el árbol a la vez que un lance de dos parejas y comprueba que los turnos, las acciones
+/// y el final de cada secuencia coinciden con los del lance.
+fn validate_action_tree(
+    node: &ActionTree,
+    estado: &EstadoLance<DosJugadores>,
+    historial: &mut Vec<Accion>,
+) -> Result<(), SolverError> {
+    let error = invalid_action_tree;
+    let (pareja, children) = match (node, estado.turno()) {
+        (ActionNode::Terminal, None) => return Ok(()),
+        (ActionNode::Terminal, Some(_)) => {
+            return Err(error(
+                historial,
+                "the tree ends but the betting round does not".to_string(),
+            ));
+        }
+        (ActionNode::NonTerminal(..), None) => {
+            return Err(error(
+                historial,
+                "the betting round is over but the tree goes on".to_string(),
+            ));
+        }
+        (
+            ActionNode::NonTerminal(pareja, children),
+            Some(Turno::Jugador(turno) | Turno::Pareja(turno)),
+        ) => {
+            if *pareja != turno as usize {
+                return Err(error(
+                    historial,
+                    format!("couple {turno} must act, not couple {pareja}"),
+                ));
+            }
+            (pareja, children)
+        }
+    };
+    if children.is_empty() {
+        return Err(error(historial, format!("couple {pareja} has no actions")));
+    }
+    for (idx, (accion, child)) in children.iter().enumerate() {
+        if children[..idx].iter().any(|(a, _)| a == accion) {
+            return Err(error(historial, format!("repeated action {accion:?}")));
+        }
+        if *accion == Accion::Quiero && estado.ultima_apuesta() == Apuesta::Tantos(0) {
+            return Err(error(historial, "Quiero without a pending bet".to_string()));
+        }
+        let mut siguiente = estado.clone();
+        if siguiente.actuar(*accion).is_err() {
+            return Err(error(historial, format!("{accion:?} is not a valid bet")));
+        }
+        historial.push(*accion);
+        validate_action_tree(child, &siguiente, historial)?;
+        historial.pop();
+    }
+    Ok(())
+}
+
+/// Recorre las acciones que da el árbol cuando quedan `apuesta_maxima` tantos por ganar y
+/// comprueba que son legales en el lance y que el árbol no termina antes que él.
+fn validate_apuesta_maxima(
+    action_tree: &ActionTree,
+    estado: &EstadoLance<DosJugadores>,
+    apuesta_maxima: u8,
+    historial: &mut Vec<Accion>,
+) -> Result<(), SolverError> {
+    if estado.turno().is_none() {
+        return Ok(());
+    }
+    let Some(acciones) = BetAbstraction::action_tree_actions(
+        action_tree,
+        estado.ultima_apuesta(),
+        apuesta_maxima,
+        historial,
+    ) else {
+        return Err(invalid_action_tree(
+            historial,
+            format!(
+                "the tree ends but the betting round does not with {apuesta_maxima} points left"
+            ),
+        ));
+    };
+    for accion in acciones {
+        let mut siguiente = estado.clone();
+        if siguiente.actuar(accion).is_err() {
+            return Err(invalid_action_tree(
+                historial,
+                format!("{accion:?} is not a valid bet with {apuesta_maxima} points left"),
+            ));
+        }
+        historial.push(accion);
+        validate_apuesta_maxima(action_tree, &siguiente, apuesta_maxima, historial)?;
+        historial.pop();
+    }
+    Ok(())
+}
+
+fn invalid_action_tree(historial: &[Accion], reason: String) -> SolverError {
+    let historial: Vec<String> = historial.iter().map(|a| format!("{a:?}")).collect();
+    SolverError::InvalidActionTree(historial.join(", "), reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bet_abstraction() {
+        let bets = BetAbstraction::default();
+        let acciones = |tantos, num_envites| {
+            bets.actions(Lance::Grande, Apuesta::Tantos(tantos), num_envites, 40, &[])
+        };
+        assert_eq!(
+            acciones(0, 0),
+            vec![
+                Accion::Paso,
+                Accion::Envido(2),
+                Accion::Envido(5),
+                Accion::Envido(10),
+                Accion::Ordago
+            ]
+        );
+        assert_eq!(
+            acciones(2, 1),
+            vec![
+                Accion::Paso,
+                Accion::Quiero,
+                Accion::Envido(2),
+                Accion::Envido(5),
+                Accion::Envido(10),
+                Accion::Ordago
+            ]
+        );
+        assert_eq!(
+            acciones(5, 1),
+            vec![
+                Accion::Paso,
+                Accion::Quiero,
+                Accion::Envido(10),
+                Accion::Ordago
+            ]
+        );
+        assert_eq!(
+            acciones(12, 2),
+            vec![Accion::Paso, Accion::Quiero, Accion::Ordago]
+        );
+        assert_eq!(
+            bets.actions(Lance::Grande, Apuesta::Ordago, 3, 40, &[]),
+            vec![Accion::Paso, Accion::Quiero]
+        );
+        // Con 6 tantos por ganar solo cabe el envite de 2.
+        assert_eq!(
+            bets.actions(Lance::Pares, Apuesta::Tantos(2), 1, 6, &[]),
+            vec![
+                Accion::Paso,
+                Accion::Quiero,
+                Accion::Envido(2),
+                Accion::Ordago
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_bet_abstraction() {
+        let mut bets = BetAbstraction::uniform(vec![
+            BetSize::Tantos(3),
+            BetSize::Tantos(4),
+            BetSize::Resto(50),
+        ]);
+        bets.lances.push((
+            Lance::Punto,
+            BetSizes {
+                rules: vec![RaiseRule {
+                    max_bet: u8::MAX,
+                    sizes: vec![BetSize::Tantos(2)],
+                }],
+                max_raises: Some(1),
+            },
+        ));
+        assert_eq!(
+            bets.actions(Lance::Chica, Apuesta::Tantos(3), 1, 20, &[]),
+            vec![
+                Accion::Paso,
+                Accion::Quiero,
+                Accion::Envido(3),
+                Accion::Envido(4),
+                Accion::Envido(10),
+                Accion::Ordago
+            ]
+        );
+        assert_eq!("50%".parse(), Ok(BetSize::Resto(50)));
+        assert_eq!("3".parse(), Ok(BetSize::Tantos(3)));
+        assert!("0".parse::<BetSize>().is_err());
+        assert_eq!(
+            bets.actions(Lance::Punto, Apuesta::Tantos(0), 0, 20, &[]),
+            vec![Accion::Paso, Accion::Envido(2), Accion::Ordago]
+        );
+        assert_eq!(
+            bets.actions(Lance::Punto, Apuesta::Tantos(2), 1, 20, &[]),
+            vec![Accion::Paso, Accion::Quiero, Accion::Ordago]
+        );
+    }
+
+    #[test]
+    fn action_tree_bet_abstraction() {
+        let bets = BetAbstraction::from_action_tree("config/simple_action_tree.json").unwrap();
+        assert!(BetAbstraction::from_action_tree("config/action_tree.json").is_ok());
+        assert_eq!(
+            bets.actions(Lance::Grande, Apuesta::Tantos(0), 0, 40, &[]),
+            vec![Accion::Paso, Accion::Envido(2), Accion::Ordago]
+        );
+        assert_eq!(
+            bets.actions(
+                Lance::Juego,
+                Apuesta::Tantos(2),
+                1,
+                40,
+                &[Accion::Paso, Accion::Envido(2)]
+            ),
+            vec![Accion::Paso, Accion::Quiero, Accion::Ordago]
+        );
+
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&bets).unwrap();
+        let archived = rkyv::from_bytes::<BetAbstraction, rkyv::rancor::Error>(&bytes).unwrap();
+        assert_eq!(archived, bets);
+
+        // Querer sin envite previo.
+        let mut tree = ActionTree::new(0);
+        tree.add_terminal_action(Accion::Quiero);
+        assert!(matches!(
+            BetAbstraction::default().with_action_tree(tree),
+            Err(SolverError::InvalidActionTree(..))
+        ));
+        // El lance sigue tras un solo paso.
+        let mut tree = ActionTree::new(0);
+        tree.add_terminal_action(Accion::Paso);
+        assert!(BetAbstraction::default().with_action_tree(tree).is_err());
+        // Tras el paso de la pareja mano le toca a la otra pareja.
+        let mut tree = ActionTree::new(0);
+        tree.add_non_terminal_action(Accion::Paso, 0)
+            .unwrap()
+            .add_terminal_action(Accion::Paso);
+        assert!(BetAbstraction::default().with_action_tree(tree).is_err());
+        let mut tree = ActionTree::new(0);
+        tree.add_non_terminal_action(Accion::Paso, 1)
+            .unwrap()
+            .add_terminal_action(Accion::Paso);
+        assert!(BetAbstraction::default().with_action_tree(tree).is_ok());
+    }
+
+    #[test]
+    fn action_tree_apuesta_maxima() {
+        let mut tree = ActionTree::new(0);
+        tree.add_non_terminal_action(Accion::Paso, 1)
+            .unwrap()
+            .add_terminal_action(Accion::Paso);
+        let envite = tree.add_non_terminal_action(Accion::Envido(2), 1).unwrap();
+        envite.add_terminal_action(Accion::Paso);
+        envite.add_terminal_action(Accion::Quiero);
+        let resto = envite
+            .add_non_terminal_action(Accion::Envido(10), 0)
+            .unwrap();
+        resto.add_terminal_action(Accion::Paso);
+        resto.add_terminal_action(Accion::Quiero);
+        let envite = tree.add_non_terminal_action(Accion::Envido(10), 1).unwrap();
+        envite.add_terminal_action(Accion::Paso);
+        envite.add_terminal_action(Accion::Quiero);
+        let bets = BetAbstraction::default().with_action_tree(tree).unwrap();
+
+        assert_eq!(
+            bets.actions(Lance::Grande, Apuesta::Tantos(0), 0, 40, &[]),
+            vec![Accion::Paso, Accion::Envido(2), Accion::Envido(10)]
+        );
+        // Los envites que alcanzan los tantos que quedan se sustituyen por un único órdago.
+        assert_eq!(
+            bets.actions(Lance::Grande, Apuesta::Tantos(0), 0, 10, &[]),
+            vec![Accion::Paso, Accion::Envido(2), Accion::Ordago]
+        );
+        let historial = [Accion::Envido(2)];
+        assert_eq!(
+            bets.actions(Lance::Grande, Apuesta::Tantos(2), 1, 10, &historial),
+            vec![Accion::Paso, Accion::Quiero, Accion::Ordago]
+        );
+        // El órdago que sustituye a un envite no está en el árbol: solo puede aceptarse o no.
+        let historial = [Accion::Envido(2), Accion::Ordago];
+        assert_eq!(
+            bets.actions(Lance::Grande, Apuesta::Ordago, 2, 10, &historial),
+            vec![Accion::Paso, Accion::Quiero]
+        );
+    }
+}
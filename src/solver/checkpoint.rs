@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    mem,
+    path::Path,
+};
+
+use rkyv::ser::writer::IoWriter;
+
+use crate::{Cfr, InfoSetKey, InfoSetNames, Node, RngState, StopReason, WarmStart};
+
+use super::{GameConfig, SolverError, TrainerConfig};
+
+/// Estado completo de un entrenamiento en curso. A diferencia de `Strategy`, que solo guarda la
+/// estrategia media, conserva los regrets y las sumas de estrategia de todos los nodos para que
+/// el entrenamiento pueda continuar exactamente donde se interrumpió.
+#[derive(Debug, Clone, rkyv::Serialize, rkyv::Deserialize, rkyv::Archive)]
+pub struct Checkpoint {
+    pub trainer_config: TrainerConfig,
+    pub game_config: GameConfig,
+    /// Marcador inicial con el que se lanzó el entrenamiento.
+    pub target: [u8; 2],
+    /// Marcador que se estaba entrenando al guardar el checkpoint.
+    pub tantos: [u8; 2],
+    /// Iteraciones completadas del marcador `tantos`. El bloque de descuento en curso se deduce
+    /// de este contador.
+    pub iteration: usize,
+    /// Utilidades esperadas de los marcadores ya entrenados.
+    pub utility_table: Vec<Vec<f64>>,
+    /// Motivo por el que terminó cada marcador ya entrenado.
+    pub stop_reasons: Vec<([u8; 2], StopReason)>,
+    /// Nodos y conjuntos de información en texto tal como los guarda `Cfr`, para no tener que
+    /// copiarlos al guardar el checkpoint. Ver `Checkpoint::save`.
+    pub(crate) nodes: HashMap<InfoSetKey, Node>,
+    pub(crate) info_sets: InfoSetNames,
+    /// Estrategia inicial con la que se crean los nodos nuevos, si el entrenamiento empezó desde
+    /// otra estrategia. Los marcadores que quedan por entrenar crean nodos nuevos, así que sin
+    /// ella la continuación divergiría del entrenamiento sin interrupción.
+    pub(crate) warm_start: WarmStart,
+    /// Estado del generador aleatorio, para que al reanudar continúe la misma secuencia que sin
+    /// interrupción.
+    pub rng_state: RngState,
+}
+
+impl Checkpoint {
+    pub fn cfr(&self) -> Cfr {
+        let mut cfr = Cfr {
+            nodes: self.nodes.clone(),
+            info_sets: self.info_sets.clone(),
+            warm_start: self.warm_start.clone(),
+            ..Cfr::new()
+        };
+        cfr.set_rng_state(&self.rng_state);
+        cfr
+    }
+
+    /// Igual que `Cfr::from_strategy` con la estrategia media de los nodos del checkpoint. Los
+    /// regrets no se conservan.
+    pub fn warm_start(&self, weight: f64) -> Cfr {
+        let strategies = self.nodes.iter().map(|(key, node)| {
+            let info_set = self
+                .info_sets
+                .get(*key)
+                .expect("Todos los nodos deben tener su conjunto de información.");
+            (info_set, node.get_average_strategy())
+        });
+        Cfr::new().with_warm_start(strategies, weight)
+    }
+
+    /// Guarda el checkpoint con los nodos y la estrategia inicial de `cfr`, que sustituyen a los
+    /// del checkpoint. No se copian: se toman de `cfr` mientras se escribe el fichero y se le
+    /// devuelven al terminar, aunque la escritura falle.
+    pub(crate) fn save(mut self, cfr: &mut Cfr, path: impl AsRef<Path>) -> Result<(), SolverError> {
+        self.nodes = mem::take(&mut cfr.nodes);
+        self.info_sets = mem::take(&mut cfr.info_sets);
+        self.warm_start = mem::take(&mut cfr.warm_start);
+        let result = self.to_file(path);
+        cfr.nodes = self.nodes;
+        cfr.info_sets = self.info_sets;
+        cfr.warm_start = self.warm_start;
+        result
+    }
+
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<(), SolverError> {
+        let path_error =
+            |err| SolverError::InvalidCheckpointPath(err, path.as_ref().display().to_string());
+        // Se escribe primero a un fichero temporal para no corromper el checkpoint anterior si
+        // el proceso muere a mitad de escritura. Se serializa directamente al fichero para no
+        // tener en memoria una copia de los nodos serializados.
+        let tmp_path = path.as_ref().with_extension("tmp");
+        let writer = IoWriter::new(BufWriter::new(File::create(&tmp_path).map_err(path_error)?));
+        let mut writer = rkyv::api::high::to_bytes_in::<_, rkyv::rancor::Error>(self, writer)
+            .map_err(SolverError::ParseCheckpointError)?
+            .into_inner();
+        writer
+            .flush()
+            .and_then(|_| fs::rename(&tmp_path, path.as_ref()))
+            .map_err(path_error)?;
+        Ok(())
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SolverError> {
+        let contents = fs::read(path.as_ref()).map_err(|err| {
+            SolverError::InvalidCheckpointPath(err, path.as_ref().display().to_string())
+        })?;
+        rkyv::from_bytes::<Self, rkyv::rancor::Error>(&contents)
+            .map_err(SolverError::ParseCheckpointError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mus::Lance,
+        solver::{GameType, UtilityTable},
+    };
+
+    #[test]
+    fn checkpoint_roundtrip() {
+        let mut node = Node::new(2);
+        node.regret_sum = vec![1.5, -3.];
+        node.update_strategy();
+        node.update_strategy_sum(2.);
+        let checkpoint = Checkpoint {
+            trainer_config: TrainerConfig {
+                method: crate::CfrMethod::ExternalSampling,
+                iterations: 1000,
+                threads: 4,
+                seed: 7,
+                ..Default::default()
+            },
+            game_config: GameConfig::new(GameType::LanceGame(Lance::Grande)),
+            target: [0, 0],
+            tantos: [12, 30],
+            iteration: 500,
+            utility_table: UtilityTable::diferencia_tantos(40),
+            stop_reasons: vec![([39, 39], StopReason::TargetExploitability)],
+            nodes: HashMap::new(),
+            info_sets: InfoSetNames::default(),
+            warm_start: WarmStart::default(),
+            rng_state: Cfr::new().with_seed(3).rng_state(),
+        };
+        let mut cfr = Cfr::from_nodes(HashMap::from([("0,G,".to_owned(), node)]))
+            .with_warm_start([("1,G,".to_owned(), vec![0.25, 0.75])], 10.);
+        let path = std::env::temp_dir().join("musolver_checkpoint_roundtrip.ckpt");
+        checkpoint.clone().save(&mut cfr, &path).unwrap();
+        // Los nodos vuelven a `cfr` tras guardarlos.
+        assert_eq!(cfr.num_nodes(), 1);
+        let loaded = Checkpoint::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.tantos, [12, 30]);
+        assert_eq!(loaded.iteration, 500);
+        assert_eq!(loaded.trainer_config.threads, 4);
+        assert_eq!(loaded.utility_table, checkpoint.utility_table);
+        assert_eq!(loaded.stop_reasons, checkpoint.stop_reasons);
+        let cfr = loaded.cfr();
+        assert_eq!(cfr.rng_state(), checkpoint.rng_state);
+        let node = cfr.node("0,G,").unwrap();
+        assert_eq!(node.regret_sum, vec![1.5, -3.]);
+        assert_eq!(node.get_average_strategy(), vec![1., 0.]);
+        // Los nodos nuevos siguen partiendo de la estrategia inicial.
+        let new_node = cfr
+            .warm_start
+            .new_node(crate::InfoSetHasher::hash_str("1,G,"), 2);
+        assert_eq!(new_node.get_average_strategy(), vec![0.25, 0.75]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use arrayvec::ArrayVec;
+
+use crate::mus::{Accion, Carta, Lance, Mano};
+
+/// Descarte que conserva las `keep` mejores cartas de la mano para un lance y descarta el resto.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub struct KeepBest {
+    pub lance: Lance,
+    /// Cartas que se conservan, de 0 a 3.
+    pub keep: u8,
+}
+
+impl KeepBest {
+    /// Posiciones de la mano que se descartan. En grande, juego y punto se conservan las cartas
+    /// más altas; en chica, las más bajas; y en pares, las que se repiten más veces y, entre
+    /// ellas, las más altas.
+    fn descarte(&self, mano: &Mano) -> [bool; 4] {
+        let cartas = mano.cartas();
+        // Las manos están ordenadas de mayor a menor.
+        let mut orden: [usize; 4] = [0, 1, 2, 3];
+        match self.lance {
+            Lance::Grande | Lance::Juego | Lance::Punto => {}
+            Lance::Chica => orden.reverse(),
+            Lance::Pares => orden.sort_by_key(|&idx| {
+                std::cmp::Reverse(cartas.iter().filter(|c| **c == cartas[idx]).count())
+            }),
+        }
+        let mut descartes = [true; 4];
+        for &idx in orden.iter().take(self.keep.min(3) as usize) {
+            descartes[idx] = false;
+        }
+        descartes
+    }
+}
+
+impl FromStr for KeepBest {
+    type Err = String;
+
+    /// Un lance y el número de cartas que se conservan, como `grande:2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            format!(
+                "Descarte no válido ({s}). Debe ser un lance y las cartas que se conservan, de 0 a 3, como grande:2."
+            )
+        };
+        let (lance, keep) = s.split_once(':').ok_or_else(error)?;
+        let lance = match lance.trim().to_lowercase().as_str() {
+            "grande" => Lance::Grande,
+            "chica" => Lance::Chica,
+            "pares" => Lance::Pares,
+            "punto" => Lance::Punto,
+            "juego" => Lance::Juego,
+            _ => return Err(error()),
+        };
+        match keep.trim().parse() {
+            Ok(keep) if keep <= 3 => Ok(Self { lance, keep }),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Abstracción de los descartes: qué descartes puede elegir cada jugador en la fase de
+/// descartes. Los descartes que tiran las mismas cartas son una única acción.
+///
+/// Como la abstracción de envites, forma parte de la configuración de la partida porque cambia
+/// el árbol de juego.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub enum DiscardAbstraction {
+    /// Un único descarte: todas las cartas salvo los reyes, o un rey si la mano tiene cuatro.
+    #[default]
+    KeepKings,
+    /// Todos los descartes posibles, uno por cada subconjunto no vacío de la mano.
+    All,
+    /// Un descarte por cada lance y número de cartas que se conservan.
+    KeepBest(Vec<KeepBest>),
+}
+
+static DEFAULT_DISCARD_ABSTRACTION: DiscardAbstraction = DiscardAbstraction::KeepKings;
+
+impl DiscardAbstraction {
+    /// La abstracción de una partida, o la abstracción por defecto si no tiene.
+    pub(crate) fn or_default(discard_abstraction: Option<&Self>) -> &Self {
+        discard_abstraction.unwrap_or(&DEFAULT_DISCARD_ABSTRACTION)
+    }
+
+    /// Descartes legales para la mano `mano`.
+    pub fn actions(&self, mano: &Mano) -> Vec<Accion> {
+        let descartes: Vec<[bool; 4]> = match self {
+            DiscardAbstraction::KeepKings => {
+                let mut descartes = [false; 4];
+                for (idx, carta) in mano.iter().enumerate() {
+                    descartes[idx] = *carta != Carta::Rey;
+                }
+                if descartes == [false; 4] {
+                    descartes[0] = true;
+                }
+                vec![descartes]
+            }
+            DiscardAbstraction::All => (1..16u8)
+                .map(|bits| std::array::from_fn(|idx| bits & (1 << idx) != 0))
+                .collect(),
+            DiscardAbstraction::KeepBest(objetivos) => objetivos
+                .iter()
+                .map(|objetivo| objetivo.descarte(mano))
+                .collect(),
+        };
+        let mut descartadas: Vec<ArrayVec<Carta, 4>> = Vec::with_capacity(descartes.len());
+        let mut acciones = Vec::with_capacity(descartes.len());
+        for descarte in descartes {
+            let cartas: ArrayVec<Carta, 4> = mano
+                .iter()
+                .zip(descarte)
+                .filter_map(|(carta, descartada)| descartada.then_some(*carta))
+                .collect();
+            if !descartadas.contains(&cartas) {
+                descartadas.push(cartas);
+                acciones.push(Accion::Descartar(descarte));
+            }
+        }
+        acciones
+    }
+}
+
+impl FromStr for DiscardAbstraction {
+    type Err = String;
+
+    /// `kings` para descartar todo salvo los reyes, `all` para todos los descartes o una lista de
+    /// lances y cartas que se conservan separados por comas, como `grande:2,pares:2,juego:3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "kings" => Ok(DiscardAbstraction::KeepKings),
+            "all" => Ok(DiscardAbstraction::All),
+            objetivos => objetivos
+                .split(',')
+                .map(KeepBest::from_str)
+                .collect::<Result<_, _>>()
+                .map(DiscardAbstraction::KeepBest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descartes(discard_abstraction: &DiscardAbstraction, mano: &str) -> Vec<[bool; 4]> {
+        discard_abstraction
+            .actions(&mano.parse().unwrap())
+            .into_iter()
+            .map(|accion| match accion {
+                Accion::Descartar(descarte) => descarte,
+                _ => panic!("Acción de descarte no válida: {accion:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn discard_abstraction() {
+        assert_eq!(
+            descartes(&DiscardAbstraction::KeepKings, "RC51"),
+            vec![[false, true, true, true]]
+        );
+        assert_eq!(
+            descartes(&DiscardAbstraction::KeepKings, "RRRR"),
+            vec![[true, false, false, false]]
+        );
+
+        assert_eq!(descartes(&DiscardAbstraction::All, "RC51").len(), 15);
+        // Con tres reyes solo importa cuántos reyes se descartan y si se descarta el as.
+        assert_eq!(descartes(&DiscardAbstraction::All, "RRR1").len(), 7);
+        assert_eq!(descartes(&DiscardAbstraction::All, "RRRR").len(), 4);
+
+        let keep_best: DiscardAbstraction = "grande:2,chica:1,pares:2,juego:0".parse().unwrap();
+        assert_eq!(
+            descartes(&keep_best, "RC55"),
+            vec![
+                [false, false, true, true],
+                [true, true, true, false],
+                [true, true, false, false],
+                [true, true, true, true],
+            ]
+        );
+        // Conservar los dos reyes en pares es el mismo descarte que conservarlos en grande.
+        assert_eq!(descartes(&keep_best, "RR54").len(), 3);
+
+        assert_eq!("all".parse(), Ok(DiscardAbstraction::All));
+        assert!("grande:4".parse::<DiscardAbstraction>().is_err());
+        assert!("mus:1".parse::<DiscardAbstraction>().is_err());
+    }
+}
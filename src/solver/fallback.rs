@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use crate::mus::Accion;
+
+/// Alternativa a la que recurre [`Strategy::actions_with_fallback`](super::Strategy) cuando la
+/// estrategia no tiene el conjunto de información, como los estados poco visitados en los
+/// entrenamientos por muestreo. Las alternativas se prueban en el orden en que se indican.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fallback {
+    /// El conjunto de información de otra mano con la misma jugada abstracta en el lance que se
+    /// está jugando, o en grande antes de los envites. Se empieza por las manos que comparten
+    /// más cartas con la real.
+    Abstract,
+    /// El mismo historial con un marcador que difiera en hasta tantos tantos de cada pareja,
+    /// empezando por los más cercanos.
+    NeighbouringTantos(u8),
+    /// Una política fija, que siempre tiene respuesta.
+    Default(DefaultPolicy),
+}
+
+/// Política fija para los conjuntos de información que no están en la estrategia.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefaultPolicy {
+    /// Todas las acciones con la misma probabilidad.
+    Uniform,
+    /// Siempre la primera acción: mus, el primer descarte o paso.
+    FirstAction,
+}
+
+impl Fallback {
+    /// Cadena por defecto: la mano abstracta, marcadores a dos tantos como mucho y, si no hay
+    /// nada, acciones uniformes.
+    pub const DEFAULT_CHAIN: [Fallback; 3] = [
+        Fallback::Abstract,
+        Fallback::NeighbouringTantos(2),
+        Fallback::Default(DefaultPolicy::Uniform),
+    ];
+}
+
+impl DefaultPolicy {
+    pub fn probabilities(&self, actions: &[Accion]) -> Vec<f64> {
+        match self {
+            DefaultPolicy::Uniform => vec![1. / actions.len() as f64; actions.len()],
+            DefaultPolicy::FirstAction => {
+                let mut probabilities = vec![0.; actions.len()];
+                probabilities[0] = 1.;
+                probabilities
+            }
+        }
+    }
+}
+
+impl FromStr for Fallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "abstract" => Ok(Fallback::Abstract),
+            "uniform" => Ok(Fallback::Default(DefaultPolicy::Uniform)),
+            "first-action" => Ok(Fallback::Default(DefaultPolicy::FirstAction)),
+            other => other
+                .strip_prefix("tantos:")
+                .and_then(|distance| distance.parse().ok())
+                .map(Fallback::NeighbouringTantos)
+                .ok_or_else(|| {
+                    format!(
+                        "Alternativa no válida ({s}). Debe ser abstract, tantos:N, uniform o first-action."
+                    )
+                }),
+        }
+    }
+}
+
+/// Acciones y probabilidades de la estrategia para una situación de la partida, junto con la
+/// alternativa que las ha dado, o `None` si estaban en la estrategia.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyLookup {
+    pub actions: Vec<Accion>,
+    pub probabilities: Vec<f64>,
+    pub fallback: Option<Fallback>,
+}
+
+impl StrategyLookup {
+    /// Las mismas probabilidades, escaladas para que sumen uno: la estrategia las guarda
+    /// redondeadas. Si todas se han redondeado a cero, se usa la estrategia uniforme.
+    pub fn normalized(self) -> Self {
+        let total: f64 = self.probabilities.iter().sum();
+        let probabilities = if total > 0. {
+            self.probabilities.iter().map(|p| p / total).collect()
+        } else {
+            DefaultPolicy::Uniform.probabilities(&self.actions)
+        };
+        Self {
+            probabilities,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fallback() {
+        assert_eq!("abstract".parse(), Ok(Fallback::Abstract));
+        assert_eq!("tantos:3".parse(), Ok(Fallback::NeighbouringTantos(3)));
+        assert_eq!(
+            "first-action".parse(),
+            Ok(Fallback::Default(DefaultPolicy::FirstAction))
+        );
+        assert!("tantos:x".parse::<Fallback>().is_err());
+        assert_eq!(
+            DefaultPolicy::Uniform.probabilities(&[Accion::Paso, Accion::Envido(2)]),
+            vec![0.5, 0.5]
+        );
+    }
+
+    #[test]
+    fn normalized() {
+        let lookup = |probabilities| StrategyLookup {
+            actions: vec![Accion::Paso, Accion::Envido(2)],
+            probabilities,
+            fallback: None,
+        };
+        assert_eq!(
+            lookup(vec![0.3, 0.3]).normalized().probabilities,
+            vec![0.5, 0.5]
+        );
+        assert_eq!(
+            lookup(vec![0., 0.]).normalized().probabilities,
+            vec![0.5, 0.5]
+        );
+    }
+}
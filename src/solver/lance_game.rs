@@ -1,17 +1,20 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::Arc};
 
 use arrayvec::{ArrayString, ArrayVec};
 use itertools::Itertools;
+use rand::Rng;
 
 use crate::{
-    Game, NodeType,
+    Game, InfoSetHasher, InfoSetKey, NodeType,
     mus::{
-        Accion, Apuesta, Baraja, CuatroJugadores, DistribucionDobleCartaIter, EstadoLance,
-        FaseEnvites, Juego, Lance, Mano, Pares, Turno,
+        Accion, Apuesta, CuatroJugadores, DistribucionDobleCartaIter, EstadoLance, FaseEnvites,
+        Juego, Lance, Mano, Pares, RepartoMusIter, Turno, Variante,
     },
 };
 
-use super::{AbstractChica, AbstractGrande, AbstractJuego, AbstractPares, AbstractPunto};
+use super::{
+    AbstractChica, AbstractGrande, AbstractJuego, AbstractPares, AbstractPunto, BetAbstraction,
+};
 
 /// Representación de las distintas configuraciones de las manos en un lance de mus.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -306,6 +309,11 @@ pub struct LanceGame {
     abstract_game: bool,
     last_action: Option<Accion>,
     history_str: ArrayVec<ArrayString<4>, 14>,
+    /// Acciones de pareja del lance, para recorrer el árbol de envites.
+    historial_lance: ArrayVec<Accion, 14>,
+    bet_abstraction: Option<Arc<BetAbstraction>>,
+    max_tantos: u8,
+    variante: Variante,
 }
 
 impl LanceGame {
@@ -318,10 +326,33 @@ impl LanceGame {
             info_set_prefix: None,
             last_action: None,
             history_str: ArrayVec::new(),
+            historial_lance: ArrayVec::new(),
             pareja_mano: 0,
+            bet_abstraction: None,
+            max_tantos: FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            variante: Variante::default(),
         }
     }
 
+    /// Envites del lance. Sin abstracción se usa `BetAbstraction::default()`.
+    pub fn with_bet_abstraction(self, bet_abstraction: Arc<BetAbstraction>) -> Self {
+        Self {
+            bet_abstraction: Some(bet_abstraction),
+            ..self
+        }
+    }
+
+    /// Tantos con los que se gana el chico. Por defecto, 40.
+    pub fn with_max_tantos(self, max_tantos: u8) -> Self {
+        Self { max_tantos, ..self }
+    }
+
+    /// Variante de reglas, que determina la baraja con la que se reparte. Por defecto, a ocho
+    /// reyes.
+    pub fn with_variante(self, variante: Variante) -> Self {
+        Self { variante, ..self }
+    }
+
     pub fn new_with_configuration(&mut self, hand_configuration: HandConfiguration) {
         let jugadores = match hand_configuration {
             HandConfiguration::CuatroManos => vec![0, 1, 2, 3],
@@ -336,7 +367,7 @@ impl LanceGame {
             &jugadores,
             [0, 0],
             0,
-            FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            self.max_tantos,
         );
         self.pareja_mano = match estado_lance.turno().unwrap() {
             Turno::Pareja(idx) | Turno::Jugador(idx) => idx as usize,
@@ -356,7 +387,7 @@ impl LanceGame {
             estado_lance: Some(EstadoLance::<CuatroJugadores>::new(
                 &lance,
                 partida_mus.manos(),
-                FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+                partida_mus.max_tantos(),
             )),
             info_set_prefix: LanceGame::info_set_prefix(
                 &lance,
@@ -365,8 +396,14 @@ impl LanceGame {
                 abstract_game,
             ),
             last_action: None,
-            history_str: ArrayVec::new(),
-            pareja_mano: 0,
+            // Como en `new_random`, para que los conjuntos de información coincidan con los del
+            // entrenamiento.
+            history_str: ArrayVec::from_iter([ArrayString::from("M").unwrap()]),
+            historial_lance: ArrayVec::new(),
+            pareja_mano: lance.turno_inicial(partida_mus.manos()),
+            bet_abstraction: None,
+            max_tantos: partida_mus.max_tantos(),
+            variante: Variante::default(),
         })
     }
 
@@ -403,16 +440,15 @@ impl Game for LanceGame {
     type Action = Accion;
     const N_PLAYERS: usize = 4;
 
-    fn new_random(&mut self) {
-        let mut baraja = Baraja::baraja_mus();
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         loop {
+            // Repartir consume la baraja, así que cada intento empieza con una completa.
+            let mut baraja = self.variante.baraja();
+            baraja.barajar(rng);
             let manos = baraja.repartir_manos();
             let turno_inicial = self.lance.turno_inicial(&manos);
-            let intento_partida = EstadoLance::<CuatroJugadores>::new(
-                &self.lance,
-                &manos,
-                FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
-            );
+            let intento_partida =
+                EstadoLance::<CuatroJugadores>::new(&self.lance, &manos, self.max_tantos);
             if intento_partida.turno().is_some() {
                 self.estado_lance = Some(intento_partida);
                 self.info_set_prefix = LanceGame::info_set_prefix(
@@ -434,12 +470,13 @@ impl Game for LanceGame {
         self.pareja_mano = 0;
         self.last_action = None;
         self.history_str.clear();
+        self.historial_lance.clear();
     }
 
     fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
-        DistribucionDobleCartaIter::<4, 8>::new(Baraja::FREC_BARAJA_MUS).flat_map(
+        DistribucionDobleCartaIter::<4>::new(self.variante.frecuencias()).flat_map(
             move |(_mano1, _mano2, prob)| {
-                DistribucionDobleCartaIter::<4, 8>::new(Baraja::FREC_BARAJA_MUS).map(
+                DistribucionDobleCartaIter::<4>::new(self.variante.frecuencias()).map(
                     move |(_mano3, _mano4, prob2)| {
                         // let manos = [
                         //     Mano::new(mano1.to_owned()),
@@ -454,7 +491,12 @@ impl Game for LanceGame {
                         //);
                         //let turno_inicial = self.lance.turno_inicial(&manos);
                         (
-                            Self::new(self.lance, self.tantos, self.abstract_game),
+                            Self {
+                                bet_abstraction: self.bet_abstraction.clone(),
+                                max_tantos: self.max_tantos,
+                                variante: self.variante,
+                                ..Self::new(self.lance, self.tantos, self.abstract_game)
+                            },
                             prob * prob2,
                         )
                         //if intento_partida.turno().is_some() {
@@ -520,15 +562,15 @@ impl Game for LanceGame {
         let ganador = estado_lance.resolver_lance();
         let tantos_ganador = match estado_lance.tantos_apostados() {
             Apuesta::Tantos(t) => t,
-            Apuesta::Ordago => FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            Apuesta::Ordago => self.max_tantos,
         } + estado_lance.tantos_mano()[ganador as usize];
         let mut tantos = self.tantos;
         if self.pareja_mano == 1 {
             tantos.swap(0, 1);
         }
         tantos[ganador as usize] += tantos_ganador;
-        if tantos[ganador as usize] >= FaseEnvites::<CuatroJugadores>::MAX_TANTOS {
-            tantos[ganador as usize] = FaseEnvites::<CuatroJugadores>::MAX_TANTOS;
+        if tantos[ganador as usize] >= self.max_tantos {
+            tantos[ganador as usize] = self.max_tantos;
             tantos[1 - ganador as usize] = 0;
         }
         let payoff = [
@@ -548,35 +590,33 @@ impl Game for LanceGame {
         output
     }
 
+    fn info_set_key(&self, player: usize) -> InfoSetKey {
+        let mut hasher = InfoSetHasher::new();
+        hasher.write_str(&self.info_set_prefix.as_ref().unwrap()[player]);
+        for i in &self.history_str {
+            hasher.write_str(i);
+        }
+        hasher.finish()
+    }
+
+    fn info_set_prefix_len(&self, player: usize) -> usize {
+        self.info_set_prefix.as_ref().unwrap()[player].len()
+    }
+
     fn actions(&self) -> Vec<Accion> {
         let partida = self.estado_lance.as_ref().unwrap();
         let turno = partida.turno().unwrap();
-        let ultimo_envite: Apuesta = partida.ultima_apuesta();
-        let mut acciones = match ultimo_envite {
-            Apuesta::Tantos(0) => vec![
-                Accion::Paso,
-                Accion::Envido(2),
-                Accion::Envido(5),
-                Accion::Envido(10),
-                Accion::Ordago,
-            ],
-            Apuesta::Tantos(2) => vec![
-                Accion::Paso,
-                Accion::Quiero,
-                Accion::Envido(2),
-                Accion::Envido(5),
-                Accion::Envido(10),
-                Accion::Ordago,
-            ],
-            Apuesta::Tantos(4..=5) => vec![
-                Accion::Paso,
-                Accion::Quiero,
-                Accion::Envido(10),
-                Accion::Ordago,
-            ],
-            Apuesta::Ordago => vec![Accion::Paso, Accion::Quiero],
-            _ => vec![Accion::Paso, Accion::Quiero, Accion::Ordago],
-        };
+        // Como en la partida, los envites se limitan a los tantos que le quedan por ganar a la
+        // pareja que va por detrás, aunque el estado del lance admita apuestas por todo el chico.
+        let tantos_restantes =
+            self.max_tantos - self.tantos[0].min(self.tantos[1]).min(self.max_tantos);
+        let mut acciones = BetAbstraction::or_default(self.bet_abstraction.as_deref()).actions(
+            self.lance,
+            partida.ultima_apuesta(),
+            partida.num_envites(),
+            tantos_restantes,
+            &self.historial_lance,
+        );
         if turno == Turno::Pareja(2) || turno == Turno::Pareja(3) {
             acciones.retain(|a| a >= self.last_action.as_ref().unwrap());
         }
@@ -611,7 +651,10 @@ impl Game for LanceGame {
         };
         let action_str = match turno {
             Turno::Pareja(0) | Turno::Pareja(1) => a.to_string() + "*",
-            _ => a.to_string(),
+            _ => {
+                self.historial_lance.push(a);
+                a.to_string()
+            }
         };
         self.history_str
             .push(ArrayString::<4>::from(&action_str).unwrap());
@@ -623,9 +666,275 @@ impl Game for LanceGame {
     }
 }
 
+/// Implementación del trait Game para un lance del mus en el que cada pareja conoce sus dos manos
+/// y actúa una sola vez en los turnos de pareja. Hay dos jugadores, uno por pareja.
+///
+/// Sirve de referencia para [`LanceGame`], en el que cada jugador solo conoce su mano, como
+/// `MusGameTwoHands` para `MusGame`.
+#[derive(Debug, Clone)]
+pub struct LanceGameTwoHands {
+    lance: Lance,
+    tantos: [u8; 2],
+    estado_lance: Option<EstadoLance<CuatroJugadores>>,
+    info_set_prefix: Option<[ArrayString<64>; 2]>,
+    abstract_game: bool,
+    history_str: ArrayVec<ArrayString<4>, 14>,
+    /// Acciones de pareja del lance, para recorrer el árbol de envites.
+    historial_lance: ArrayVec<Accion, 14>,
+    /// Acción del primer jugador de la pareja cuando el lance se juega jugador a jugador, a la
+    /// espera de la de su compañero.
+    accion_pareja: Option<Accion>,
+    bet_abstraction: Option<Arc<BetAbstraction>>,
+    max_tantos: u8,
+    variante: Variante,
+}
+
+impl LanceGameTwoHands {
+    pub fn new(lance: Lance, tantos: [u8; 2], abstract_game: bool) -> Self {
+        Self {
+            lance,
+            tantos,
+            abstract_game,
+            estado_lance: None,
+            info_set_prefix: None,
+            history_str: ArrayVec::new(),
+            historial_lance: ArrayVec::new(),
+            accion_pareja: None,
+            bet_abstraction: None,
+            max_tantos: FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            variante: Variante::default(),
+        }
+    }
+
+    /// Envites del lance. Sin abstracción se usa `BetAbstraction::default()`.
+    pub fn with_bet_abstraction(self, bet_abstraction: Arc<BetAbstraction>) -> Self {
+        Self {
+            bet_abstraction: Some(bet_abstraction),
+            ..self
+        }
+    }
+
+    /// Tantos con los que se gana el chico. Por defecto, 40.
+    pub fn with_max_tantos(self, max_tantos: u8) -> Self {
+        Self { max_tantos, ..self }
+    }
+
+    /// Variante de reglas, que determina la baraja con la que se reparte. Por defecto, a ocho
+    /// reyes.
+    pub fn with_variante(self, variante: Variante) -> Self {
+        Self { variante, ..self }
+    }
+
+    /// Reparte las manos `manos`. Si con ellas no se juega el lance, la partida queda terminada.
+    pub fn with_hands(self, manos: [Mano; 4]) -> Self {
+        let mut new_game = self.clone();
+        let manos = manos.map(|mano| self.variante.normalizar(&mano));
+        let estado_lance =
+            EstadoLance::<CuatroJugadores>::new(&self.lance, &manos, self.max_tantos);
+        new_game.set_hands(&manos, estado_lance);
+        new_game
+    }
+
+    fn set_hands(&mut self, manos: &[Mano; 4], estado_lance: EstadoLance<CuatroJugadores>) {
+        let manos_normalizadas = ManosNormalizadas::normalizar_mano(manos, &self.lance);
+        self.info_set_prefix = Some(core::array::from_fn(|pareja| {
+            ArrayString::<64>::from(&InfoSet::str(
+                &manos_normalizadas.hand_configuration(),
+                &self.tantos,
+                &manos[pareja],
+                Some(&manos[pareja + 2]),
+                &[],
+                self.abstract_game.then_some(self.lance),
+            ))
+            .unwrap()
+        }));
+        self.estado_lance = Some(estado_lance);
+        self.history_str.clear();
+        self.history_str.push(ArrayString::from("M").unwrap());
+        self.historial_lance.clear();
+        self.accion_pareja = None;
+    }
+
+    /// Acción de un solo jugador, como las que se registran en las partidas de cuatro jugadores.
+    /// En los turnos de pareja la acción de la pareja es la mayor de las de sus dos jugadores, y
+    /// hasta que actúa el segundo queda pendiente en [`LanceGameTwoHands::accion_pareja`].
+    pub fn act_jugador(&mut self, a: Accion) {
+        let estado_lance = self
+            .estado_lance
+            .as_mut()
+            .expect("La partida debe estar repartida.");
+        let turno = estado_lance.turno().unwrap();
+        let _ = estado_lance.actuar(a);
+        if let Turno::Pareja(0) | Turno::Pareja(1) = turno {
+            self.accion_pareja = Some(a);
+            return;
+        }
+        let accion = self.accion_pareja.take().map_or(a, |accion| accion.max(a));
+        self.historial_lance.push(accion);
+        self.history_str
+            .push(ArrayString::<4>::from(&accion.to_string()).unwrap());
+    }
+
+    /// Acción que ha elegido el primer jugador de la pareja a la que le toca, si se está jugando
+    /// jugador a jugador y falta la de su compañero.
+    pub fn accion_pareja(&self) -> Option<Accion> {
+        self.accion_pareja
+    }
+}
+
+impl Game for LanceGameTwoHands {
+    type Action = Accion;
+    const N_PLAYERS: usize = 2;
+
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        loop {
+            // Repartir consume la baraja, así que cada intento empieza con una completa.
+            let mut baraja = self.variante.baraja();
+            baraja.barajar(rng);
+            let manos = baraja.repartir_manos();
+            let estado_lance =
+                EstadoLance::<CuatroJugadores>::new(&self.lance, &manos, self.max_tantos);
+            if estado_lance.turno().is_some() {
+                self.set_hands(&manos, estado_lance);
+                break;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.estado_lance = None;
+        self.info_set_prefix = None;
+        self.history_str.clear();
+        self.historial_lance.clear();
+        self.accion_pareja = None;
+    }
+
+    fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
+        // Como en `new_random`, solo se reparten manos con las que se juega el lance, así que la
+        // probabilidad de cada reparto se divide por la de que se juegue.
+        let repartos = move || {
+            RepartoMusIter::con_variante(self.variante).filter_map(
+                move |(mano1, mano2, mano3, mano4, probability, _)| {
+                    let manos = [
+                        Mano::new(mano1),
+                        Mano::new(mano2),
+                        Mano::new(mano3),
+                        Mano::new(mano4),
+                    ];
+                    let estado_lance =
+                        EstadoLance::<CuatroJugadores>::new(&self.lance, &manos, self.max_tantos);
+                    estado_lance
+                        .turno()
+                        .is_some()
+                        .then_some((manos, estado_lance, probability))
+                },
+            )
+        };
+        let total: f64 = repartos().map(|(_, _, probability)| probability).sum();
+        repartos().map(move |(manos, estado_lance, probability)| {
+            let mut game = self.clone();
+            game.set_hands(&manos, estado_lance);
+            (game, probability / total)
+        })
+    }
+
+    fn utility(&mut self, player: usize) -> f64 {
+        let estado_lance = self.estado_lance.as_mut().unwrap();
+        let ganador = estado_lance.resolver_lance() as usize;
+        let tantos_ganador = match estado_lance.tantos_apostados() {
+            Apuesta::Tantos(t) => t,
+            Apuesta::Ordago => self.max_tantos,
+        } + estado_lance.tantos_mano()[ganador];
+        let mut tantos = self.tantos;
+        tantos[ganador] += tantos_ganador;
+        if tantos[ganador] >= self.max_tantos {
+            tantos[ganador] = self.max_tantos;
+            tantos[1 - ganador] = 0;
+        }
+        let payoff = [
+            tantos[0] as i8 - tantos[1] as i8,
+            tantos[1] as i8 - tantos[0] as i8,
+        ];
+        payoff[player] as f64
+    }
+
+    fn info_set_str(&self, player: usize) -> String {
+        let info_set_prefix = &self.info_set_prefix.as_ref().unwrap()[player];
+        let mut output = String::with_capacity(info_set_prefix.len() + self.history_str.len() + 1);
+        output.push_str(info_set_prefix);
+        for i in &self.history_str {
+            output.push_str(i);
+        }
+        output
+    }
+
+    fn info_set_key(&self, player: usize) -> InfoSetKey {
+        let mut hasher = InfoSetHasher::new();
+        hasher.write_str(&self.info_set_prefix.as_ref().unwrap()[player]);
+        for i in &self.history_str {
+            hasher.write_str(i);
+        }
+        hasher.finish()
+    }
+
+    fn info_set_prefix_len(&self, player: usize) -> usize {
+        self.info_set_prefix.as_ref().unwrap()[player].len()
+    }
+
+    fn actions(&self) -> Vec<Accion> {
+        let partida = self.estado_lance.as_ref().unwrap();
+        let tantos_restantes =
+            self.max_tantos - self.tantos[0].min(self.tantos[1]).min(self.max_tantos);
+        let mut acciones = BetAbstraction::or_default(self.bet_abstraction.as_deref()).actions(
+            self.lance,
+            partida.ultima_apuesta(),
+            partida.num_envites(),
+            tantos_restantes,
+            &self.historial_lance,
+        );
+        if let Some(accion_pareja) = self.accion_pareja {
+            acciones.retain(|a| *a >= accion_pareja);
+        }
+        acciones
+    }
+
+    fn current_player(&self) -> NodeType {
+        match &self.estado_lance {
+            None => NodeType::Chance,
+            Some(estado_lance) => match estado_lance.turno() {
+                None => NodeType::Terminal,
+                Some(Turno::Jugador(player_id)) | Some(Turno::Pareja(player_id)) => {
+                    NodeType::Player(player_id as usize % 2)
+                }
+            },
+        }
+    }
+
+    fn act(&mut self, a: Accion) {
+        let turno = self
+            .estado_lance
+            .as_ref()
+            .expect("At least one EstadoLance must be available.")
+            .turno()
+            .unwrap();
+        // Los dos jugadores de la pareja hacen la acción de la pareja.
+        if let Turno::Pareja(0) | Turno::Pareja(1) = turno {
+            self.act_jugador(a);
+        }
+        self.act_jugador(a);
+    }
+
+    fn history_str(&self) -> String {
+        self.history_str.join("")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Cfr, CfrMethod, SampledGame};
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+    use std::sync::atomic::AtomicBool;
 
     #[test]
     fn test_normalizar() {
@@ -723,4 +1032,208 @@ mod tests {
         let manos_normalizadas = ManosNormalizadas::normalizar_mano(&manos, &Lance::Juego);
         assert_eq!(manos_normalizadas.pareja_mano(), 1);
     }
+
+    /// Lance de grande con unos pocos repartos posibles. Es lo bastante pequeño para recorrerlo
+    /// entero con CFR y calcular su explotabilidad exacta.
+    #[derive(Debug, Clone)]
+    struct LanceReducido {
+        repartos: Vec<[Mano; 4]>,
+        lance_game: Option<LanceGame>,
+    }
+
+    impl LanceReducido {
+        fn new(repartos: &[[&str; 4]]) -> Self {
+            Self {
+                repartos: repartos
+                    .iter()
+                    .map(|manos| manos.map(|m| Mano::try_from(m).unwrap()))
+                    .collect(),
+                lance_game: None,
+            }
+        }
+    }
+
+    impl Game for LanceReducido {
+        type Action = Accion;
+        const N_PLAYERS: usize = 4;
+
+        fn utility(&mut self, player: usize) -> f64 {
+            self.lance_game.as_mut().unwrap().utility(player)
+        }
+
+        fn info_set_str(&self, player: usize) -> String {
+            self.lance_game.as_ref().unwrap().info_set_str(player)
+        }
+
+        fn history_str(&self) -> String {
+            self.lance_game
+                .as_ref()
+                .map_or_else(String::new, |g| g.history_str())
+        }
+
+        fn actions(&self) -> Vec<Accion> {
+            self.lance_game.as_ref().unwrap().actions()
+        }
+
+        fn current_player(&self) -> NodeType {
+            self.lance_game
+                .as_ref()
+                .map_or(NodeType::Chance, |g| g.current_player())
+        }
+
+        fn act(&mut self, a: Accion) {
+            self.lance_game.as_mut().unwrap().act(a);
+        }
+
+        fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+            let manos = self.repartos.choose(rng).unwrap();
+            let partida =
+                FaseEnvites::new_partida_lance(Lance::Grande, manos.clone(), [0, 0]).unwrap();
+            self.lance_game = LanceGame::from_partida_mus(&partida, false);
+        }
+
+        fn reset(&mut self) {
+            self.lance_game = None;
+        }
+
+        fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
+            let prob = 1. / self.repartos.len() as f64;
+            self.repartos.iter().map(move |manos| {
+                let partida =
+                    FaseEnvites::new_partida_lance(Lance::Grande, manos.clone(), [0, 0]).unwrap();
+                let game = Self {
+                    repartos: self.repartos.clone(),
+                    lance_game: LanceGame::from_partida_mus(&partida, false),
+                };
+                (game, prob)
+            })
+        }
+    }
+
+    #[test]
+    fn cfr_plus_lance() {
+        let mut game = LanceReducido::new(&[
+            ["RR11", "RC51", "R751", "C741"],
+            ["RC51", "RR11", "C741", "R751"],
+            ["R751", "C741", "RR11", "RC51"],
+        ]);
+        // Con las mismas iteraciones, CFR+ queda más cerca del equilibrio que CFR.
+        let exploitability: Vec<f64> = [CfrMethod::Cfr, CfrMethod::CfrPlus]
+            .into_iter()
+            .map(|cfr_method| {
+                let mut cfr = Cfr::new();
+                cfr.train(&mut game, cfr_method, 40, |_, _| {});
+                game.reset();
+                cfr.exploitability(&mut game)
+            })
+            .collect();
+        assert!(
+            exploitability[1] < exploitability[0],
+            "Exploitability CFR, CFR+: {exploitability:?}"
+        );
+    }
+
+    #[test]
+    fn parallel_chance_sampling_lance() {
+        let mut game = LanceReducido::new(&[
+            ["RR11", "RC51", "R751", "C741"],
+            ["RC51", "RR11", "C741", "R751"],
+            ["R751", "C741", "RR11", "RC51"],
+        ]);
+        let exploitability: Vec<f64> = [5, 60]
+            .into_iter()
+            .map(|n| {
+                let mut cfr = Cfr::new();
+                let mut iterations = vec![];
+                let cancel = AtomicBool::new(false);
+                let end = cfr.train_parallel(
+                    &mut game,
+                    CfrMethod::ChanceSampling,
+                    0..n,
+                    4,
+                    &cancel,
+                    |i, _| iterations.push(*i),
+                );
+                assert_eq!(end, n);
+                iterations.sort();
+                assert_eq!(iterations, (0..n).collect::<Vec<usize>>());
+                game.reset();
+                cfr.exploitability(&mut game)
+            })
+            .collect();
+        assert!(
+            exploitability[1] < exploitability[0] / 2.,
+            "Exploitability: {exploitability:?}"
+        );
+    }
+
+    #[test]
+    fn sampled_exploitability() {
+        // La pareja mano tiene las mismas manos en los dos repartos, pero en uno gana el lance y
+        // en el otro lo pierde.
+        let mut game = LanceReducido::new(&[
+            ["RC51", "RR11", "C741", "R751"],
+            ["RC51", "R751", "C741", "C761"],
+        ]);
+        let mut cfr = Cfr::new();
+        cfr.train(&mut game, CfrMethod::CfrPlus, 4, |_, _| {});
+        game.reset();
+        let exploitability = cfr.exploitability(&mut game);
+        let mut rng = StdRng::seed_from_u64(3);
+        let sampled_game = SampledGame::new(&game, 5, &mut rng);
+        assert_eq!(sampled_game.num_samples(), 5);
+
+        // Con una sola muestra la mejor respuesta conoce el reparto y gana más de lo que ganaría
+        // en la partida real. Con más muestras el sesgo se reduce.
+        let one_deal = cfr.estimate_exploitability(&game, 1, 20, &mut rng);
+        let many_deals = cfr.estimate_exploitability(&game, 50, 2, &mut rng);
+        assert!(
+            one_deal.mean > exploitability + one_deal.sampling_margin.unwrap(),
+            "Exploitability: {exploitability}, sampled: {one_deal:?}"
+        );
+        assert!(
+            (many_deals.mean - exploitability).abs() < (one_deal.mean - exploitability) / 4.,
+            "Exploitability: {exploitability}, sampled: {many_deals:?}"
+        );
+    }
+
+    #[test]
+    fn lance_two_hands_infoset() {
+        let manos = ["RR11", "RC51", "R751", "C741"].map(|m| Mano::try_from(m).unwrap());
+        let mut game =
+            LanceGameTwoHands::new(Lance::Grande, [5, 3], false).with_hands(manos.clone());
+        // Cada pareja ve sus dos manos y actúa una sola vez.
+        assert_eq!(game.current_player(), NodeType::Player(0));
+        assert_eq!(game.info_set_str(0), "5:3,2-2,RR11,R751,M");
+        assert_eq!(game.info_set_str(1), "5:3,2-2,RC51,C741,M");
+        game.act(Accion::Envido(2));
+        assert_eq!(game.current_player(), NodeType::Player(1));
+        assert_eq!(game.info_set_str(1), "5:3,2-2,RC51,C741,Me2");
+        game.act(Accion::Paso);
+        assert_eq!(game.current_player(), NodeType::Terminal);
+        assert_eq!(game.utility(0), 3.);
+
+        // Jugador a jugador, la acción de la pareja es la mayor de las de sus dos jugadores.
+        let mut por_jugadores =
+            LanceGameTwoHands::new(Lance::Grande, [5, 3], false).with_hands(manos.clone());
+        por_jugadores.act_jugador(Accion::Paso);
+        assert_eq!(por_jugadores.accion_pareja(), Some(Accion::Paso));
+        assert_eq!(por_jugadores.current_player(), NodeType::Player(0));
+        por_jugadores.act_jugador(Accion::Envido(2));
+        assert_eq!(por_jugadores.accion_pareja(), None);
+        assert_eq!(por_jugadores.info_set_str(1), "5:3,2-2,RC51,C741,Me2");
+    }
+
+    #[test]
+    fn lance_two_hands_training() {
+        let mut game = LanceGameTwoHands::new(Lance::Pares, [0, 0], true);
+        let mut cfr = Cfr::new();
+        cfr.train(&mut game, CfrMethod::ExternalSampling, 200, |_, _| {});
+        assert!(cfr.num_nodes() > 0);
+        // Las claves incluyen las dos manos de la pareja, aunque una no tenga pares.
+        assert!(
+            cfr.nodes()
+                .all(|(info_set, _)| info_set.split(',').count() == 5)
+        );
+    }
 }
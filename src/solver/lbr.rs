@@ -0,0 +1,454 @@
+use std::fmt;
+
+use rand::{Rng, distributions::Distribution, distributions::WeightedIndex};
+
+use crate::{Game, NodeType, mus::Accion};
+
+use super::{
+    DefaultPolicy, Fallback, GameType, MusGame, MusGameTwoPlayers, SolverError, Strategy,
+    StrategyLookup,
+};
+
+/// Partidas de mus sobre las que se puede evaluar una estrategia con [`Lbr`]. El agente necesita
+/// generar repartos compatibles con lo que ve un jugador y mantenerlos al día con los descartes
+/// de la partida real.
+pub trait LbrGame: Game<Action = Accion> + Clone {
+    /// Nueva partida en la que `player` tiene la misma mano que en esta y el resto de manos se
+    /// reparten al azar con las cartas que no ve. Debe llamarse con la partida recién repartida.
+    fn redeal<R: Rng + ?Sized>(&self, player: usize, rng: &mut R) -> Self;
+
+    /// Resuelve el descarte pendiente de esta partida, un mundo posible de `player`, de forma
+    /// coherente con el descarte que ha llevado la partida real de `real` a `real_next`. Si
+    /// descarta `player` recibe las mismas cartas que en la partida real; el resto de jugadores
+    /// las roban de la baraja del mundo. Devuelve false si las cartas de `player` no pueden
+    /// salir de la baraja del mundo, que entonces deja de ser posible.
+    fn follow_discard<R: Rng + ?Sized>(
+        &mut self,
+        real: &Self,
+        real_next: &Self,
+        player: usize,
+        rng: &mut R,
+    ) -> bool;
+
+    /// Indica si `player` no ve la acción que va a tomar el jugador al que le toca, como una seña
+    /// entre rivales. Los mundos no siguen esas acciones: las toman de la estrategia.
+    fn hidden_action(&self, _player: usize) -> bool {
+        false
+    }
+}
+
+/// Cota inferior de la explotabilidad calculada por [`Lbr::evaluate`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct LbrEstimate {
+    /// Suma de las utilidades medias del agente en cada posición.
+    pub lower_bound: f64,
+    /// Semiamplitud del intervalo de confianza del 95% de la cota. Solo se conoce con más de un
+    /// reparto.
+    pub margin: Option<f64>,
+    /// Utilidad media del agente en cada posición.
+    pub values: Vec<f64>,
+    /// Repartos jugados en cada posición.
+    pub deals: usize,
+}
+
+impl fmt::Display for LbrEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.margin {
+            Some(margin) => write!(f, "{:.6} ± {:.6}", self.lower_bound, margin),
+            None => write!(f, "{:.6}", self.lower_bound),
+        }
+    }
+}
+
+/// Mejor respuesta local (LBR) contra una estrategia. Sirve para acotar la explotabilidad de
+/// estrategias de partidas completas, en las que no es posible calcular la mejor respuesta
+/// exacta ni sobre una muestra de repartos.
+///
+/// El agente ocupa una posición y el resto de jugadores, incluido su compañero, juegan la
+/// estrategia. En cada decisión el agente estima el rango de los rivales con un conjunto de
+/// mundos: repartos compatibles con su mano, con los descartes que ha visto y con las acciones
+/// de los demás, ponderados por la probabilidad de esas acciones según la estrategia. El valor
+/// de cada acción se estima con una partida simulada por mundo en la que, tras la acción, todos
+/// juegan la estrategia, y el agente elige la acción de mayor valor.
+///
+/// El agente no es una mejor respuesta exacta, así que su utilidad es una cota inferior de la
+/// explotabilidad tal como la define `Cfr::exploitability`.
+pub struct Lbr<'a> {
+    strategy: &'a Strategy,
+    worlds: usize,
+    fallbacks: Vec<Fallback>,
+}
+
+impl<'a> Lbr<'a> {
+    pub const DEFAULT_WORLDS: usize = 32;
+    /// Repartos que se prueban por cada mundo que falta antes de decidir con menos mundos.
+    const ATTEMPTS_PER_WORLD: usize = 8;
+
+    pub fn new(strategy: &'a Strategy) -> Self {
+        Self {
+            strategy,
+            worlds: Self::DEFAULT_WORLDS,
+            fallbacks: vec![Fallback::Default(DefaultPolicy::Uniform)],
+        }
+    }
+
+    /// Número de mundos con los que el agente estima el rango de los rivales.
+    pub fn with_worlds(self, worlds: usize) -> Self {
+        Self { worlds, ..self }
+    }
+
+    /// Alternativas para los conjuntos de información que no están en la estrategia, como en
+    /// [`Strategy::game_actions_with_fallback`]. Por defecto, la estrategia uniforme. Deben
+    /// terminar en una [`Fallback::Default`] para que siempre haya respuesta.
+    pub fn with_fallbacks(self, fallbacks: Vec<Fallback>) -> Self {
+        assert!(
+            matches!(fallbacks.last(), Some(Fallback::Default(_))),
+            "Las alternativas de LBR deben terminar en una política fija."
+        );
+        Self { fallbacks, ..self }
+    }
+
+    /// Juega `deals` repartos con el agente en cada posición de `game`. La cota es la suma de las
+    /// utilidades medias de cada posición.
+    pub fn evaluate<G, R>(&self, game: &G, deals: usize, rng: &mut R) -> LbrEstimate
+    where
+        G: LbrGame,
+        R: Rng + ?Sized,
+    {
+        let n = deals as f64;
+        let mut values = Vec::with_capacity(G::N_PLAYERS);
+        let mut variance = 0.;
+        for player in 0..G::N_PLAYERS {
+            let utilities: Vec<f64> = (0..deals).map(|_| self.play(game, player, rng)).collect();
+            let mean = utilities.iter().sum::<f64>() / n;
+            if deals > 1 {
+                variance += utilities.iter().map(|u| (u - mean).powi(2)).sum::<f64>() / (n - 1.);
+            }
+            values.push(mean);
+        }
+        LbrEstimate {
+            lower_bound: values.iter().sum(),
+            margin: (deals > 1).then(|| 1.96 * (variance / n).sqrt()),
+            values,
+            deals,
+        }
+    }
+
+    /// Evalúa la estrategia en su tipo de partida con el marcador `tantos`. Solo las partidas
+    /// completas con un jugador en cada posición admiten LBR.
+    pub fn evaluate_strategy<R: Rng + ?Sized>(
+        &self,
+        tantos: [u8; 2],
+        deals: usize,
+        rng: &mut R,
+    ) -> Result<LbrEstimate, SolverError> {
+        let game_config = &self.strategy.strategy_config.game_config;
+        let bet_abstraction = self.strategy.bet_abstraction();
+        let discard_abstraction = self.strategy.discard_abstraction();
+        match game_config.game_type {
+            GameType::MusGame => {
+                let mut game = MusGame::new(
+                    tantos,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
+                )
+                .with_bet_abstraction(bet_abstraction)
+                .with_discard_abstraction(discard_abstraction)
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante);
+                if let Some(signals) = &game_config.signals {
+                    game = game.with_signals(std::sync::Arc::new(signals.clone()));
+                }
+                Ok(self.evaluate(&game, deals, rng))
+            }
+            GameType::MusGameTwoPlayers => {
+                let game = MusGameTwoPlayers::new(
+                    tantos,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
+                )
+                .with_bet_abstraction(bet_abstraction)
+                .with_discard_abstraction(discard_abstraction)
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante);
+                Ok(self.evaluate(&game, deals, rng))
+            }
+            game_type => Err(SolverError::UnsupportedGameType(game_type)),
+        }
+    }
+
+    /// Juega un reparto de `game` con el agente en la posición `player` y devuelve su utilidad.
+    pub fn play<G, R>(&self, game: &G, player: usize, rng: &mut R) -> f64
+    where
+        G: LbrGame,
+        R: Rng + ?Sized,
+    {
+        let mut game = game.clone();
+        game.reset();
+        game.new_random(rng);
+        // Estados por los que pasa la partida real y la posición de la acción que lleva de cada
+        // uno al siguiente, o `None` si es un descarte.
+        let mut states = vec![game.clone()];
+        let mut steps: Vec<Option<usize>> = Vec::new();
+        let mut worlds: Vec<(G, f64)> = Vec::new();
+        loop {
+            let step = match game.current_player() {
+                NodeType::Terminal => return game.utility(player),
+                NodeType::Chance => {
+                    game.new_random(rng);
+                    None
+                }
+                NodeType::Player(current_player) => {
+                    let action = if current_player == player {
+                        self.fill_worlds(&states, &steps, &mut worlds, player, rng);
+                        self.best_action(&game, &worlds, player, rng)
+                    } else {
+                        sample(&self.policy(&game), rng)
+                    };
+                    let real_action = game.actions()[action];
+                    game.act(real_action);
+                    Some(action)
+                }
+            };
+            states.push(game.clone());
+            steps.push(step);
+            let real = &states[states.len() - 2..];
+            worlds
+                .retain_mut(|(world, weight)| self.follow(world, weight, real, step, player, rng));
+        }
+    }
+
+    /// Completa hasta `self.worlds` mundos compatibles con la partida real. Cada candidato es un
+    /// nuevo reparto que repite la partida desde el principio, y se rechaza si en algún momento
+    /// deja de ser compatible. Los mundos que ya había se conservan con su peso.
+    fn fill_worlds<G, R>(
+        &self,
+        states: &[G],
+        steps: &[Option<usize>],
+        worlds: &mut Vec<(G, f64)>,
+        player: usize,
+        rng: &mut R,
+    ) where
+        G: LbrGame,
+        R: Rng + ?Sized,
+    {
+        let mut attempts = 0;
+        while worlds.len() < self.worlds && attempts < self.worlds * Self::ATTEMPTS_PER_WORLD {
+            attempts += 1;
+            let mut world = states[0].redeal(player, rng);
+            let mut weight = 1.;
+            let compatible = steps
+                .iter()
+                .zip(states.windows(2))
+                .all(|(step, real)| self.follow(&mut world, &mut weight, real, *step, player, rng));
+            if compatible {
+                worlds.push((world, weight));
+            }
+        }
+    }
+
+    /// Avanza un mundo de `player` con el paso que ha llevado la partida real del primer estado de
+    /// `real` al segundo. Las acciones de los demás jugadores multiplican el peso del mundo por su
+    /// probabilidad según la estrategia. Las acciones se identifican por su posición: en un
+    /// mundo no siempre son las mismas, como los descartes de las manos ocultas.
+    ///
+    /// Devuelve false si el mundo deja de ser compatible con lo que `player` ve de la partida
+    /// real: su conjunto de información incluye las acciones públicas, como el número de cartas
+    /// descartadas o quién lleva pares o juego, que dependen de las manos ocultas.
+    fn follow<G, R>(
+        &self,
+        world: &mut G,
+        weight: &mut f64,
+        real: &[G],
+        step: Option<usize>,
+        player: usize,
+        rng: &mut R,
+    ) -> bool
+    where
+        G: LbrGame,
+        R: Rng + ?Sized,
+    {
+        match step {
+            None => {
+                if !world.follow_discard(&real[0], &real[1], player, rng) {
+                    return false;
+                }
+            }
+            Some(_) if world.hidden_action(player) => {
+                if !matches!(world.current_player(), NodeType::Player(_)) {
+                    return false;
+                }
+                let action = sample(&self.policy(world), rng);
+                let world_action = world.actions()[action];
+                world.act(world_action);
+            }
+            Some(action) => {
+                // Las acciones legales pueden depender de las manos ocultas, como las señas, así
+                // que el mundo sigue la acción real y no su posición.
+                let real_action = real[0].actions()[action];
+                let Some(action) = world.actions().iter().position(|a| *a == real_action) else {
+                    return false;
+                };
+                if let NodeType::Player(current_player) = world.current_player()
+                    && current_player != player
+                {
+                    *weight *= self.policy(world)[action];
+                }
+                world.act(real_action);
+            }
+        }
+        *weight > 0.
+            && world.current_player() == real[1].current_player()
+            && world.info_set_key(player) == real[1].info_set_key(player)
+    }
+
+    /// Acción de mayor valor estimado sobre los mundos. Si ningún mundo es compatible con lo
+    /// ocurrido en la partida, el agente juega la estrategia.
+    fn best_action<G, R>(&self, game: &G, worlds: &[(G, f64)], player: usize, rng: &mut R) -> usize
+    where
+        G: LbrGame,
+        R: Rng + ?Sized,
+    {
+        if worlds.is_empty() {
+            return sample(&self.policy(game), rng);
+        }
+        (0..game.actions().len())
+            .map(|action| {
+                let value: f64 = worlds
+                    .iter()
+                    .map(|(world, weight)| {
+                        let mut world = world.clone();
+                        let world_action = world.actions()[action];
+                        world.act(world_action);
+                        weight * self.rollout(world, player, rng)
+                    })
+                    .sum();
+                (action, value)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(action, _)| action)
+            .unwrap()
+    }
+
+    /// Utilidad de `player` al terminar `game` con todos los jugadores siguiendo la estrategia.
+    fn rollout<G, R>(&self, mut game: G, player: usize, rng: &mut R) -> f64
+    where
+        G: LbrGame,
+        R: Rng + ?Sized,
+    {
+        loop {
+            match game.current_player() {
+                NodeType::Terminal => return game.utility(player),
+                NodeType::Chance => game.new_random(rng),
+                NodeType::Player(_) => {
+                    let actions = game.actions();
+                    let action = sample(&self.policy(&game), rng);
+                    game.act(actions[action]);
+                }
+            }
+        }
+    }
+
+    /// Estrategia del jugador al que le toca en el estado actual, con las alternativas de
+    /// `self.fallbacks` para los conjuntos de información que no están en la estrategia.
+    fn policy<G: Game<Action = Accion>>(&self, game: &G) -> Vec<f64> {
+        self.strategy
+            .game_actions_with_fallback(game, &self.fallbacks)
+            .map(StrategyLookup::normalized)
+            .expect("Las alternativas terminan en una política fija.")
+            .probabilities
+    }
+}
+
+fn sample<R: Rng + ?Sized>(probs: &[f64], rng: &mut R) -> usize {
+    WeightedIndex::new(probs)
+        .expect("La estrategia debe tener alguna acción con probabilidad positiva.")
+        .sample(rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        CfrMethod,
+        mus::{Lance, Sena},
+        solver::{
+            GameConfig, GameType, MusGame, MusGameTwoPlayers, Signals, StrategyConfig,
+            TrainerConfig,
+        },
+    };
+
+    /// Estrategia sin nodos, que juega todas las acciones con la misma probabilidad.
+    fn uniform_strategy(game_type: GameType, signals: Option<Signals>) -> Strategy {
+        Strategy {
+            strategy_config: StrategyConfig {
+                trainer_config: TrainerConfig {
+                    method: CfrMethod::ExternalSampling,
+                    ..Default::default()
+                },
+                game_config: GameConfig {
+                    signals,
+                    ..GameConfig::new(game_type)
+                },
+                stop_reasons: Vec::new(),
+            },
+            nodes: BTreeMap::new(),
+            exploitability: None,
+            utility_table: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn lbr_exploits_uniform_strategy() {
+        // Sin nodos la estrategia es uniforme: los rivales aceptan y rechazan órdagos al azar.
+        let strategy = uniform_strategy(GameType::MusGameTwoPlayers, None);
+        let game = MusGameTwoPlayers::new([35, 35], false, 1);
+        let estimate =
+            Lbr::new(&strategy)
+                .with_worlds(8)
+                .evaluate(&game, 300, &mut StdRng::seed_from_u64(1));
+        assert_eq!(estimate.values.len(), 2);
+        assert_eq!(estimate.deals, 300);
+        let margin = estimate.margin.unwrap();
+        assert!(estimate.lower_bound - margin > 0., "{estimate}");
+    }
+
+    #[test]
+    fn evaluate_strategy() {
+        let strategy = uniform_strategy(GameType::MusGameTwoPlayers, None);
+        let estimate = Lbr::new(&strategy)
+            .with_worlds(4)
+            .evaluate_strategy([35, 35], 10, &mut StdRng::seed_from_u64(3))
+            .unwrap();
+        assert_eq!(estimate.values.len(), 2);
+        assert_eq!(estimate.deals, 10);
+
+        let strategy = uniform_strategy(GameType::LanceGame(Lance::Grande), None);
+        assert!(matches!(
+            Lbr::new(&strategy).evaluate_strategy([0, 0], 10, &mut StdRng::seed_from_u64(3)),
+            Err(SolverError::UnsupportedGameType(_))
+        ));
+    }
+
+    #[test]
+    fn lbr_follows_senas() {
+        // Las señas legales dependen de la mano: los mundos siguen la seña real del compañero y
+        // sacan de la estrategia las de los rivales que el agente no ve.
+        let signals = Signals {
+            vocabulary: Sena::SENAS.to_vec(),
+            opponent_probability: 0.5,
+        };
+        let strategy = uniform_strategy(GameType::MusGame, Some(signals.clone()));
+        let game = MusGame::new([35, 35], false, 0).with_signals(Arc::new(signals));
+        let estimate =
+            Lbr::new(&strategy)
+                .with_worlds(4)
+                .evaluate(&game, 20, &mut StdRng::seed_from_u64(2));
+        assert_eq!(estimate.values.len(), 4);
+        assert!(estimate.lower_bound.is_finite());
+    }
+}
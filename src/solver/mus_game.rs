@@ -1,16 +1,19 @@
-use std::{fmt::Write, rc::Rc};
+use std::{fmt::Write, iter, sync::Arc};
 
-use arrayvec::ArrayString;
+use arrayvec::{ArrayString, ArrayVec};
 use itertools::{Either, Itertools};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
 use crate::{
-    Game, NodeType,
+    Game, InfoSetHasher, InfoSetKey, NodeType,
     mus::{
-        Accion, Apuesta, Baraja, Carta, CuatroJugadores, DosJugadores, FasePartida, Lance, Mano,
-        ModalidadMus, PartidaMus, RepartoDescarteMusIter, RepartoMusDosJugadoresIter,
-        RepartoMusIter, Turno,
+        Accion, Baraja, Carta, CuatroJugadores, DosJugadores, FaseEnvites, FasePartida,
+        FrecuenciasBaraja, Lance, Mano, ModalidadMus, PartidaMus, RepartoDescarteMusIter,
+        RepartoMusDosJugadoresIter, RepartoMusIter, Sena, Turno, Variante,
+    },
+    solver::{
+        BetAbstraction, DiscardAbstraction, LbrGame, ManosNormalizadas, Signals, UtilityTable,
     },
-    solver::ManosNormalizadas,
 };
 
 /// Número máximo de rondas de mus admitido por [`MusGame`], [`MusGameTwoHands`] y
@@ -34,7 +37,22 @@ pub struct MusGame {
     mus_rounds: u8,
     max_mus_rounds: u8,
     abstract_game: bool,
-    utility_table: Option<Rc<[[f64; 40]; 40]>>,
+    max_tantos: u8,
+    variante: Variante,
+    utility_table: Option<Arc<UtilityTable>>,
+    bet_abstraction: Option<Arc<BetAbstraction>>,
+    discard_abstraction: Option<Arc<DiscardAbstraction>>,
+    // Acciones de pareja del lance en curso. Solo se llevan si los envites siguen un árbol.
+    historial_lance: Vec<Accion>,
+    signals: Option<Arc<Signals>>,
+    // Jugador al que le toca hacer seña mientras dura la fase de señas.
+    turno_sena: Option<u8>,
+    // Seña, o None si no se ha hecho, a la espera de saber si la ven los rivales.
+    sena_pendiente: Option<(u8, Option<Sena>)>,
+    // Señas que ha visto cada jugador, una por jugador, con 'p' si no ha visto ninguna.
+    senas_str: [ArrayString<5>; 4],
+    // Todas las señas y si las han visto los rivales. Solo forma parte del historial completo.
+    historial_senas: ArrayString<16>,
 }
 
 impl MusGame {
@@ -60,21 +78,36 @@ impl MusGame {
             max_mus_rounds,
             abstract_game,
             utility_table: None,
+            bet_abstraction: None,
+            discard_abstraction: None,
+            historial_lance: Vec::new(),
+            signals: None,
+            turno_sena: None,
+            sena_pendiente: None,
+            senas_str: [ArrayString::new(); 4],
+            historial_senas: ArrayString::new(),
+            max_tantos: FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            variante: Variante::default(),
         }
     }
 
     pub fn with_hands(self, manos: [Mano; 4]) -> Self {
         let mut new_game = self.clone();
-        new_game.set_hands(manos);
+        new_game.set_hands(manos.map(|mano| self.variante.normalizar(&mano)));
         new_game
     }
 
     fn set_hands(&mut self, manos: [Mano; 4]) {
-        self.partida = Some(PartidaMus::<CuatroJugadores>::new(manos, self.tantos));
+        self.partida = Some(PartidaMus::<CuatroJugadores>::con_max_tantos(
+            manos,
+            self.tantos,
+            self.max_tantos,
+        ));
         self.actualizar_manos(Lance::Grande);
         // La 'M' marca el reparto, no la fase de mus: distingue la partida repartida del nodo
         // de azar inicial, que es el que `GameGraph` indexa con el historial vacío.
         self.history_str = ArrayString::<192>::from("M").unwrap();
+        self.historial_lance.clear();
         self.enforce_max_mus_rounds();
     }
 
@@ -93,6 +126,61 @@ impl MusGame {
         let _ = partida.actuar(Accion::NoMus);
         if let Some(FasePartida::Envites(lance)) = partida.fase() {
             self.actualizar_manos(lance);
+            self.iniciar_senas();
+        }
+    }
+
+    /// Empieza la fase de señas, si la partida tiene señas, al entrar en la fase de envites.
+    fn iniciar_senas(&mut self) {
+        if self.signals.is_none() {
+            return;
+        }
+        self.turno_sena = Some(0);
+        self.senas_str = [ArrayString::from("S").unwrap(); 4];
+        self.historial_senas = ArrayString::from("S").unwrap();
+    }
+
+    /// Anota la seña de `turno`, que ven él y su compañero, y pasa el turno al siguiente
+    /// jugador. Los rivales la ven al resolverse el nodo de azar, si lo hay.
+    fn hacer_sena(&mut self, turno: u8, accion: Accion) {
+        let signals = self.signals.as_ref().expect("La partida debe tener señas.");
+        let sena = match accion {
+            Accion::Sena(sena) => Some(sena),
+            _ => None,
+        };
+        let codigo = sena.map_or('p', |sena| sena.codigo());
+        for jugador in (turno as usize % 2..4).step_by(2) {
+            self.senas_str[jugador].push(codigo);
+        }
+        self.historial_senas.push(codigo);
+        self.sena_pendiente = Some((turno, sena));
+        if !signals.chance() {
+            let vista = signals.opponent_probability >= 1.;
+            self.ver_sena(vista);
+        }
+        self.turno_sena = (turno < 3).then_some(turno + 1);
+    }
+
+    /// Resuelve si los rivales ven la seña pendiente. Si no la ven, o si no se hizo seña, los
+    /// rivales anotan 'p'.
+    fn ver_sena(&mut self, vista: bool) {
+        let (turno, sena) = self
+            .sena_pendiente
+            .take()
+            .expect("Debe haber una seña pendiente.");
+        let codigo = sena.filter(|_| vista).map_or('p', |sena| sena.codigo());
+        for jugador in (1 - turno as usize % 2..4).step_by(2) {
+            self.senas_str[jugador].push(codigo);
+        }
+        self.historial_senas.push(if vista { '+' } else { '-' });
+    }
+
+    /// Historial de acciones que ven todos los jugadores, sin las señas.
+    fn historial_publico(&self) -> String {
+        if self.second_player_turn() {
+            format!("{}{}*", self.history_str, self.last_action.unwrap())
+        } else {
+            self.history_str.to_string()
         }
     }
 
@@ -126,19 +214,62 @@ impl MusGame {
         })
     }
 
-    pub fn with_utility_table(self, utility_table: Rc<[[f64; 40]; 40]>) -> Self {
+    /// Utilidades al acabar la mano: las de las manos siguientes del chico y la de ganarlo. Sin
+    /// tabla, la utilidad es la diferencia de tantos de la mano.
+    pub fn with_utility_table(self, utility_table: Arc<UtilityTable>) -> Self {
         Self {
             utility_table: Some(utility_table),
             ..self
         }
     }
 
-    pub fn default_utility_table() -> [[f64; 40]; 40] {
-        std::array::from_fn(|t1| std::array::from_fn(|t2| t1 as f64 - t2 as f64))
+    /// Envites de la fase de envites. Sin abstracción se usa `BetAbstraction::default()`.
+    pub fn with_bet_abstraction(self, bet_abstraction: Arc<BetAbstraction>) -> Self {
+        Self {
+            bet_abstraction: Some(bet_abstraction),
+            ..self
+        }
+    }
+
+    /// Descartes de la fase de descartes. Sin abstracción se usa `DiscardAbstraction::default()`.
+    pub fn with_discard_abstraction(self, discard_abstraction: Arc<DiscardAbstraction>) -> Self {
+        Self {
+            discard_abstraction: Some(discard_abstraction),
+            ..self
+        }
+    }
+
+    /// Señas entre compañeros al empezar la fase de envites. Sin señas, cada jugador solo sabe de
+    /// su compañero lo que dicen sus acciones.
+    pub fn with_signals(self, signals: Arc<Signals>) -> Self {
+        Self {
+            signals: Some(signals),
+            ..self
+        }
+    }
+
+    /// Tantos con los que se gana el chico. Por defecto, 40.
+    pub fn with_max_tantos(self, max_tantos: u8) -> Self {
+        Self { max_tantos, ..self }
+    }
+
+    pub fn mus_game(&self) -> Option<&PartidaMus<CuatroJugadores>> {
+        self.partida.as_ref()
+    }
+
+    /// Indica si los jugadores están haciendo las señas.
+    pub fn en_senas(&self) -> bool {
+        self.turno_sena.is_some()
+    }
+
+    /// Variante de reglas, que determina la baraja con la que se reparte. Por defecto, a ocho
+    /// reyes.
+    pub fn with_variante(self, variante: Variante) -> Self {
+        Self { variante, ..self }
     }
 
     fn iter_descartes<const N: usize>(game: Self) -> impl Iterator<Item = (Self, f64)> {
-        let Some(CardSource::Iterable(estado_baraja)) = game.cards else {
+        let Some(CardSource::Iterable(estado_baraja)) = &game.cards else {
             panic!("iter_descartes expects an iterable CardSource");
         };
         let iter = RepartoDescarteMusIter::<N>::new(estado_baraja);
@@ -183,24 +314,50 @@ impl Game for MusGame {
     }
 
     fn info_set_str(&self, player: usize) -> String {
-        let mut output = String::with_capacity(15 + self.history_str.len());
+        let mut output = String::with_capacity(20 + self.history_str.len());
         output.push_str(&self.info_set_prefix[player]);
         output.push_str(&self.descarte_str[player]);
-        output.push_str(&self.history_str());
+        output.push_str(&self.senas_str[player]);
+        output.push_str(&self.historial_publico());
         output
     }
 
-    fn new_random(&mut self) {
+    fn info_set_key(&self, player: usize) -> InfoSetKey {
+        let mut hasher = InfoSetHasher::new();
+        hasher
+            .write_str(&self.info_set_prefix[player])
+            .write_str(&self.descarte_str[player])
+            .write_str(&self.senas_str[player])
+            .write_str(&self.history_str);
+        if self.second_player_turn() {
+            let _ = write!(hasher, "{}*", self.last_action.unwrap());
+        }
+        hasher.finish()
+    }
+
+    fn info_set_prefix_len(&self, player: usize) -> usize {
+        self.info_set_prefix[player].len()
+            + self.descarte_str[player].len()
+            + self.senas_str[player].len()
+    }
+
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.sena_pendiente.is_some() {
+            let probability = self.signals.as_ref().unwrap().opponent_probability;
+            self.ver_sena(rng.gen_bool(probability));
+            return;
+        }
         let mut reparto_resuelto = false;
         match &mut self.partida {
             None => {
-                let mut baraja = Baraja::baraja_mus();
+                let mut baraja = self.variante.baraja();
+                baraja.barajar(rng);
                 let manos = baraja.repartir_manos();
                 self.set_hands(manos);
-                self.set_card_source(CardSource::Baraja(baraja));
+                self.set_card_source(CardSource::Baraja(baraja, rng.r#gen()));
             }
             Some(p) => {
-                if let Some(CardSource::Baraja(baraja)) = &mut self.cards {
+                if let Some(CardSource::Baraja(baraja, semilla)) = &mut self.cards {
                     let turno = match p
                         .turno()
                         .expect("Some player must be active to call new_random() after game start")
@@ -210,7 +367,8 @@ impl Game for MusGame {
                     } as usize;
                     let descartes = p.descartadas().unwrap();
                     InfoSetWriter(&mut self.descarte_str[turno]).descarte(&descartes);
-                    let nuevas = baraja.descartar(descartes.into_iter());
+                    let nuevas = baraja.descartar(descartes.into_iter(), rng);
+                    *semilla = rng.r#gen();
                     let _ = p.descartar_con_nuevas(&nuevas);
                     self.history_str.push('C');
                     // Las cartas nuevas cambian la mano de quien descartó.
@@ -234,28 +392,52 @@ impl Game for MusGame {
         self.manos_juego.clear();
         self.cards = None;
         self.mus_rounds = 0;
+        self.historial_lance.clear();
+        self.turno_sena = None;
+        self.sena_pendiente = None;
+        self.senas_str = [ArrayString::new(); 4];
+        self.historial_senas.clear();
     }
 
     fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
-        match &self.partida {
+        if let Some((_, sena)) = self.sena_pendiente {
+            // Los rivales no distinguen no haber visto la seña de que no se haya hecho, de modo
+            // que también hay nodo de azar sin seña, con un único resultado.
+            let probability = self.signals.as_ref().unwrap().opponent_probability;
+            let resultados = match sena {
+                Some(_) => vec![(true, probability), (false, 1. - probability)],
+                None => vec![(false, 1.)],
+            };
+            let game = self.clone();
+            return Either::Left(resultados.into_iter().map(move |(vista, probability)| {
+                let mut game = game.clone();
+                game.ver_sena(vista);
+                (game, probability)
+            }));
+        }
+        Either::Right(match &self.partida {
             None => {
-                let (tantos, abstract_game, max_mus_rounds) =
-                    (self.tantos, self.abstract_game, self.max_mus_rounds);
-                let partidas = RepartoMusIter::new().map(
+                // Los repartos conservan la configuración de la partida, como la tabla de
+                // utilidades y la abstracción de envites.
+                let root = self.clone();
+                let partidas = RepartoMusIter::con_variante(self.variante).map(
                     move |(mano1, mano2, mano3, mano4, probability, dist)| {
-                        let mut game =
-                            Self::new(tantos, abstract_game, max_mus_rounds).with_hands([
-                                Mano::new(mano1),
-                                Mano::new(mano2),
-                                Mano::new(mano3),
-                                Mano::new(mano4),
-                            ]);
+                        let mut game = root.clone();
+                        game.set_hands([
+                            Mano::new(mano1),
+                            Mano::new(mano2),
+                            Mano::new(mano3),
+                            Mano::new(mano4),
+                        ]);
                         game.set_card_source(CardSource::Iterable(dist));
                         (game, probability)
                     },
                 );
                 Either::Left(partidas)
             }
+            Some(_) if matches!(self.cards, Some(CardSource::Baraja(..))) => {
+                Either::Right(Either::Left(descarte_con_baraja(self, &self.cards)))
+            }
             Some(p) => {
                 let turno = match p
                     .turno()
@@ -275,19 +457,32 @@ impl Game for MusGame {
                     4 => Either::Right(Either::Right(Self::iter_descartes::<4>(game))),
                     _ => unreachable!(),
                 };
-                Either::Right(partidas)
+                Either::Right(Either::Right(partidas))
             }
-        }
+        })
     }
 
     fn actions(&self) -> Vec<Accion> {
+        if let Some(turno) = self.turno_sena {
+            let manos = self.partida.as_ref().unwrap().manos();
+            return self
+                .signals
+                .as_ref()
+                .unwrap()
+                .actions(&manos[turno as usize]);
+        }
         let partida = self.partida.as_ref().unwrap();
         debug_assert!(
             !matches!(partida.fase(), Some(FasePartida::Mus))
                 || self.mus_rounds < self.max_mus_rounds,
             "Mus phase reached and no available rounds: missing call to enforce_max_mus_rounds."
         );
-        let mut acciones = actions(partida);
+        let mut acciones = actions(
+            partida,
+            self.bet_abstraction.as_deref(),
+            self.discard_abstraction.as_deref(),
+            &self.historial_lance,
+        );
         if self.second_player_turn() {
             // El compañero conoce ya la acción del primer miembro de la pareja y solo puede
             // igualarla o subirla: la acción de la pareja es la suya.
@@ -300,6 +495,12 @@ impl Game for MusGame {
     }
 
     fn current_player(&self) -> NodeType {
+        if self.sena_pendiente.is_some() {
+            return NodeType::Chance;
+        }
+        if let Some(turno) = self.turno_sena {
+            return NodeType::Player(turno as usize);
+        }
         match &self.partida {
             None => NodeType::Chance,
             Some(partida) => match partida.fase() {
@@ -318,8 +519,15 @@ impl Game for MusGame {
     }
 
     fn act(&mut self, a: Accion) {
+        if let Some(turno) = self.turno_sena {
+            self.hacer_sena(turno, a);
+            return;
+        }
         self.last_action = Some(a);
-        if !self.first_player_action() {
+        // La acción de la pareja es la de su segundo jugador, que solo puede igualar o subir la
+        // del primero.
+        let accion_pareja = !self.first_player_action();
+        if accion_pareja {
             self.history_str.push_str(&a.to_string());
         }
         let fase = self
@@ -339,16 +547,23 @@ impl Game for MusGame {
                 match partida.fase() {
                     // Todos han pedido mus: se consume una ronda.
                     Some(FasePartida::Descartes) => self.mus_rounds += 1,
-                    Some(FasePartida::Envites(lance)) => self.actualizar_manos(lance),
+                    Some(FasePartida::Envites(lance)) => {
+                        self.actualizar_manos(lance);
+                        self.iniciar_senas();
+                    }
                     _ => {}
                 }
             }
             Some(FasePartida::Envites(lance_previo)) => {
                 let _ = partida.actuar(a);
+                if accion_pareja && sigue_arbol(self.bet_abstraction.as_deref()) {
+                    self.historial_lance.push(a);
+                }
                 let Some(FasePartida::Envites(lance_siguiente)) = partida.fase() else {
                     return;
                 };
                 if lance_previo != lance_siguiente {
+                    self.historial_lance.clear();
                     self.info_set_prefix = MusGame::info_set_prefix(
                         self.partida.as_ref().unwrap().manos(),
                         &self.tantos,
@@ -368,11 +583,12 @@ impl Game for MusGame {
     }
 
     fn history_str(&self) -> String {
-        if self.second_player_turn() {
-            format!("{}{}*", self.history_str, self.last_action.unwrap())
-        } else {
-            self.history_str.to_string()
-        }
+        format!("{}{}", self.historial_senas, self.historial_publico())
+    }
+
+    fn public_state_key(&self) -> InfoSetKey {
+        // Las señas no son públicas: cada jugador ve solo las que le dejan ver.
+        InfoSetHasher::hash_str(&self.historial_publico())
     }
 }
 
@@ -390,7 +606,13 @@ pub struct MusGameTwoHands {
     mus_rounds: u8,
     max_mus_rounds: u8,
     abstract_game: bool,
-    utility_table: Option<Rc<[[f64; 40]; 40]>>,
+    max_tantos: u8,
+    variante: Variante,
+    utility_table: Option<Arc<UtilityTable>>,
+    bet_abstraction: Option<Arc<BetAbstraction>>,
+    discard_abstraction: Option<Arc<DiscardAbstraction>>,
+    // Acciones de pareja del lance en curso. Solo se llevan si los envites siguen un árbol.
+    historial_lance: Vec<Accion>,
 }
 
 impl MusGameTwoHands {
@@ -415,20 +637,30 @@ impl MusGameTwoHands {
             max_mus_rounds,
             abstract_game,
             utility_table: None,
+            bet_abstraction: None,
+            discard_abstraction: None,
+            historial_lance: Vec::new(),
+            max_tantos: FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            variante: Variante::default(),
         }
     }
     pub fn with_hands(self, manos: [Mano; 4]) -> Self {
         let mut new_game = self.clone();
-        new_game.set_hands(manos);
+        new_game.set_hands(manos.map(|mano| self.variante.normalizar(&mano)));
         new_game
     }
 
     fn set_hands(&mut self, manos: [Mano; 4]) {
-        self.partida = Some(PartidaMus::<CuatroJugadores>::new(manos, self.tantos));
+        self.partida = Some(PartidaMus::<CuatroJugadores>::con_max_tantos(
+            manos,
+            self.tantos,
+            self.max_tantos,
+        ));
         self.actualizar_manos(Lance::Grande);
         // La 'M' marca el reparto, no la fase de mus: distingue la partida repartida del nodo
         // de azar inicial, que es el que `GameGraph` indexa con el historial vacío.
         self.history_str = ArrayString::<192>::from("M").unwrap();
+        self.historial_lance.clear();
         self.enforce_max_mus_rounds();
     }
 
@@ -505,15 +737,48 @@ impl MusGameTwoHands {
         })
     }
 
-    pub fn with_utility_table(self, utility_table: Rc<[[f64; 40]; 40]>) -> Self {
+    /// Utilidades al acabar la mano: las de las manos siguientes del chico y la de ganarlo. Sin
+    /// tabla, la utilidad es la diferencia de tantos de la mano.
+    pub fn with_utility_table(self, utility_table: Arc<UtilityTable>) -> Self {
         Self {
             utility_table: Some(utility_table),
             ..self
         }
     }
 
+    /// Envites de la fase de envites. Sin abstracción se usa `BetAbstraction::default()`.
+    pub fn with_bet_abstraction(self, bet_abstraction: Arc<BetAbstraction>) -> Self {
+        Self {
+            bet_abstraction: Some(bet_abstraction),
+            ..self
+        }
+    }
+
+    /// Descartes de la fase de descartes. Sin abstracción se usa `DiscardAbstraction::default()`.
+    pub fn with_discard_abstraction(self, discard_abstraction: Arc<DiscardAbstraction>) -> Self {
+        Self {
+            discard_abstraction: Some(discard_abstraction),
+            ..self
+        }
+    }
+
+    /// Tantos con los que se gana el chico. Por defecto, 40.
+    pub fn with_max_tantos(self, max_tantos: u8) -> Self {
+        Self { max_tantos, ..self }
+    }
+
+    pub fn mus_game(&self) -> Option<&PartidaMus<CuatroJugadores>> {
+        self.partida.as_ref()
+    }
+
+    /// Variante de reglas, que determina la baraja con la que se reparte. Por defecto, a ocho
+    /// reyes.
+    pub fn with_variante(self, variante: Variante) -> Self {
+        Self { variante, ..self }
+    }
+
     fn iter_descartes<const N: usize>(game: Self) -> impl Iterator<Item = (Self, f64)> {
-        let Some(CardSource::Iterable(estado_baraja)) = game.cards else {
+        let Some(CardSource::Iterable(estado_baraja)) = &game.cards else {
             panic!("iter_descartes expects an iterable CardSource");
         };
         let iter = RepartoDescarteMusIter::<N>::new(estado_baraja);
@@ -551,17 +816,30 @@ impl Game for MusGameTwoHands {
         output
     }
 
-    fn new_random(&mut self) {
+    fn info_set_key(&self, player: usize) -> InfoSetKey {
+        InfoSetHasher::new()
+            .write_str(&self.info_set_prefix[player])
+            .write_str(&self.descarte_str[player])
+            .write_str(&self.history_str)
+            .finish()
+    }
+
+    fn info_set_prefix_len(&self, player: usize) -> usize {
+        self.info_set_prefix[player].len() + self.descarte_str[player].len()
+    }
+
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         let mut reparto_resuelto = false;
         match &mut self.partida {
             None => {
-                let mut baraja = Baraja::baraja_mus();
+                let mut baraja = self.variante.baraja();
+                baraja.barajar(rng);
                 let manos = baraja.repartir_manos();
                 self.set_hands(manos);
-                self.set_card_source(CardSource::Baraja(baraja));
+                self.set_card_source(CardSource::Baraja(baraja, rng.r#gen()));
             }
             Some(p) => {
-                if let Some(CardSource::Baraja(baraja)) = &mut self.cards {
+                if let Some(CardSource::Baraja(baraja, semilla)) = &mut self.cards {
                     let turno = match p
                         .turno()
                         .expect("Some player must be active to call new_random() after game start")
@@ -571,7 +849,8 @@ impl Game for MusGameTwoHands {
                     } as usize;
                     let descartes = p.descartadas().unwrap();
                     InfoSetWriter(&mut self.descarte_str[turno % 2]).descarte(&descartes);
-                    let nuevas = baraja.descartar(descartes.into_iter());
+                    let nuevas = baraja.descartar(descartes.into_iter(), rng);
+                    *semilla = rng.r#gen();
                     let _ = p.descartar_con_nuevas(&nuevas);
                     self.history_str.push('C');
                     // Las cartas nuevas cambian la mano de quien descartó.
@@ -594,28 +873,33 @@ impl Game for MusGameTwoHands {
         self.cards = None;
         self.mus_rounds = 0;
         self.descarte_str = [ArrayString::new(); 2];
+        self.historial_lance.clear();
     }
 
     fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
         match &self.partida {
             None => {
-                let (tantos, abstract_game, max_mus_rounds) =
-                    (self.tantos, self.abstract_game, self.max_mus_rounds);
-                let partidas = RepartoMusIter::new().map(
+                // Los repartos conservan la configuración de la partida, como la tabla de
+                // utilidades y la abstracción de envites.
+                let root = self.clone();
+                let partidas = RepartoMusIter::con_variante(self.variante).map(
                     move |(mano1, mano2, mano3, mano4, probability, dist)| {
-                        let mut game =
-                            Self::new(tantos, abstract_game, max_mus_rounds).with_hands([
-                                Mano::new(mano1),
-                                Mano::new(mano2),
-                                Mano::new(mano3),
-                                Mano::new(mano4),
-                            ]);
+                        let mut game = root.clone();
+                        game.set_hands([
+                            Mano::new(mano1),
+                            Mano::new(mano2),
+                            Mano::new(mano3),
+                            Mano::new(mano4),
+                        ]);
                         game.set_card_source(CardSource::Iterable(dist));
                         (game, probability)
                     },
                 );
                 Either::Left(partidas)
             }
+            Some(_) if matches!(self.cards, Some(CardSource::Baraja(..))) => {
+                Either::Right(Either::Left(descarte_con_baraja(self, &self.cards)))
+            }
             Some(p) => {
                 let turno = match p
                     .turno()
@@ -636,7 +920,7 @@ impl Game for MusGameTwoHands {
                     _ => unreachable!(),
                 };
 
-                Either::Right(games)
+                Either::Right(Either::Right(games))
             }
         }
     }
@@ -648,7 +932,12 @@ impl Game for MusGameTwoHands {
                 || self.mus_rounds < self.max_mus_rounds,
             "Nodo de jugador en la fase de mus sin rondas disponibles:              falta un enforce_max_mus_rounds tras resolver el descarte."
         );
-        actions(partida)
+        actions(
+            partida,
+            self.bet_abstraction.as_deref(),
+            self.discard_abstraction.as_deref(),
+            &self.historial_lance,
+        )
     }
 
     fn current_player(&self) -> NodeType {
@@ -691,10 +980,14 @@ impl Game for MusGameTwoHands {
                         let _ = partida.actuar(a);
                     }
                     let _ = partida.actuar(a);
+                    if sigue_arbol(self.bet_abstraction.as_deref()) {
+                        self.historial_lance.push(a);
+                    }
                     let Some(FasePartida::Envites(lance_siguiente)) = partida.fase() else {
                         return;
                     };
                     if lance_previo != &lance_siguiente {
+                        self.historial_lance.clear();
                         self.info_set_prefix = MusGameTwoHands::info_set_prefix(
                             self.partida.as_ref().unwrap().manos(),
                             &self.tantos,
@@ -733,7 +1026,13 @@ pub struct MusGameTwoPlayers {
     mus_rounds: u8,
     max_mus_rounds: u8,
     abstract_game: bool,
-    utility_table: Option<Rc<[[f64; 40]; 40]>>,
+    max_tantos: u8,
+    variante: Variante,
+    utility_table: Option<Arc<UtilityTable>>,
+    bet_abstraction: Option<Arc<BetAbstraction>>,
+    discard_abstraction: Option<Arc<DiscardAbstraction>>,
+    // Acciones de pareja del lance en curso. Solo se llevan si los envites siguen un árbol.
+    historial_lance: Vec<Accion>,
 }
 
 impl MusGameTwoPlayers {
@@ -758,28 +1057,67 @@ impl MusGameTwoPlayers {
             max_mus_rounds,
             abstract_game,
             utility_table: None,
+            bet_abstraction: None,
+            discard_abstraction: None,
+            historial_lance: Vec::new(),
+            max_tantos: FaseEnvites::<CuatroJugadores>::MAX_TANTOS,
+            variante: Variante::default(),
         }
     }
 
-    pub fn with_utility_table(self, utility_table: Rc<[[f64; 40]; 40]>) -> Self {
+    /// Utilidades al acabar la mano: las de las manos siguientes del chico y la de ganarlo. Sin
+    /// tabla, la utilidad es la diferencia de tantos de la mano.
+    pub fn with_utility_table(self, utility_table: Arc<UtilityTable>) -> Self {
         Self {
             utility_table: Some(utility_table),
             ..self
         }
     }
 
+    /// Envites de la fase de envites. Sin abstracción se usa `BetAbstraction::default()`.
+    pub fn with_bet_abstraction(self, bet_abstraction: Arc<BetAbstraction>) -> Self {
+        Self {
+            bet_abstraction: Some(bet_abstraction),
+            ..self
+        }
+    }
+
+    /// Descartes de la fase de descartes. Sin abstracción se usa `DiscardAbstraction::default()`.
+    pub fn with_discard_abstraction(self, discard_abstraction: Arc<DiscardAbstraction>) -> Self {
+        Self {
+            discard_abstraction: Some(discard_abstraction),
+            ..self
+        }
+    }
+
+    /// Tantos con los que se gana el chico. Por defecto, 40.
+    pub fn with_max_tantos(self, max_tantos: u8) -> Self {
+        Self { max_tantos, ..self }
+    }
+
+    /// Variante de reglas, que determina la baraja con la que se reparte. Por defecto, a ocho
+    /// reyes.
+    pub fn with_variante(self, variante: Variante) -> Self {
+        Self { variante, ..self }
+    }
+
     pub fn with_hands(self, manos: [Mano; 2]) -> Self {
         let mut new_game = self.clone();
-        new_game.set_hands(manos);
+        new_game.set_hands(manos.map(|mano| self.variante.normalizar(&mano)));
         new_game
     }
 
     fn set_hands(&mut self, manos: [Mano; 2]) {
-        self.partida = Some(PartidaMus::<DosJugadores>::new(manos, self.tantos));
+        self.partida = Some(PartidaMus::<DosJugadores>::con_max_tantos(
+            manos,
+            self.tantos,
+            self.max_tantos,
+        ));
         self.actualizar_manos(Lance::Grande);
         // La 'M' marca el reparto, no la fase de mus: distingue la partida repartida del nodo
         // de azar inicial, que es el que `GameGraph` indexa con el historial vacío.
         self.history_str = ArrayString::<192>::from("M").unwrap();
+        self.historial_lance.clear();
         self.enforce_max_mus_rounds();
     }
 
@@ -845,7 +1183,7 @@ impl MusGameTwoPlayers {
     }
 
     fn iter_descartes<const N: usize>(game: Self) -> impl Iterator<Item = (Self, f64)> {
-        let Some(CardSource::Iterable(estado_baraja)) = game.cards else {
+        let Some(CardSource::Iterable(estado_baraja)) = &game.cards else {
             panic!("iter_descartes expects an iterable CardSource");
         };
         let iter = RepartoDescarteMusIter::<N>::new(estado_baraja);
@@ -883,17 +1221,30 @@ impl Game for MusGameTwoPlayers {
         output
     }
 
-    fn new_random(&mut self) {
+    fn info_set_key(&self, player: usize) -> InfoSetKey {
+        InfoSetHasher::new()
+            .write_str(&self.info_set_prefix[player])
+            .write_str(&self.descarte_str[player])
+            .write_str(&self.history_str)
+            .finish()
+    }
+
+    fn info_set_prefix_len(&self, player: usize) -> usize {
+        self.info_set_prefix[player].len() + self.descarte_str[player].len()
+    }
+
+    fn new_random<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         let mut reparto_resuelto = false;
         match &mut self.partida {
             None => {
-                let mut baraja = Baraja::baraja_mus();
+                let mut baraja = self.variante.baraja();
+                baraja.barajar(rng);
                 let manos = baraja.repartir_manos();
                 self.set_hands(manos);
-                self.set_card_source(CardSource::Baraja(baraja));
+                self.set_card_source(CardSource::Baraja(baraja, rng.r#gen()));
             }
             Some(p) => {
-                if let Some(CardSource::Baraja(baraja)) = &mut self.cards {
+                if let Some(CardSource::Baraja(baraja, semilla)) = &mut self.cards {
                     let turno = match p
                         .turno()
                         .expect("Some player must be active to call new_random() after game start")
@@ -903,7 +1254,8 @@ impl Game for MusGameTwoPlayers {
                     } as usize;
                     let descartes = p.descartadas().unwrap();
                     InfoSetWriter(&mut self.descarte_str[turno]).descarte(&descartes);
-                    let nuevas = baraja.descartar(descartes.into_iter());
+                    let nuevas = baraja.descartar(descartes.into_iter(), rng);
+                    *semilla = rng.r#gen();
                     let _ = p.descartar_con_nuevas(&nuevas);
                     self.history_str.push('C');
                     // Las cartas nuevas cambian la mano de quien descartó.
@@ -926,23 +1278,28 @@ impl Game for MusGameTwoPlayers {
         self.cards = None;
         self.mus_rounds = 0;
         self.descarte_str = [ArrayString::new(); 2];
+        self.historial_lance.clear();
     }
 
     fn new_iter(&self) -> impl Iterator<Item = (Self, f64)> {
         match &self.partida {
             None => {
-                let (tantos, abstract_game, max_mus_rounds) =
-                    (self.tantos, self.abstract_game, self.max_mus_rounds);
-                let games = RepartoMusDosJugadoresIter::new().map(
+                // Los repartos conservan la configuración de la partida, como la tabla de
+                // utilidades y la abstracción de envites.
+                let root = self.clone();
+                let games = RepartoMusDosJugadoresIter::con_variante(self.variante).map(
                     move |(mano1, mano2, probability, dist)| {
-                        let mut game = Self::new(tantos, abstract_game, max_mus_rounds)
-                            .with_hands([Mano::new(mano1), Mano::new(mano2)]);
+                        let mut game = root.clone();
+                        game.set_hands([Mano::new(mano1), Mano::new(mano2)]);
                         game.set_card_source(CardSource::Iterable(dist));
                         (game, probability)
                     },
                 );
                 Either::Left(games)
             }
+            Some(_) if matches!(self.cards, Some(CardSource::Baraja(..))) => {
+                Either::Right(Either::Left(descarte_con_baraja(self, &self.cards)))
+            }
             Some(p) => {
                 let turno = match p
                     .turno()
@@ -962,7 +1319,7 @@ impl Game for MusGameTwoPlayers {
                     4 => Either::Right(Either::Right(Self::iter_descartes::<4>(game))),
                     _ => unreachable!(),
                 };
-                Either::Right(games)
+                Either::Right(Either::Right(games))
             }
         }
     }
@@ -974,7 +1331,12 @@ impl Game for MusGameTwoPlayers {
                 || self.mus_rounds < self.max_mus_rounds,
             "Mus phase reached and no available rounds: missing call to enforce_max_mus_rounds."
         );
-        actions(partida)
+        actions(
+            partida,
+            self.bet_abstraction.as_deref(),
+            self.discard_abstraction.as_deref(),
+            &self.historial_lance,
+        )
     }
 
     fn current_player(&self) -> NodeType {
@@ -1013,10 +1375,14 @@ impl Game for MusGameTwoPlayers {
                 }
                 Some(FasePartida::Envites(lance_previo)) => {
                     let _ = partida.actuar(a);
+                    if sigue_arbol(self.bet_abstraction.as_deref()) {
+                        self.historial_lance.push(a);
+                    }
                     let Some(FasePartida::Envites(lance_siguiente)) = partida.fase() else {
                         return;
                     };
                     if lance_previo != &lance_siguiente {
+                        self.historial_lance.clear();
                         self.info_set_prefix = MusGameTwoPlayers::info_set_prefix(
                             self.partida.as_ref().unwrap().manos(),
                             &self.tantos,
@@ -1041,10 +1407,180 @@ impl Game for MusGameTwoPlayers {
     }
 }
 
+impl LbrGame for MusGame {
+    fn redeal<R: Rng + ?Sized>(&self, player: usize, rng: &mut R) -> Self {
+        let partida = self
+            .partida
+            .as_ref()
+            .expect("La partida debe estar repartida.");
+        let (manos, baraja) = repartir_ocultas(partida.manos(), player, self.variante, rng);
+        let mut game = self.clone();
+        game.reset();
+        game.set_hands(manos);
+        game.set_card_source(CardSource::Baraja(baraja, rng.r#gen()));
+        game
+    }
+
+    fn follow_discard<R: Rng + ?Sized>(
+        &mut self,
+        real: &Self,
+        real_next: &Self,
+        player: usize,
+        rng: &mut R,
+    ) -> bool {
+        if self.sena_pendiente.is_some() {
+            // Los rivales ven la seña en todos los mundos si la vieron en la partida real.
+            self.ver_sena(real_next.historial_senas.ends_with('+'));
+            return true;
+        }
+        let (Some(partida), Some(real), Some(real_next)) =
+            (&self.partida, &real.partida, &real_next.partida)
+        else {
+            panic!("La partida debe estar repartida.");
+        };
+        if !preparar_descarte(&mut self.cards, partida, real, real_next, player) {
+            return false;
+        }
+        self.new_random(rng);
+        true
+    }
+
+    fn hidden_action(&self, player: usize) -> bool {
+        // Si los rivales pueden ver la seña, el nodo de azar que la sigue descarta los mundos
+        // incompatibles.
+        self.turno_sena.is_some_and(|turno| {
+            turno as usize % 2 != player % 2
+                && self.signals.as_ref().unwrap().opponent_probability < 1.
+        })
+    }
+}
+
+impl LbrGame for MusGameTwoPlayers {
+    fn redeal<R: Rng + ?Sized>(&self, player: usize, rng: &mut R) -> Self {
+        let partida = self
+            .partida
+            .as_ref()
+            .expect("La partida debe estar repartida.");
+        let (manos, baraja) = repartir_ocultas(partida.manos(), player, self.variante, rng);
+        let mut game = self.clone();
+        game.reset();
+        game.set_hands(manos);
+        game.set_card_source(CardSource::Baraja(baraja, rng.r#gen()));
+        game
+    }
+
+    fn follow_discard<R: Rng + ?Sized>(
+        &mut self,
+        real: &Self,
+        real_next: &Self,
+        player: usize,
+        rng: &mut R,
+    ) -> bool {
+        let (Some(partida), Some(real), Some(real_next)) =
+            (&self.partida, &real.partida, &real_next.partida)
+        else {
+            panic!("La partida debe estar repartida.");
+        };
+        if !preparar_descarte(&mut self.cards, partida, real, real_next, player) {
+            return false;
+        }
+        self.new_random(rng);
+        true
+    }
+}
+
+/// Reparte al azar las manos de todos los jugadores salvo `player`, que conserva la suya, con las
+/// cartas que no tiene de la baraja de `variante`. Devuelve las manos y la baraja con las cartas
+/// sin repartir.
+fn repartir_ocultas<const N: usize, R: Rng + ?Sized>(
+    manos: &[Mano; N],
+    player: usize,
+    variante: Variante,
+    rng: &mut R,
+) -> ([Mano; N], Baraja) {
+    let mut baraja = variante.baraja();
+    for carta in manos[player].iter() {
+        baraja.extraer(*carta);
+    }
+    baraja.barajar(rng);
+    let manos = core::array::from_fn(|i| {
+        if i == player {
+            manos[i].clone()
+        } else {
+            Mano::from_arrayvec((0..4).map(|_| baraja.repartir().unwrap()).collect())
+        }
+    });
+    (manos, baraja)
+}
+
+/// Si el descarte pendiente de `partida` es de `player`, coloca encima de la baraja las cartas
+/// que recibió en la partida real al pasar de `real` a `real_next`, para que el siguiente
+/// `new_random` se las reparta. Devuelve false si esas cartas no quedan en la baraja.
+fn preparar_descarte<T: ModalidadMus>(
+    cards: &mut Option<CardSource>,
+    partida: &PartidaMus<T>,
+    real: &PartidaMus<T>,
+    real_next: &PartidaMus<T>,
+    player: usize,
+) -> bool {
+    let Some(Turno::Jugador(turno)) = partida.turno() else {
+        unreachable!("Los descartes son individuales.");
+    };
+    if turno as usize != player {
+        return true;
+    }
+    let Some(CardSource::Baraja(baraja, _)) = cards else {
+        panic!("follow_discard necesita una baraja concreta.");
+    };
+    let descartadas = real
+        .descartadas()
+        .expect("Debe haber un descarte pendiente.");
+    let conservadas = diferencia(real.manos().as_ref()[player].cartas(), &descartadas);
+    let nuevas = diferencia(real_next.manos().as_ref()[player].cartas(), &conservadas);
+    baraja.colocar_encima(&nuevas)
+}
+
+/// Descarte pendiente con una baraja concreta, como la de un reparto muestreado. Las cartas
+/// nuevas son las siguientes de la baraja y el descarte tiene un único resultado. Si se agota la
+/// baraja, se baraja de nuevo con la semilla de `cards`, de modo que el resultado depende solo
+/// del reparto.
+fn descarte_con_baraja<G: Game + Clone>(
+    game: &G,
+    cards: &Option<CardSource>,
+) -> iter::Once<(G, f64)> {
+    let Some(CardSource::Baraja(_, semilla)) = cards else {
+        panic!("El descarte necesita una baraja concreta.");
+    };
+    let mut game = game.clone();
+    game.new_random(&mut StdRng::seed_from_u64(*semilla));
+    iter::once((game, 1.))
+}
+
+/// Cartas de `cartas` que no están en `quitar`, contando las repetidas.
+fn diferencia(cartas: &[Carta], quitar: &[Carta]) -> ArrayVec<Carta, 4> {
+    let mut quitar: ArrayVec<Carta, 4> = quitar.iter().copied().collect();
+    cartas
+        .iter()
+        .copied()
+        .filter(|carta| match quitar.iter().position(|c| c == carta) {
+            Some(idx) => {
+                quitar.remove(idx);
+                false
+            }
+            None => true,
+        })
+        .collect()
+}
+
 /// Acciones legales en el estado actual. Solo se llega a un nodo de jugador en la fase de mus
 /// mientras queden rondas por jugar: al agotarse, `salir_de_mus_si_agotado` fuerza el `NoMus` en
 /// vez de dejar aquí un nodo con una única acción.
-fn actions<T: ModalidadMus>(partida: &PartidaMus<T>) -> Vec<Accion> {
+fn actions<T: ModalidadMus>(
+    partida: &PartidaMus<T>,
+    bet_abstraction: Option<&BetAbstraction>,
+    discard_abstraction: Option<&DiscardAbstraction>,
+    historial_lance: &[Accion],
+) -> Vec<Accion> {
     match partida.fase() {
         Some(FasePartida::Mus) => {
             vec![Accion::Mus, Accion::NoMus]
@@ -1058,65 +1594,30 @@ fn actions<T: ModalidadMus>(partida: &PartidaMus<T>) -> Vec<Accion> {
                 Turno::Pareja(t) => t,
             } as usize;
             let mano = &partida.manos().as_ref()[turno];
-            let mut descartes = [false; 4];
-            for (idx, carta) in mano.iter().enumerate() {
-                descartes[idx] = *carta != Carta::Rey;
-            }
-            if descartes == [false; 4] {
-                descartes[0] = true;
-            }
-            vec![Accion::Descartar(descartes)]
+            DiscardAbstraction::or_default(discard_abstraction).actions(mano)
         }
-        Some(FasePartida::Envites(_)) => {
+        Some(FasePartida::Envites(lance)) => {
             let fase_envites = partida.fase_envites().unwrap();
-            let ultimo_envite: Apuesta = fase_envites.ultima_apuesta();
-            let apuesta_maxima = fase_envites.apuesta_maxima();
-            let mut actions = match ultimo_envite {
-                Apuesta::Tantos(tantos) if tantos == apuesta_maxima => {
-                    return vec![Accion::Paso, Accion::Quiero, Accion::Ordago];
-                }
-                Apuesta::Tantos(0) => vec![
-                    Accion::Paso,
-                    Accion::Envido(2),
-                    Accion::Envido(5),
-                    Accion::Envido(10),
-                    Accion::Ordago,
-                ],
-                Apuesta::Tantos(2) => vec![
-                    Accion::Paso,
-                    Accion::Quiero,
-                    Accion::Envido(2),
-                    Accion::Envido(5),
-                    Accion::Envido(10),
-                    Accion::Ordago,
-                ],
-                Apuesta::Tantos(4..=5) => vec![
-                    Accion::Paso,
-                    Accion::Quiero,
-                    Accion::Envido(10),
-                    Accion::Ordago,
-                ],
-                Apuesta::Ordago => return vec![Accion::Paso, Accion::Quiero],
-                _ => return vec![Accion::Paso, Accion::Quiero, Accion::Ordago],
-            };
-            actions.retain(|action| {
-                if let Apuesta::Tantos(tantos) = ultimo_envite
-                    && let Accion::Envido(v) = action
-                {
-                    tantos + *v < apuesta_maxima
-                } else {
-                    true
-                }
-            });
-            actions
+            BetAbstraction::or_default(bet_abstraction).actions(
+                lance,
+                fase_envites.ultima_apuesta(),
+                fase_envites.num_envites(),
+                fase_envites.apuesta_maxima(),
+                historial_lance,
+            )
         }
         Some(FasePartida::DescartePendiente) => vec![],
         None => todo!(),
     }
 }
 
-fn utility(player: usize, tantos: &[u8; 2], utility_table: Option<&[[f64; 40]; 40]>) -> f64 {
-    utility_table.as_ref().map_or_else(
+/// Solo hace falta llevar el historial del lance si los envites siguen un árbol.
+fn sigue_arbol(bet_abstraction: Option<&BetAbstraction>) -> bool {
+    bet_abstraction.is_some_and(|bet_abstraction| bet_abstraction.action_tree.is_some())
+}
+
+fn utility(player: usize, tantos: &[u8; 2], utility_table: Option<&UtilityTable>) -> f64 {
+    utility_table.map_or_else(
         || {
             let payoff = [
                 tantos[0] as i8 - tantos[1] as i8,
@@ -1125,23 +1626,7 @@ fn utility(player: usize, tantos: &[u8; 2], utility_table: Option<&[[f64; 40]; 4
 
             payoff[player % 2] as f64
         },
-        |utility_table| {
-            if tantos[0] == 40 || tantos[1] == 40 {
-                let payoff = [
-                    tantos[0] as i8 - tantos[1] as i8,
-                    tantos[1] as i8 - tantos[0] as i8,
-                ];
-
-                payoff[player % 2] as f64
-            } else {
-                let expected_utility = utility_table[tantos[1] as usize][tantos[0] as usize];
-                if player.is_multiple_of(2) {
-                    -expected_utility
-                } else {
-                    expected_utility
-                }
-            }
-        },
+        |utility_table| utility_table.utility(player, tantos),
     )
 }
 
@@ -1185,8 +1670,10 @@ fn jugadas_manos(manos: &[Mano]) -> (ArrayString<4>, ArrayString<4>) {
 
 #[derive(Debug, Clone)]
 enum CardSource {
-    Baraja(Baraja),
-    Iterable([(Carta, u8); 8]),
+    /// Baraja concreta y semilla con la que se baraja de nuevo si se agota al descartar. La
+    /// semilla se renueva en cada descarte.
+    Baraja(Baraja, u64),
+    Iterable(FrecuenciasBaraja),
 }
 
 struct InfoSetWriter<W: Write>(W);
@@ -1271,6 +1758,52 @@ mod tests {
         assert_eq!(game.info_set_str(0), "38:37,R775,Mnpppop1000");
     }
 
+    #[test]
+    fn max_tantos() {
+        let manos = [
+            Mano::from_str("RRR5").unwrap(),
+            Mano::from_str("RCC1").unwrap(),
+        ];
+        // A 30 tantos, con 27 a 27 se juega como con 37 a 37 a 40.
+        let mut game = MusGameTwoPlayers::new([27, 27], false, 1)
+            .with_max_tantos(30)
+            .with_utility_table(Arc::new(UtilityTable::new(
+                UtilityTable::diferencia_tantos(30),
+                &Default::default(),
+            )))
+            .with_hands(manos);
+        game.act(Accion::NoMus);
+        assert_eq!(
+            game.actions(),
+            vec![Accion::Paso, Accion::Envido(2), Accion::Ordago,]
+        );
+        game.act(Accion::Ordago);
+        game.act(Accion::Quiero);
+        assert_eq!(game.current_player(), NodeType::Terminal);
+        assert_eq!(game.utility(0), 30.);
+        assert_eq!(game.utility(1), -30.);
+    }
+
+    #[test]
+    fn variante() {
+        // A cuatro reyes los treses se reparten como cartas propias, no como reyes.
+        let game = MusGameTwoPlayers::new([0, 0], false, 0).with_variante(Variante::CuatroReyes);
+        let (mut total, mut sin_treses) = (0., 0.);
+        for (game, probability) in game.new_iter() {
+            total += probability;
+            let manos = game.partida.as_ref().unwrap().manos().clone();
+            if manos
+                .iter()
+                .all(|mano| mano.iter().all(|c| *c != Carta::TresCuatroReyes))
+            {
+                sin_treses += probability;
+            }
+        }
+        assert!((total - 1.).abs() < 1e-9);
+        // Ninguna de las ocho cartas repartidas es uno de los cuatro treses.
+        assert!((sin_treses - 30_260_340. / 76_904_685.).abs() < 1e-9);
+    }
+
     #[test]
     fn two_players_actions() {
         let manos = [
@@ -1313,6 +1846,34 @@ mod tests {
         assert_eq!(game.actions(), vec![Accion::Paso, Accion::Ordago]);
     }
 
+    #[test]
+    fn action_tree_actions() {
+        let bet_abstraction =
+            Arc::new(BetAbstraction::from_action_tree("config/simple_action_tree.json").unwrap());
+        let mut game = MusGame::new([0, 0], false, 0)
+            .with_bet_abstraction(bet_abstraction)
+            .with_hands(cuatro_manos());
+        let abrir = vec![Accion::Paso, Accion::Envido(2), Accion::Ordago];
+        assert_eq!(game.actions(), abrir);
+        game.act(Accion::Paso);
+        assert_eq!(game.actions(), abrir);
+        game.act(Accion::Paso);
+        assert_eq!(game.current_player(), NodeType::Player(1));
+        assert_eq!(game.actions(), abrir);
+        game.act(Accion::Envido(2));
+        assert_eq!(game.actions(), vec![Accion::Envido(2), Accion::Ordago]);
+        game.act(Accion::Envido(2));
+        assert_eq!(
+            game.actions(),
+            vec![Accion::Paso, Accion::Quiero, Accion::Ordago]
+        );
+        game.act(Accion::Quiero);
+        game.act(Accion::Quiero);
+        // En chica se vuelve a la raíz del árbol.
+        assert_eq!(game.current_player(), NodeType::Player(0));
+        assert_eq!(game.actions(), abrir);
+    }
+
     /// Cuatro manos con pares y juego, para que todos los lances se jueguen con las dos parejas
     /// completas.
     fn cuatro_manos() -> [Mano; 4] {
@@ -1407,12 +1968,68 @@ mod tests {
         assert_eq!(game.info_set_str(2), "38:37,RRR4,Mnpppop1111op1111");
     }
 
+    #[test]
+    fn senas_infoset() {
+        let signals = |opponent_probability| {
+            Arc::new(Signals {
+                vocabulary: vec![Sena::Medias, Sena::Treintayuna],
+                opponent_probability,
+            })
+        };
+        let mut game = MusGame::new([38, 37], false, 0)
+            .with_signals(signals(0.))
+            .with_hands(cuatro_manos());
+        // Cada jugador solo puede hacer las señas que corresponden a su mano.
+        assert_eq!(game.current_player(), NodeType::Player(0));
+        assert_eq!(
+            game.actions(),
+            vec![Accion::Paso, Accion::Sena(Sena::Medias)]
+        );
+        game.act(Accion::Sena(Sena::Medias));
+        assert_eq!(game.current_player(), NodeType::Player(1));
+        assert_eq!(
+            game.actions(),
+            vec![Accion::Paso, Accion::Sena(Sena::Treintayuna)]
+        );
+        game.act(Accion::Sena(Sena::Treintayuna));
+        game.act(Accion::Paso);
+        game.act(Accion::Paso);
+        // Cada pareja ve sus señas, pero no las de los rivales.
+        assert_eq!(game.current_player(), NodeType::Player(0));
+        assert_eq!(game.info_set_str(2), "38:37,RRR4,SmpppM");
+        assert_eq!(game.info_set_str(3), "38:37,RCC7,SpjppM");
+
+        // Si los rivales pueden verla, cada seña va seguida de un nodo de azar, también cuando no
+        // se hace seña.
+        let mut game = MusGame::new([38, 37], false, 0)
+            .with_signals(signals(0.25))
+            .with_hands(cuatro_manos());
+        game.act(Accion::Sena(Sena::Medias));
+        assert_eq!(game.current_player(), NodeType::Chance);
+        let resultados: Vec<(MusGame, f64)> = game.new_iter().collect();
+        assert_eq!(resultados.len(), 2);
+        assert_eq!(resultados[0].1, 0.25);
+        assert_eq!(resultados[0].0.info_set_str(1), "38:37,RCC1,SmM");
+        assert_eq!(resultados[1].0.info_set_str(1), "38:37,RCC1,SpM");
+        // Los rivales no distinguen si han visto la seña, pero el historial completo sí.
+        assert_ne!(resultados[0].0.history_str(), resultados[1].0.history_str());
+        assert_eq!(
+            resultados[0].0.public_state_key(),
+            resultados[1].0.public_state_key()
+        );
+        let mut game = resultados[1].0.clone();
+        game.act(Accion::Paso);
+        assert_eq!(game.new_iter().count(), 1);
+    }
+
     #[test]
     fn mus_game_descartes_infoset() {
         let mut game = MusGame::new([0, 0], false, 1).with_hands(cuatro_manos());
         // La distribución de la que salen las cartas nuevas; el test solo depende de las cartas
         // descartadas, que son las que entran en el conjunto de información.
-        game.set_card_source(CardSource::Iterable(Baraja::FREC_BARAJA_MUS));
+        game.set_card_source(CardSource::Iterable(
+            Baraja::FREC_BARAJA_MUS.into_iter().collect(),
+        ));
 
         for jugador in [0, 2, 1, 3] {
             assert_eq!(game.current_player(), NodeType::Player(jugador));
@@ -1466,6 +2083,50 @@ mod tests {
         assert!(!game.info_set_str(1).contains(",5,"));
     }
 
+    #[test]
+    fn discard_abstraction_infoset() {
+        let manos = [
+            Mano::from_str("RC51").unwrap(),
+            Mano::from_str("RRR5").unwrap(),
+        ];
+        let mut game = MusGameTwoPlayers::new([0, 0], false, 1)
+            .with_discard_abstraction(Arc::new(DiscardAbstraction::All))
+            .with_hands(manos);
+        game.set_card_source(CardSource::Iterable(
+            Baraja::FREC_BARAJA_MUS.into_iter().collect(),
+        ));
+        game.act(Accion::Mus);
+        game.act(Accion::Mus);
+        assert_eq!(game.actions().len(), 15);
+
+        let descartar = |descarte: [bool; 4]| {
+            let mut game = game.clone();
+            game.act(Accion::Descartar(descarte));
+            let repartos: Vec<_> = game.new_iter().collect();
+            let probabilidad: f64 = repartos.iter().map(|(_, p)| p).sum();
+            assert!((probabilidad - 1.).abs() < 1e-9);
+            repartos.into_iter().next().unwrap().0
+        };
+        let sin_c5 = descartar([false, true, true, false]);
+        let sin_51 = descartar([false, false, true, true]);
+        // Iteradas en el mismo orden, las cartas nuevas son las mismas: solo cambia lo que se
+        // conserva de la mano.
+        let nuevas = |game: &MusGameTwoPlayers, conservadas: &[Carta]| {
+            diferencia(
+                game.partida.as_ref().unwrap().manos()[0].cartas(),
+                conservadas,
+            )
+        };
+        assert_eq!(
+            nuevas(&sin_c5, &[Carta::Rey, Carta::As]),
+            nuevas(&sin_51, &[Carta::Rey, Carta::Caballo])
+        );
+        // El rival solo ve cuántas cartas se descartan.
+        assert_eq!(sin_c5.info_set_str(1), sin_51.info_set_str(1));
+        assert!(sin_c5.info_set_str(0).contains(",C5,"));
+        assert!(sin_51.info_set_str(0).contains(",51,"));
+    }
+
     #[test]
     fn update_hands_after_discard() {
         // Ninguna mano tiene juego al reparto inicial.
@@ -1504,4 +2165,110 @@ mod tests {
             MusGameTwoPlayers::info_set_prefix(&manos_reales, &game.tantos, None);
         assert_eq!(game.info_set_prefix, prefijo_esperado);
     }
+
+    /// Recorre partidas aleatorias comprobando en cada nodo de jugador que la clave compacta del
+    /// conjunto de información es el hash de su representación en texto.
+    fn check_info_set_keys<G>(mut game: G)
+    where
+        G: Game<Action = Accion> + Clone,
+    {
+        use rand::seq::SliceRandom;
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            game.reset();
+            loop {
+                match game.current_player() {
+                    NodeType::Chance => game.new_random(&mut rng),
+                    NodeType::Player(player) => {
+                        assert_eq!(
+                            game.info_set_key(player),
+                            InfoSetHasher::hash_str(&game.info_set_str(player)),
+                            "{}",
+                            game.info_set_str(player)
+                        );
+                        assert!(
+                            game.info_set_str(player)
+                                .is_char_boundary(game.info_set_prefix_len(player))
+                        );
+                        let action = *game.actions().choose(&mut rng).unwrap();
+                        game.act(action);
+                    }
+                    NodeType::Terminal => break,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn info_set_key() {
+        check_info_set_keys(MusGame::new([0, 0], false, 2));
+        check_info_set_keys(MusGame::new([30, 35], true, 1));
+        check_info_set_keys(
+            MusGame::new([0, 0], false, 1).with_signals(Arc::new(Signals {
+                vocabulary: Sena::SENAS.to_vec(),
+                opponent_probability: 0.5,
+            })),
+        );
+        check_info_set_keys(MusGameTwoHands::new([0, 0], false, 2));
+        check_info_set_keys(MusGameTwoPlayers::new([0, 0], false, 2));
+    }
+
+    #[test]
+    fn sampled_exploitability() {
+        // Con la baraja de un reparto muestreado cada descarte tiene un único resultado, así que
+        // la mejor respuesta puede recorrer los repartos muestreados hasta el final.
+        let mut game = MusGameTwoPlayers::new([38, 38], false, 1);
+        let mut cfr = crate::Cfr::new().with_seed(5);
+        cfr.train(
+            &mut game,
+            crate::CfrMethod::ExternalSampling,
+            100,
+            |_, _| {},
+        );
+        let mut sampled_game = crate::SampledGame::new(&game, 20, &mut StdRng::seed_from_u64(5));
+        let exploitability = cfr.exploitability(&mut sampled_game);
+        assert!(exploitability.is_finite() && exploitability >= 0.);
+    }
+
+    #[test]
+    fn lbr_worlds_follow_discards() {
+        // El jugador conserva en sus mundos la misma mano que en la partida real, también después
+        // de los descartes. Un mundo deja de ser posible si otra mano tiene las cartas que recibe.
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut game = MusGame::new([0, 0], false, 2);
+        game.new_random(&mut rng);
+        let mut worlds: Vec<MusGame> = (0..20).map(|_| game.redeal(1, &mut rng)).collect();
+        let mut descartes = 0;
+        while let Some(FasePartida::Mus | FasePartida::Descartes | FasePartida::DescartePendiente) =
+            game.partida.as_ref().unwrap().fase()
+        {
+            if let NodeType::Chance = game.current_player() {
+                let real = game.clone();
+                game.new_random(&mut rng);
+                worlds.retain_mut(|world| world.follow_discard(&real, &game, 1, &mut rng));
+                descartes += 1;
+            } else {
+                game.act(game.actions()[0]);
+                for world in &mut worlds {
+                    world.act(world.actions()[0]);
+                }
+            }
+            for world in &worlds {
+                assert_eq!(world.info_set_prefix[1], game.info_set_prefix[1]);
+                assert_eq!(world.descarte_str[1], game.descarte_str[1]);
+                let cartas: usize = world
+                    .partida
+                    .as_ref()
+                    .unwrap()
+                    .manos()
+                    .iter()
+                    .map(|mano| mano.cartas().len())
+                    .sum();
+                assert_eq!(cartas, 16);
+            }
+        }
+        assert_eq!(descartes, 8);
+        assert!(!worlds.is_empty());
+    }
 }
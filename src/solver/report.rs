@@ -0,0 +1,964 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use rand::{Rng, distributions::Distribution, distributions::WeightedIndex};
+
+use crate::{
+    Game, NodeType,
+    mus::{Accion, DosJugadores, EstadoLance, Lance, Mano},
+    solver::{
+        GameConfig, GameType, LanceGame, LanceGameTwoHands, LbrEstimate, ManosNormalizadas,
+        MusGame, MusGameTwoHands, MusGameTwoPlayers, Strategy, lance_en_juego,
+    },
+};
+
+/// Fase de la partida en la que se toma una decisión.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub enum Phase {
+    Mus,
+    Descartes,
+    Senas,
+    Grande,
+    Chica,
+    Pares,
+    Juego,
+    Punto,
+}
+
+impl Phase {
+    /// Fase de una decisión entre `actions` durante los envites de `lance`, o antes de ellos. Las
+    /// señas no se distinguen por sus acciones, que empiezan por el paso y pueden ser solo el
+    /// paso: se reconocen por el estado de la partida.
+    fn new(actions: &[Accion], lance: Lance) -> Self {
+        match actions.first() {
+            Some(Accion::Mus | Accion::NoMus) => Phase::Mus,
+            Some(Accion::Descartar(_)) => Phase::Descartes,
+            _ => match lance {
+                Lance::Grande => Phase::Grande,
+                Lance::Chica => Phase::Chica,
+                Lance::Pares => Phase::Pares,
+                Lance::Juego => Phase::Juego,
+                Lance::Punto => Phase::Punto,
+            },
+        }
+    }
+
+    fn lance(&self) -> Option<Lance> {
+        match self {
+            Phase::Grande => Some(Lance::Grande),
+            Phase::Chica => Some(Lance::Chica),
+            Phase::Pares => Some(Lance::Pares),
+            Phase::Juego => Some(Lance::Juego),
+            Phase::Punto => Some(Lance::Punto),
+            Phase::Mus | Phase::Descartes | Phase::Senas => None,
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.lance() {
+            Some(lance) => write!(f, "{lance:?}"),
+            None if *self == Phase::Senas => f.write_str("Señas"),
+            None => write!(f, "{self:?}"),
+        }
+    }
+}
+
+/// Frecuencia con la que se envida y se echa órdago con una clase de manos.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct Aggression {
+    pub decisions: usize,
+    /// Probabilidad media de envidar, sin contar los órdagos.
+    pub envido: f64,
+    /// Probabilidad media de echar órdago.
+    pub ordago: f64,
+}
+
+/// Decisiones de una fase en las partidas simuladas.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct PhaseReport {
+    /// Conjuntos de información distintos por los que se ha pasado.
+    pub info_sets: usize,
+    /// Conjuntos de información de la estrategia en esta fase, se haya pasado por ellos o no.
+    pub total_info_sets: usize,
+    pub decisions: usize,
+    /// Probabilidad media de cada acción, de la más jugada a la menos.
+    pub actions: Vec<(String, f64)>,
+    /// Agresividad por clase de mano: la jugada abstracta del lance. Solo en los lances.
+    pub aggression: BTreeMap<String, Aggression>,
+}
+
+/// Resumen de la cobertura y la calidad de una estrategia.
+///
+/// Las estadísticas de los nodos se calculan sobre todos los conjuntos de información de la
+/// estrategia. Las de cada fase, sobre partidas simuladas en las que todos los jugadores siguen
+/// la estrategia, así que cada decisión pesa según lo a menudo que se llega a ella.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StrategyReport {
+    pub game_type: GameType,
+    pub info_sets: usize,
+    /// Fracción de los nodos con más de una acción cuya estrategia no se aleja de la uniforme
+    /// más de [`StrategyReport::NEAR_UNIFORM`] en ninguna acción. Suelen ser nodos sin entrenar.
+    pub near_uniform: f64,
+    /// Número de nodos con más de una acción en cada décima de entropía normalizada, de la
+    /// estrategia pura a la uniforme.
+    pub entropy_histogram: Vec<usize>,
+    pub mean_entropy: f64,
+    /// Partidas simuladas, repartidas entre los marcadores de la estrategia.
+    pub deals: usize,
+    /// Decisiones de las partidas simuladas cuyo conjunto de información no está en la
+    /// estrategia. Se juegan con la estrategia uniforme.
+    pub missing_info_sets: usize,
+    /// Conjuntos de información de la estrategia por los que no ha pasado ninguna simulación.
+    pub unvisited_info_sets: usize,
+    /// Conjuntos de información de la estrategia en cuyo historial no se reconoce la fase.
+    pub unclassified_info_sets: usize,
+    pub phases: BTreeMap<Phase, PhaseReport>,
+    /// Utilidad esperada de la pareja mano con cada marcador, de la tabla de utilidades.
+    pub utility_table: Vec<Vec<f64>>,
+    /// Cota inferior de la explotabilidad con [`Lbr`](super::Lbr), si se ha calculado.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lbr: Option<LbrEstimate>,
+}
+
+/// Acumulado de las decisiones de una fase durante las simulaciones.
+#[derive(Default)]
+struct PhaseStats {
+    info_sets: BTreeSet<String>,
+    decisions: usize,
+    actions: BTreeMap<String, f64>,
+    aggression: BTreeMap<String, Aggression>,
+}
+
+impl StrategyReport {
+    /// Distancia máxima a la probabilidad uniforme de los nodos que se consideran sin entrenar.
+    /// Las probabilidades de las estrategias exportadas están redondeadas a centésimas.
+    pub const NEAR_UNIFORM: f64 = 0.05;
+    const ENTROPY_BUCKETS: usize = 10;
+
+    /// Analiza `strategy` simulando `deals` partidas.
+    pub fn new<R: Rng + ?Sized>(strategy: &Strategy, deals: usize, rng: &mut R) -> Self {
+        let mut near_uniform = 0;
+        let mut entropy_histogram = vec![0; Self::ENTROPY_BUCKETS];
+        let mut total_entropy = 0.;
+        let mut nodes = 0;
+        for probabilities in strategy.nodes.values().filter(|p| p.len() > 1) {
+            let n = probabilities.len() as f64;
+            if probabilities
+                .iter()
+                .all(|p| (p - 1. / n).abs() <= Self::NEAR_UNIFORM)
+            {
+                near_uniform += 1;
+            }
+            let entropy = normalized_entropy(probabilities);
+            let bucket =
+                ((entropy * Self::ENTROPY_BUCKETS as f64) as usize).min(Self::ENTROPY_BUCKETS - 1);
+            entropy_histogram[bucket] += 1;
+            total_entropy += entropy;
+            nodes += 1;
+        }
+
+        let mut simulation = Simulation {
+            strategy,
+            phases: BTreeMap::new(),
+            missing: 0,
+        };
+        simulation.run(deals, rng);
+        let visited: BTreeSet<&String> = simulation
+            .phases
+            .values()
+            .flat_map(|stats| stats.info_sets.iter())
+            .collect();
+        let unvisited_info_sets = strategy
+            .nodes
+            .keys()
+            .filter(|info_set| !visited.contains(info_set))
+            .count();
+        let mut phases: BTreeMap<Phase, PhaseReport> = simulation
+            .phases
+            .into_iter()
+            .map(|(phase, stats)| (phase, stats.into_report()))
+            .collect();
+        let mut unclassified_info_sets = 0;
+        for info_set in strategy.nodes.keys() {
+            match info_set_phase(info_set, &strategy.strategy_config.game_config) {
+                Some(phase) => phases.entry(phase).or_default().total_info_sets += 1,
+                None => unclassified_info_sets += 1,
+            }
+        }
+
+        Self {
+            game_type: strategy.strategy_config.game_config.game_type,
+            info_sets: strategy.nodes.len(),
+            near_uniform: fraction(near_uniform as f64, nodes),
+            entropy_histogram,
+            mean_entropy: fraction(total_entropy, nodes),
+            deals,
+            missing_info_sets: simulation.missing,
+            unvisited_info_sets,
+            unclassified_info_sets,
+            phases,
+            utility_table: strategy.utility_table.clone(),
+            lbr: None,
+        }
+    }
+
+    pub fn with_lbr(self, lbr: LbrEstimate) -> Self {
+        Self {
+            lbr: Some(lbr),
+            ..self
+        }
+    }
+}
+
+impl PhaseStats {
+    fn into_report(self) -> PhaseReport {
+        let mut actions: Vec<(String, f64)> = self
+            .actions
+            .into_iter()
+            .map(|(action, total)| (action, total / self.decisions as f64))
+            .collect();
+        actions.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        PhaseReport {
+            info_sets: self.info_sets.len(),
+            // Se cuentan sobre los nodos de la estrategia, no sobre las simulaciones.
+            total_info_sets: 0,
+            decisions: self.decisions,
+            actions,
+            aggression: self
+                .aggression
+                .into_iter()
+                .map(|(class, aggression)| {
+                    let decisions = aggression.decisions as f64;
+                    let aggression = Aggression {
+                        envido: aggression.envido / decisions,
+                        ordago: aggression.ordago / decisions,
+                        ..aggression
+                    };
+                    (class, aggression)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn fraction(value: f64, total: usize) -> f64 {
+    if total == 0 { 0. } else { value / total as f64 }
+}
+
+/// Entropía de una estrategia dividida por la de la uniforme con el mismo número de acciones.
+fn normalized_entropy(probabilities: &[f64]) -> f64 {
+    let total: f64 = probabilities.iter().sum();
+    if total <= 0. {
+        return 1.;
+    }
+    let entropy: f64 = probabilities
+        .iter()
+        .map(|p| p / total)
+        .filter(|p| *p > 0.)
+        .map(|p| -p * p.ln())
+        .sum();
+    entropy / (probabilities.len() as f64).ln()
+}
+
+/// Partidas simuladas con la estrategia.
+struct Simulation<'a> {
+    strategy: &'a Strategy,
+    phases: BTreeMap<Phase, PhaseStats>,
+    missing: usize,
+}
+
+impl Simulation<'_> {
+    /// Juega `deals` partidas repartidas por igual entre los marcadores que aparecen en la
+    /// estrategia.
+    fn run<R: Rng + ?Sized>(&mut self, deals: usize, rng: &mut R) {
+        let mut marcadores: Vec<[u8; 2]> = self
+            .strategy
+            .nodes
+            .keys()
+            .filter_map(|info_set| parse_tantos(info_set))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if marcadores.is_empty() {
+            marcadores.push([0, 0]);
+        }
+        for (i, tantos) in marcadores.iter().enumerate() {
+            let deals = deals / marcadores.len() + usize::from(i < deals % marcadores.len());
+            self.run_tantos(*tantos, deals, rng);
+        }
+    }
+
+    fn run_tantos<R: Rng + ?Sized>(&mut self, tantos: [u8; 2], deals: usize, rng: &mut R) {
+        let game_config = &self.strategy.strategy_config.game_config;
+        let bet_abstraction = self.strategy.bet_abstraction();
+        let discard_abstraction = self.strategy.discard_abstraction();
+        match game_config.game_type {
+            GameType::LanceGame(lance) => {
+                let game = LanceGame::new(lance, tantos, game_config.abstract_game)
+                    .with_bet_abstraction(bet_abstraction)
+                    .with_max_tantos(game_config.max_tantos)
+                    .with_variante(game_config.variante);
+                self.play(&game, deals, rng, |_, actions| Phase::new(actions, lance));
+            }
+            GameType::LanceGameTwoHands(lance) => {
+                let game = LanceGameTwoHands::new(lance, tantos, game_config.abstract_game)
+                    .with_bet_abstraction(bet_abstraction)
+                    .with_max_tantos(game_config.max_tantos)
+                    .with_variante(game_config.variante);
+                self.play(&game, deals, rng, |_, actions| Phase::new(actions, lance));
+            }
+            GameType::MusGame => {
+                let mut game = MusGame::new(
+                    tantos,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
+                )
+                .with_bet_abstraction(bet_abstraction)
+                .with_discard_abstraction(discard_abstraction)
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante);
+                if let Some(signals) = &game_config.signals {
+                    game = game.with_signals(std::sync::Arc::new(signals.clone()));
+                }
+                self.play(&game, deals, rng, |game, actions| {
+                    if game.en_senas() {
+                        return Phase::Senas;
+                    }
+                    let lance = lance_en_juego(game.mus_game().and_then(|partida| partida.fase()));
+                    Phase::new(actions, lance)
+                });
+            }
+            GameType::MusGameTwoHands => {
+                let game = MusGameTwoHands::new(
+                    tantos,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
+                )
+                .with_bet_abstraction(bet_abstraction)
+                .with_discard_abstraction(discard_abstraction)
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante);
+                self.play(&game, deals, rng, |game, actions| {
+                    let lance = lance_en_juego(game.mus_game().and_then(|partida| partida.fase()));
+                    Phase::new(actions, lance)
+                });
+            }
+            GameType::MusGameTwoPlayers => {
+                let game = MusGameTwoPlayers::new(
+                    tantos,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
+                )
+                .with_bet_abstraction(bet_abstraction)
+                .with_discard_abstraction(discard_abstraction)
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante);
+                self.play(&game, deals, rng, |game, actions| {
+                    let lance = lance_en_juego(game.mus_game().and_then(|partida| partida.fase()));
+                    Phase::new(actions, lance)
+                });
+            }
+        }
+    }
+
+    fn play<G, R>(
+        &mut self,
+        game: &G,
+        deals: usize,
+        rng: &mut R,
+        phase: impl Fn(&G, &[Accion]) -> Phase,
+    ) where
+        G: Game<Action = Accion> + Clone,
+        R: Rng + ?Sized,
+    {
+        for _ in 0..deals {
+            let mut game = game.clone();
+            game.reset();
+            game.new_random(rng);
+            loop {
+                match game.current_player() {
+                    NodeType::Terminal => break,
+                    NodeType::Chance => game.new_random(rng),
+                    NodeType::Player(player) => {
+                        let actions = game.actions();
+                        let info_set = game.info_set_str(player);
+                        let probabilities = self.record(info_set, &actions, phase(&game, &actions));
+                        let action = WeightedIndex::new(&probabilities)
+                            .expect(
+                                "La estrategia debe tener alguna acción con probabilidad positiva.",
+                            )
+                            .sample(rng);
+                        game.act(actions[action]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Anota una decisión y devuelve la estrategia con la que se toma.
+    fn record(&mut self, info_set: String, actions: &[Accion], phase: Phase) -> Vec<f64> {
+        let game_config = &self.strategy.strategy_config.game_config;
+        let probabilities = match self
+            .strategy
+            .nodes
+            .get(&info_set)
+            .filter(|probabilities| probabilities.len() == actions.len())
+            .map(|probabilities| {
+                // Las probabilidades de la estrategia están redondeadas.
+                let total: f64 = probabilities.iter().sum();
+                probabilities.iter().map(|p| p / total).collect::<Vec<_>>()
+            })
+            .filter(|probabilities| probabilities.iter().all(|p| p.is_finite()))
+        {
+            Some(probabilities) => probabilities,
+            None => {
+                self.missing += 1;
+                vec![1. / actions.len() as f64; actions.len()]
+            }
+        };
+        let stats = self.phases.entry(phase).or_default();
+        stats.decisions += 1;
+        for (action, p) in actions.iter().zip(&probabilities) {
+            *stats.actions.entry(action.to_string()).or_default() += p;
+        }
+        if let Some(lance) = phase.lance() {
+            let class = hand_class(
+                &info_set,
+                game_config.game_type,
+                lance,
+                game_config.abstract_game,
+            );
+            let aggression = stats.aggression.entry(class).or_default();
+            aggression.decisions += 1;
+            for (action, p) in actions.iter().zip(&probabilities) {
+                match action {
+                    Accion::Envido(_) => aggression.envido += p,
+                    Accion::Ordago => aggression.ordago += p,
+                    _ => {}
+                }
+            }
+        }
+        if self.strategy.nodes.contains_key(&info_set) {
+            stats.info_sets.insert(info_set);
+        }
+        probabilities
+    }
+}
+
+/// Marcador con el que empieza el conjunto de información, como 12:30.
+fn parse_tantos(info_set: &str) -> Option<[u8; 2]> {
+    let (tantos, _) = info_set.split_once(',')?;
+    let (tantos1, tantos2) = tantos.split_once(':')?;
+    Some([tantos1.parse().ok()?, tantos2.parse().ok()?])
+}
+
+/// Fase de la decisión de un conjunto de información de una partida de `game_config`, según su
+/// historial público. En los lances sueltos es siempre la del lance. En las partidas completas
+/// se siguen las rondas de mus y de descartes, las señas y los envites de cada lance, que
+/// terminan como en [`EstadoLance`]; los indicadores de pares y juego dicen qué lance sigue a
+/// la chica y a los pares. Devuelve `None` si el historial no es de una partida así.
+fn info_set_phase(info_set: &str, game_config: &GameConfig) -> Option<Phase> {
+    let manos = match game_config.game_type {
+        GameType::LanceGame(lance) | GameType::LanceGameTwoHands(lance) => {
+            return Some(Phase::new(&[], lance));
+        }
+        GameType::MusGame | GameType::MusGameTwoHands => 4,
+        GameType::MusGameTwoPlayers => 2,
+    };
+    // El historial no tiene comas: tras la última están las señas, si las hay, y el historial,
+    // que empieza por la 'M' del reparto.
+    let (_, senas_historial) = info_set.rsplit_once(',')?;
+    let (senas, historial) = senas_historial.split_once('M')?;
+    // Las señas son una 'S' seguida de la de cada jugador que ya ha hecho la suya.
+    if senas.starts_with('S') && senas.len() <= manos {
+        return Some(Phase::Senas);
+    }
+    let juego = |jugadas: &str| {
+        if jugadas.contains('1') {
+            Phase::Juego
+        } else {
+            Phase::Punto
+        }
+    };
+    let mut fase = Some(if game_config.max_mus_rounds == 0 {
+        Phase::Grande
+    } else {
+        Phase::Mus
+    });
+    // Último lance terminado mientras se esperan los indicadores del siguiente.
+    let mut previa = Phase::Mus;
+    let (mut votos, mut descartes, mut rondas) = (0, 0, 0);
+    let mut envites: Option<EstadoLance<DosJugadores>> = None;
+    for evento in eventos_historial(historial)? {
+        fase = match (fase, evento) {
+            (Some(Phase::Mus), Evento::Accion(Accion::Mus)) => {
+                votos += 1;
+                if votos < manos {
+                    Some(Phase::Mus)
+                } else {
+                    votos = 0;
+                    rondas += 1;
+                    Some(Phase::Descartes)
+                }
+            }
+            (Some(Phase::Mus), Evento::Accion(Accion::NoMus)) => Some(Phase::Grande),
+            (Some(Phase::Descartes), Evento::Descarte) => {
+                descartes += 1;
+                if descartes < manos {
+                    Some(Phase::Descartes)
+                } else if rondas < game_config.max_mus_rounds {
+                    descartes = 0;
+                    Some(Phase::Mus)
+                } else {
+                    Some(Phase::Grande)
+                }
+            }
+            (Some(fase), Evento::Accion(accion)) => {
+                let lance = fase.lance()?;
+                // En el historial hay una acción por pareja: los envites son los de dos jugadores.
+                let estado = envites.get_or_insert_with(|| {
+                    EstadoLance::<DosJugadores>::con_jugadores(
+                        &lance,
+                        &[0, 1],
+                        [0, 0],
+                        0,
+                        game_config.max_tantos,
+                    )
+                });
+                match estado.actuar(accion).ok()? {
+                    Some(_) => Some(fase),
+                    None => {
+                        envites = None;
+                        previa = fase;
+                        (fase == Phase::Grande).then_some(Phase::Chica)
+                    }
+                }
+            }
+            (None, Evento::Jugadas(jugadas)) => match previa {
+                Phase::Chica if jugadas.len() == manos => Some(Phase::Pares),
+                Phase::Chica if jugadas.len() == 2 * manos => Some(juego(&jugadas[manos..])),
+                Phase::Pares if jugadas.len() == manos => Some(juego(jugadas)),
+                _ => return None,
+            },
+            _ => return None,
+        };
+    }
+    fase
+}
+
+/// Lo que se anota en el historial de una partida de mus.
+enum Evento<'a> {
+    Accion(Accion),
+    /// Descarte de un jugador. El historial solo dice cuántas cartas descarta.
+    Descarte,
+    /// Indicadores de qué manos tienen pares o juego.
+    Jugadas(&'a str),
+}
+
+/// Separa el historial en eventos. Las marcas de los descartes resueltos se omiten, y la acción
+/// pendiente del compañero, marcada con '*', no cuenta hasta que la pareja haya actuado.
+fn eventos_historial(historial: &str) -> Option<Vec<Evento<'_>>> {
+    let fin_digitos = |inicio: usize| {
+        historial[inicio..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(historial.len(), |n| inicio + n)
+    };
+    let mut eventos = Vec::new();
+    let mut i = 0;
+    while i < historial.len() {
+        let (evento, fin) = match historial.as_bytes()[i] {
+            b'p' => (Some(Evento::Accion(Accion::Paso)), i + 1),
+            b'q' => (Some(Evento::Accion(Accion::Quiero)), i + 1),
+            b'o' => (Some(Evento::Accion(Accion::Ordago)), i + 1),
+            b'm' => (Some(Evento::Accion(Accion::Mus)), i + 1),
+            b'n' => (Some(Evento::Accion(Accion::NoMus)), i + 1),
+            b'e' => {
+                let fin = fin_digitos(i + 1);
+                let envite = historial[i + 1..fin].parse().ok()?;
+                (Some(Evento::Accion(Accion::Envido(envite))), fin)
+            }
+            b'd' => (Some(Evento::Descarte), fin_digitos(i + 1)),
+            b'0'..=b'9' => {
+                let fin = fin_digitos(i);
+                (Some(Evento::Jugadas(&historial[i..fin])), fin)
+            }
+            b'C' => (None, i + 1),
+            b'*' => {
+                eventos.pop();
+                (None, i + 1)
+            }
+            _ => return None,
+        };
+        eventos.extend(evento);
+        i = fin;
+    }
+    Some(eventos)
+}
+
+/// Jugada abstracta en `lance` de las manos del jugador de un conjunto de información. Las
+/// manos son los primeros campos tras el marcador, y en los lances sueltos tras la
+/// configuración de manos. En las partidas abstractas ya vienen abstraídas.
+fn hand_class(info_set: &str, game_type: GameType, lance: Lance, abstract_game: bool) -> String {
+    let (first, count) = match game_type {
+        GameType::LanceGame(_) => (2, 1),
+        GameType::LanceGameTwoHands(_) => (2, 2),
+        GameType::MusGame | GameType::MusGameTwoPlayers => (1, 1),
+        GameType::MusGameTwoHands => (1, 2),
+    };
+    info_set
+        .split(',')
+        .skip(first)
+        .take(count)
+        .map(|mano| {
+            let class = match mano.parse::<Mano>() {
+                Ok(mano) if !abstract_game => {
+                    ManosNormalizadas::mano_to_abstract_string(&mano, &lance)
+                }
+                _ => mano.to_string(),
+            };
+            if class.is_empty() {
+                "-".to_string()
+            } else {
+                class
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+impl fmt::Display for StrategyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Tipo de partida: {:?}", self.game_type)?;
+        writeln!(f, "Conjuntos de información: {}", self.info_sets)?;
+        writeln!(f, "Nodos casi uniformes: {:.1}%", 100. * self.near_uniform)?;
+        writeln!(f, "Entropía normalizada media: {:.3}", self.mean_entropy)?;
+        for (i, count) in self.entropy_histogram.iter().enumerate() {
+            let n = self.entropy_histogram.len() as f64;
+            writeln!(
+                f,
+                "  {:.1}-{:.1}: {count}",
+                i as f64 / n,
+                (i + 1) as f64 / n
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Partidas simuladas: {}", self.deals)?;
+        writeln!(
+            f,
+            "Decisiones sin conjunto de información: {}",
+            self.missing_info_sets
+        )?;
+        writeln!(
+            f,
+            "Conjuntos de información no visitados: {}",
+            self.unvisited_info_sets
+        )?;
+        if self.unclassified_info_sets > 0 {
+            writeln!(
+                f,
+                "Conjuntos de información sin fase reconocida: {}",
+                self.unclassified_info_sets
+            )?;
+        }
+        for (phase, report) in &self.phases {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{phase}: {} conjuntos de información ({} visitados), {} decisiones",
+                report.total_info_sets, report.info_sets, report.decisions
+            )?;
+            let actions: Vec<String> = report
+                .actions
+                .iter()
+                .take(5)
+                .map(|(action, p)| format!("{action} {:.1}%", 100. * p))
+                .collect();
+            writeln!(f, "  Acciones: {}", actions.join(", "))?;
+            for (class, aggression) in &report.aggression {
+                writeln!(
+                    f,
+                    "  {class:<10} envido {:>5.1}%  órdago {:>5.1}%  ({} decisiones)",
+                    100. * aggression.envido,
+                    100. * aggression.ordago,
+                    aggression.decisions
+                )?;
+            }
+        }
+        if let Some(lbr) = &self.lbr {
+            writeln!(f)?;
+            writeln!(f, "LBR ({} repartos por posición): {lbr}", lbr.deals)?;
+            let values: Vec<String> = lbr.values.iter().map(|v| format!("{v:.4}")).collect();
+            writeln!(f, "  Utilidad por posición: {}", values.join(", "))?;
+        }
+        writeln!(f)?;
+        if self.utility_table.is_empty() {
+            return writeln!(f, "Tabla de utilidades no disponible.");
+        }
+        // La tabla completa tiene tantas filas y columnas como tantos el chico: se muestra
+        // cada cinco tantos.
+        writeln!(
+            f,
+            "Utilidad esperada de la pareja mano (filas) por marcador:"
+        )?;
+        let step = 5;
+        write!(f, "     ")?;
+        for tantos2 in (0..self.utility_table.len()).step_by(step) {
+            write!(f, "{tantos2:>7}")?;
+        }
+        writeln!(f)?;
+        for (tantos1, row) in self.utility_table.iter().enumerate().step_by(step) {
+            write!(f, "{tantos1:>5}")?;
+            for utility in row.iter().step_by(step) {
+                write!(f, "{utility:>7.2}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        Cfr, CfrMethod,
+        mus::Sena,
+        solver::{Signals, StrategyConfig, TrainerConfig},
+    };
+
+    fn strategy(game_type: GameType, nodes: &[(&str, Vec<f64>)]) -> Strategy {
+        Strategy {
+            strategy_config: StrategyConfig {
+                trainer_config: TrainerConfig {
+                    method: CfrMethod::ExternalSampling,
+                    ..Default::default()
+                },
+                game_config: GameConfig::new(game_type),
+                stop_reasons: Vec::new(),
+            },
+            nodes: nodes
+                .iter()
+                .map(|(info_set, probabilities)| (info_set.to_string(), probabilities.clone()))
+                .collect(),
+            exploitability: None,
+            utility_table: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn report_nodes() {
+        let strategy = strategy(
+            GameType::LanceGame(Lance::Pares),
+            &[
+                ("3:5,2-2,RR11,,M", vec![0.5, 0.5]),
+                ("3:5,2-2,RR11,,Mp", vec![1., 0.]),
+                ("3:5,2-2,RR11,,Mpp", vec![1.]),
+            ],
+        );
+        let report = StrategyReport::new(&strategy, 50, &mut StdRng::seed_from_u64(1));
+        assert_eq!(report.info_sets, 3);
+        assert_eq!(report.near_uniform, 0.5);
+        assert_eq!(report.entropy_histogram[0], 1);
+        assert_eq!(report.entropy_histogram[9], 1);
+        assert_eq!(report.mean_entropy, 0.5);
+        // Solo se juegan pares.
+        assert_eq!(
+            report.phases.keys().collect::<Vec<_>>(),
+            vec![&Phase::Pares]
+        );
+        let pares = &report.phases[&Phase::Pares];
+        let total: f64 = pares.actions.iter().map(|(_, p)| p).sum();
+        assert!((total - 1.).abs() < 1e-9);
+        assert!(pares.aggression.keys().all(|class| !class.is_empty()));
+        assert!(report.missing_info_sets > 0);
+    }
+
+    #[test]
+    fn report_phases() {
+        let strategy = strategy(GameType::MusGameTwoPlayers, &[]);
+        let report = StrategyReport::new(&strategy, 20, &mut StdRng::seed_from_u64(2));
+        assert!(report.phases.contains_key(&Phase::Mus));
+        assert!(report.phases.contains_key(&Phase::Grande));
+        assert_eq!(
+            report.missing_info_sets,
+            report.phases.values().map(|p| p.decisions).sum::<usize>()
+        );
+        assert!(
+            report
+                .to_string()
+                .contains("Tabla de utilidades no disponible.")
+        );
+    }
+
+    #[test]
+    fn report_senas() {
+        let signals = Signals {
+            vocabulary: Sena::SENAS.to_vec(),
+            opponent_probability: 0.5,
+        };
+        let game_config = GameConfig {
+            signals: Some(signals.clone()),
+            ..GameConfig::new(GameType::MusGame)
+        };
+        let mut game =
+            MusGame::new([0, 0], false, game_config.max_mus_rounds).with_signals(Arc::new(signals));
+        let mut cfr = Cfr::new().with_seed(1);
+        cfr.train(&mut game, CfrMethod::ExternalSampling, 20, |_, _| {});
+        let strategy = Strategy::new(&cfr, &TrainerConfig::default(), &game_config);
+
+        let report = StrategyReport::new(&strategy, 50, &mut StdRng::seed_from_u64(3));
+        let senas = &report.phases[&Phase::Senas];
+        assert!(senas.info_sets > 0);
+        assert!(
+            senas
+                .actions
+                .iter()
+                .any(|(action, _)| action.starts_with('s'))
+        );
+        // Las decisiones de las señas no se cuentan en grande, aunque empiecen por el paso.
+        let grande = &report.phases[&Phase::Grande];
+        assert!(
+            grande
+                .actions
+                .iter()
+                .all(|(action, _)| !action.starts_with('s'))
+        );
+        assert_eq!(report.unclassified_info_sets, 0);
+        assert_eq!(
+            report
+                .phases
+                .values()
+                .map(|phase| phase.total_info_sets)
+                .sum::<usize>(),
+            report.info_sets
+        );
+        assert!(
+            report
+                .phases
+                .values()
+                .all(|phase| phase.info_sets <= phase.total_info_sets)
+        );
+    }
+
+    /// Juega partidas al azar y comprueba que la fase reconstruida del conjunto de información de
+    /// cada decisión es la del estado de la partida.
+    fn assert_info_set_phases<G>(
+        game: &G,
+        game_config: &GameConfig,
+        phase: impl Fn(&G, &[Accion]) -> Phase,
+    ) where
+        G: Game<Action = Accion> + Clone,
+    {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..200 {
+            let mut game = game.clone();
+            game.reset();
+            game.new_random(&mut rng);
+            loop {
+                match game.current_player() {
+                    NodeType::Terminal => break,
+                    NodeType::Chance => game.new_random(&mut rng),
+                    NodeType::Player(player) => {
+                        let actions = game.actions();
+                        let info_set = game.info_set_str(player);
+                        assert_eq!(
+                            info_set_phase(&info_set, game_config),
+                            Some(phase(&game, &actions)),
+                            "{info_set}"
+                        );
+                        game.act(actions[rng.gen_range(0..actions.len())]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn info_set_phases() {
+        let signals = Signals {
+            vocabulary: Sena::SENAS.to_vec(),
+            opponent_probability: 0.5,
+        };
+        for max_mus_rounds in [0, 2] {
+            let game_config = GameConfig {
+                max_mus_rounds,
+                signals: Some(signals.clone()),
+                ..GameConfig::new(GameType::MusGame)
+            };
+            let game =
+                MusGame::new([0, 0], false, max_mus_rounds).with_signals(Arc::new(signals.clone()));
+            assert_info_set_phases(&game, &game_config, |game, actions| {
+                if game.en_senas() {
+                    Phase::Senas
+                } else {
+                    Phase::new(
+                        actions,
+                        lance_en_juego(game.mus_game().and_then(|partida| partida.fase())),
+                    )
+                }
+            });
+
+            let game_config = GameConfig {
+                max_mus_rounds,
+                ..GameConfig::new(GameType::MusGameTwoHands)
+            };
+            let game = MusGameTwoHands::new([0, 0], false, max_mus_rounds);
+            assert_info_set_phases(&game, &game_config, |game, actions| {
+                Phase::new(
+                    actions,
+                    lance_en_juego(game.mus_game().and_then(|partida| partida.fase())),
+                )
+            });
+
+            let game_config = GameConfig {
+                max_mus_rounds,
+                ..GameConfig::new(GameType::MusGameTwoPlayers)
+            };
+            let game = MusGameTwoPlayers::new([0, 0], false, max_mus_rounds);
+            assert_info_set_phases(&game, &game_config, |game, actions| {
+                Phase::new(
+                    actions,
+                    lance_en_juego(game.mus_game().and_then(|partida| partida.fase())),
+                )
+            });
+        }
+        let game_config = GameConfig::new(GameType::LanceGame(Lance::Juego));
+        assert_eq!(
+            info_set_phase("3:5,2-2,RR11,,Mp", &game_config),
+            Some(Phase::Juego)
+        );
+        let game_config = GameConfig::new(GameType::MusGame);
+        assert_eq!(info_set_phase("0:0,RR11,Mx", &game_config), None);
+    }
+
+    #[test]
+    fn hand_classes() {
+        assert_eq!(
+            hand_class(
+                "0:0,RR51,C741,,M",
+                GameType::MusGameTwoHands,
+                Lance::Grande,
+                false
+            ),
+            "RR5+C"
+        );
+        assert_eq!(
+            hand_class(
+                "0:0,2-2,R751,,M",
+                GameType::LanceGame(Lance::Pares),
+                Lance::Pares,
+                false
+            ),
+            "-"
+        );
+        assert_eq!(parse_tantos("12:30,RR11,M"), Some([12, 30]));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::mus::{Accion, Mano, Sena};
+
+/// Señas entre compañeros en [`MusGame`](super::MusGame). Al empezar la fase de envites cada
+/// jugador, empezando por el mano, puede hacer a su compañero una de las señas de `vocabulary`
+/// que correspondan a su mano, o no hacer ninguna. El compañero siempre ve la seña, y los rivales
+/// con probabilidad `opponent_probability`. Los rivales no distinguen una seña que no han visto
+/// de no haber hecho seña.
+///
+/// Sirve para medir cuánto valen las señas frente a jugar sin información de la pareja, como en
+/// `MusGame` sin señas, o con información completa, como en `MusGameTwoHands`. Como las
+/// abstracciones, forma parte de la configuración de la partida porque cambia el árbol de juego.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub struct Signals {
+    pub vocabulary: Vec<Sena>,
+    /// Probabilidad de que los rivales vean cada seña, entre 0 y 1.
+    pub opponent_probability: f64,
+}
+
+impl Signals {
+    /// Señas que puede hacer un jugador con la mano `mano`. La primera acción, `Paso`, es no
+    /// hacer seña.
+    pub fn actions(&self, mano: &Mano) -> Vec<Accion> {
+        let mut acciones = vec![Accion::Paso];
+        for sena in &self.vocabulary {
+            let accion = Accion::Sena(*sena);
+            if sena.corresponde(mano) && !acciones.contains(&accion) {
+                acciones.push(accion);
+            }
+        }
+        acciones
+    }
+
+    /// Indica si que los rivales vean las señas es un suceso de azar. Con probabilidad 0 o 1 se
+    /// sabe de antemano y la partida no tiene nodos de azar para las señas.
+    pub(crate) fn chance(&self) -> bool {
+        self.opponent_probability > 0. && self.opponent_probability < 1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals() {
+        let signals = Signals {
+            vocabulary: vec![Sena::Duples, Sena::Treintayuna, Sena::Duples],
+            opponent_probability: 0.5,
+        };
+        assert_eq!(
+            signals.actions(&"RRR1".parse().unwrap()),
+            vec![Accion::Paso, Accion::Sena(Sena::Treintayuna)]
+        );
+        assert_eq!(
+            signals.actions(&"RR11".parse().unwrap()),
+            vec![Accion::Paso, Accion::Sena(Sena::Duples)]
+        );
+        assert!(signals.chance());
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::GameType;
+
 #[derive(Debug, Error)]
 pub enum SolverError {
     #[error("Invalid strategy path: {1}")]
@@ -16,4 +18,31 @@ pub enum SolverError {
 
     #[error("Cannot parse strategy file.")]
     UnsupportedFileFormat(String),
+
+    #[error("Invalid checkpoint path: {1}")]
+    InvalidCheckpointPath(#[source] std::io::Error, String),
+
+    #[error("Invalid bet abstraction path: {1}")]
+    InvalidBetAbstractionPath(#[source] std::io::Error, String),
+
+    #[error("Cannot parse bet abstraction file.")]
+    ParseBetAbstractionError(#[source] serde_json::Error),
+
+    #[error("Invalid action tree path: {1}")]
+    InvalidActionTreePath(#[source] std::io::Error, String),
+
+    #[error("Invalid action tree after [{0}]: {1}")]
+    InvalidActionTree(String, String),
+
+    #[error("Cannot parse checkpoint file.")]
+    ParseCheckpointError(#[source] rkyv::rancor::Error),
+
+    #[error("Info set not found in strategy: {0}")]
+    MissingInfoSet(String),
+
+    #[error("No player has to act after [{0}]")]
+    NoPlayerToAct(String),
+
+    #[error("Unsupported game type: {0:?}")]
+    UnsupportedGameType(GameType),
 }
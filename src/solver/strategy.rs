@@ -1,16 +1,24 @@
 use std::{
-    cmp::Ordering,
-    collections::HashMap,
+    cmp::{Ordering, Reverse},
+    collections::BTreeMap,
     fs::{self},
     path::Path,
+    sync::Arc,
 };
 
 use walkdir::WalkDir;
 
 use crate::{
-    Cfr, Game, NodeType,
-    mus::{Accion, Lance, Mano},
-    solver::{MusGame, MusGameTwoPlayers},
+    Cfr, Game, NodeType, StopReason,
+    mus::{
+        Accion, CuatroJugadores, DistribucionCartaIter, FaseEnvites, FasePartida, Lance, Mano,
+        Variante,
+    },
+    solver::{
+        BetAbstraction, DiscardAbstraction, Fallback, LanceGame, LanceGameTwoHands,
+        ManosNormalizadas, MusGame, MusGameTwoHands, MusGameTwoPlayers, Signals, StrategyLookup,
+        UtilityTarget,
+    },
 };
 
 use super::{SolverError, TrainerConfig};
@@ -48,6 +56,91 @@ pub struct GameConfig {
     /// Número máximo de rondas de mus. Con cero rondas se juega a primeras dadas. Acota el
     /// árbol de juego, que sin este límite es infinito. Solo aplica a las partidas completas.
     pub max_mus_rounds: u8,
+    /// Tantos con los que se gana el chico. Las estrategias guardadas antes de que fuera
+    /// configurable se jugaban a 40.
+    #[serde(default = "default_max_tantos")]
+    pub max_tantos: u8,
+    /// Envites de la fase de envites, por reglas o con un árbol de envites cargado de fichero.
+    /// Las estrategias guardadas antes de que fuera configurable usan la abstracción por
+    /// defecto.
+    #[serde(default)]
+    pub bet_abstraction: BetAbstraction,
+    /// Descartes de la fase de descartes. Por defecto se descarta todo salvo los reyes, como
+    /// en las estrategias guardadas antes de que fuera configurable.
+    #[serde(default)]
+    pub discard_abstraction: DiscardAbstraction,
+    /// Qué se maximiza en las partidas completas. Por defecto, la diferencia de tantos.
+    #[serde(default)]
+    pub utility_target: UtilityTarget,
+    /// Variante de reglas con la que se reparte. Las estrategias guardadas antes de que fuera
+    /// configurable se jugaban a ocho reyes.
+    #[serde(default)]
+    pub variante: Variante,
+    /// Señas entre compañeros. Solo se usan en [`GameType::MusGame`].
+    #[serde(default)]
+    pub signals: Option<Signals>,
+}
+
+impl GameConfig {
+    /// Partida de tipo `game_type` con las opciones por defecto de la línea de comandos: sin
+    /// abstracción de lances, una ronda de mus, chico a 40 tantos, a ocho reyes y sin señas.
+    pub fn new(game_type: GameType) -> Self {
+        Self {
+            game_type,
+            abstract_game: false,
+            max_mus_rounds: 1,
+            max_tantos: default_max_tantos(),
+            bet_abstraction: Default::default(),
+            discard_abstraction: Default::default(),
+            utility_target: Default::default(),
+            variante: Default::default(),
+            signals: None,
+        }
+    }
+}
+
+/// Historial de acciones para los mensajes de error.
+fn history_to_string(history: &[Accion]) -> String {
+    history.iter().map(|accion| accion.to_string()).collect()
+}
+
+/// Resultado de buscar una situación de la partida en la estrategia.
+struct Lookup {
+    actions: Vec<Accion>,
+    info_set: String,
+    player: usize,
+    /// Lance por el que se agrupan las manos en [`Fallback::Abstract`].
+    lance: Lance,
+    /// `None` si la estrategia no tiene el conjunto de información.
+    probabilities: Option<Vec<f64>>,
+}
+
+/// Lance que se está jugando o, antes de los envites, grande.
+pub(crate) fn lance_en_juego(fase: Option<FasePartida>) -> Lance {
+    match fase {
+        Some(FasePartida::Envites(lance)) => lance,
+        _ => Lance::Grande,
+    }
+}
+
+/// Número de cartas que tienen en común dos manos.
+fn cartas_comunes(mano1: &Mano, mano2: &Mano) -> usize {
+    let mut restantes = mano2.cartas().to_vec();
+    mano1
+        .cartas()
+        .iter()
+        .filter(|carta| {
+            restantes
+                .iter()
+                .position(|c| c == *carta)
+                .map(|idx| restantes.swap_remove(idx))
+                .is_some()
+        })
+        .count()
+}
+
+fn default_max_tantos() -> u8 {
+    FaseEnvites::<CuatroJugadores>::MAX_TANTOS
 }
 
 #[derive(
@@ -62,6 +155,10 @@ pub struct GameConfig {
 pub struct StrategyConfig {
     pub trainer_config: TrainerConfig,
     pub game_config: GameConfig,
+    /// Motivo por el que terminó el entrenamiento de cada marcador, en el orden en que se
+    /// entrenaron. Vacío en las estrategias guardadas antes de que se exportara.
+    #[serde(default)]
+    pub stop_reasons: Vec<([u8; 2], StopReason)>,
 }
 
 #[derive(
@@ -75,41 +172,227 @@ pub struct StrategyConfig {
 )]
 pub struct Strategy {
     pub strategy_config: StrategyConfig,
-    pub nodes: HashMap<String, Vec<f64>>,
+    /// Estrategia media de cada conjunto de información. Se ordena por el conjunto de información
+    /// para que la misma estrategia se serialice siempre igual.
+    pub nodes: BTreeMap<String, Vec<f64>>,
+    /// Explotabilidad estimada al terminar el entrenamiento, si se ha evaluado.
+    pub exploitability: Option<f64>,
+    /// Utilidad esperada de la pareja mano al empezar una mano con cada marcador, con tantas
+    /// filas y columnas como tantos tiene el chico. Vacía en las estrategias guardadas antes de
+    /// que se exportara.
+    #[serde(default)]
+    pub utility_table: Vec<Vec<f64>>,
+}
+
+impl Cfr {
+    /// Crea una instancia de CFR que parte de la estrategia media de `strategy` en los conjuntos
+    /// de información que se conservan. Ver `Cfr::with_warm_start`.
+    pub fn from_strategy(strategy: &Strategy, weight: f64) -> Self {
+        Self::new().with_warm_start(strategy.nodes.clone(), weight)
+    }
 }
 
 impl Strategy {
     pub fn new(cfr: &Cfr, trainer_config: &TrainerConfig, game_config: &GameConfig) -> Self {
         let nodes = cfr
             .nodes()
-            .iter()
             .map(|(info_set, node)| {
                 let avg_strategy: Vec<f64> = node
                     .get_average_strategy()
                     .into_iter()
                     .map(|v| (v * 100.).round() / 100.)
                     .collect();
-                (info_set.to_owned(), avg_strategy)
+                (info_set, avg_strategy)
             })
             .collect();
         Self {
             strategy_config: StrategyConfig {
                 trainer_config: trainer_config.clone(),
                 game_config: game_config.clone(),
+                stop_reasons: Vec::new(),
             },
             nodes,
+            exploitability: None,
+            utility_table: Vec::new(),
         }
     }
 
+    pub fn with_stop_reasons(mut self, stop_reasons: Vec<([u8; 2], StopReason)>) -> Self {
+        self.strategy_config.stop_reasons = stop_reasons;
+        self
+    }
+
+    pub fn with_exploitability(self, exploitability: Option<f64>) -> Self {
+        Self {
+            exploitability,
+            ..self
+        }
+    }
+
+    pub fn with_utility_table(self, utility_table: Vec<Vec<f64>>) -> Self {
+        Self {
+            utility_table,
+            ..self
+        }
+    }
+
+    /// Acciones y probabilidades de la estrategia para el jugador al que le toca tras `history`
+    /// en una partida con las manos `manos` y el marcador `tantos`. En los lances sueltos,
+    /// `history` son las acciones de cada jugador desde el comienzo del lance, como las registra
+    /// la arena. En las partidas completas, las acciones de la partida desde el reparto.
+    ///
+    /// Devuelve un error si no le toca a ningún jugador o si la estrategia no tiene el conjunto
+    /// de información.
     pub fn actions(
         &self,
         manos: &[Mano],
         tantos: [u8; 2],
         history: &[Accion],
-    ) -> Option<(Vec<Accion>, Vec<f64>)> {
-        match self.strategy_config.game_config.game_type {
-            GameType::LanceGame(_) => todo!(),
-            GameType::LanceGameTwoHands(_) => todo!(),
+    ) -> Result<(Vec<Accion>, Vec<f64>), SolverError> {
+        let lookup = self.lookup(manos, tantos, history)?;
+        let probabilities = lookup
+            .probabilities
+            .ok_or(SolverError::MissingInfoSet(lookup.info_set))?;
+        Ok((lookup.actions, probabilities))
+    }
+
+    /// Como [`Strategy::actions`], pero si la estrategia no tiene el conjunto de información
+    /// prueba las alternativas de `fallbacks` en orden. El resultado indica qué alternativa ha
+    /// dado las probabilidades. Las alternativas que buscan otro conjunto de información solo lo
+    /// aceptan si en él se elige entre las mismas acciones.
+    ///
+    /// Devuelve un error si no le toca a ningún jugador o si ninguna alternativa tiene respuesta.
+    pub fn actions_with_fallback(
+        &self,
+        manos: &[Mano],
+        tantos: [u8; 2],
+        history: &[Accion],
+        fallbacks: &[Fallback],
+    ) -> Result<StrategyLookup, SolverError> {
+        let lookup = self.lookup(manos, tantos, history)?;
+        self.with_fallback(lookup, fallbacks, Some((manos, tantos, history)))
+    }
+
+    /// Como [`Strategy::actions_with_fallback`], pero para el jugador al que le toca en `game`,
+    /// una partida en curso del tipo de la estrategia, sin reconstruirla desde el reparto. Las
+    /// alternativas que buscan otra situación de la partida, [`Fallback::Abstract`] y
+    /// [`Fallback::NeighbouringTantos`], necesitan reconstruirla, así que se saltan.
+    pub fn game_actions_with_fallback<G: Game<Action = Accion>>(
+        &self,
+        game: &G,
+        fallbacks: &[Fallback],
+    ) -> Result<StrategyLookup, SolverError> {
+        // El lance solo lo usa `Fallback::Abstract`, que aquí no se aplica.
+        let lookup = self.lookup_state(game, |_| Lance::Grande)?;
+        self.with_fallback(lookup, fallbacks, None)
+    }
+
+    /// Resultado de `lookup` o, si la estrategia no tiene el conjunto de información, de la
+    /// primera alternativa de `fallbacks` con respuesta. Las alternativas que buscan otra
+    /// situación de la partida la reconstruyen con `situacion`: las manos, el marcador y el
+    /// historial. Sin ella se saltan.
+    fn with_fallback(
+        &self,
+        lookup: Lookup,
+        fallbacks: &[Fallback],
+        situacion: Option<(&[Mano], [u8; 2], &[Accion])>,
+    ) -> Result<StrategyLookup, SolverError> {
+        if let Some(probabilities) = lookup.probabilities {
+            return Ok(StrategyLookup {
+                actions: lookup.actions,
+                probabilities,
+                fallback: None,
+            });
+        }
+        for fallback in fallbacks {
+            let probabilities = match (fallback, situacion) {
+                (Fallback::Abstract, Some((manos, tantos, history))) => {
+                    self.abstract_fallback(manos, tantos, history, &lookup)
+                }
+                (Fallback::NeighbouringTantos(distance), Some((manos, tantos, history))) => {
+                    self.tantos_fallback(manos, tantos, history, &lookup, *distance)
+                }
+                (Fallback::Abstract | Fallback::NeighbouringTantos(_), None) => None,
+                (Fallback::Default(policy), _) => Some(policy.probabilities(&lookup.actions)),
+            };
+            if let Some(probabilities) = probabilities {
+                return Ok(StrategyLookup {
+                    actions: lookup.actions,
+                    probabilities,
+                    fallback: Some(*fallback),
+                });
+            }
+        }
+        Err(SolverError::MissingInfoSet(lookup.info_set))
+    }
+
+    /// Abstracción de envites con la que se ha entrenado la estrategia, lista para construir
+    /// partidas.
+    pub fn bet_abstraction(&self) -> Arc<BetAbstraction> {
+        Arc::new(self.strategy_config.game_config.bet_abstraction.clone())
+    }
+
+    /// Abstracción de descartes con la que se ha entrenado la estrategia.
+    pub fn discard_abstraction(&self) -> Arc<DiscardAbstraction> {
+        Arc::new(self.strategy_config.game_config.discard_abstraction.clone())
+    }
+
+    fn lookup(
+        &self,
+        manos: &[Mano],
+        tantos: [u8; 2],
+        history: &[Accion],
+    ) -> Result<Lookup, SolverError> {
+        let game_config = &self.strategy_config.game_config;
+        match game_config.game_type {
+            GameType::LanceGame(lance) => {
+                // El resto de partidas normalizan las manos al repartirlas.
+                let manos: [Mano; 4] =
+                    core::array::from_fn(|i| game_config.variante.normalizar(&manos[i]));
+                let lance_game = FaseEnvites::partida_lance_con_max_tantos(
+                    lance,
+                    manos,
+                    tantos,
+                    game_config.max_tantos,
+                )
+                .and_then(|partida| {
+                    LanceGame::from_partida_mus(&partida, game_config.abstract_game)
+                })
+                .ok_or_else(|| SolverError::NoPlayerToAct(history_to_string(history)))?;
+                let mut lance_game = lance_game
+                    .with_bet_abstraction(self.bet_abstraction())
+                    .with_variante(game_config.variante);
+                self.lookup_in_game(&mut lance_game, history, |_| lance)
+            }
+            GameType::LanceGameTwoHands(lance) => {
+                let manos = [
+                    manos[0].clone(),
+                    manos[1].clone(),
+                    manos[2].clone(),
+                    manos[3].clone(),
+                ];
+                let mut lance_game =
+                    LanceGameTwoHands::new(lance, tantos, game_config.abstract_game)
+                        .with_bet_abstraction(self.bet_abstraction())
+                        .with_max_tantos(game_config.max_tantos)
+                        .with_variante(game_config.variante)
+                        .with_hands(manos);
+                // El segundo jugador de la pareja repite la acción que ya ha elegido su
+                // compañero.
+                for action in history {
+                    lance_game.act_jugador(*action);
+                }
+                if let Some(accion_pareja) = lance_game.accion_pareja() {
+                    return Ok(Lookup {
+                        actions: vec![accion_pareja],
+                        info_set: lance_game.history_str(),
+                        player: 0,
+                        lance,
+                        probabilities: Some(vec![1.]),
+                    });
+                }
+                self.lookup_in_game(&mut lance_game, &[], |_| lance)
+            }
             GameType::MusGame => {
                 let manos = [
                     manos[0].clone(),
@@ -119,42 +402,176 @@ impl Strategy {
                 ];
                 let mut mus_game = MusGame::new(
                     tantos,
-                    self.strategy_config.game_config.abstract_game,
-                    self.strategy_config.game_config.max_mus_rounds,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
                 )
-                .with_hands(manos.clone());
-                self.actions_for_game(&mut mus_game, history)
+                .with_bet_abstraction(self.bet_abstraction())
+                .with_discard_abstraction(self.discard_abstraction())
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante);
+                if let Some(signals) = &game_config.signals {
+                    mus_game = mus_game.with_signals(Arc::new(signals.clone()));
+                }
+                let mut mus_game = mus_game.with_hands(manos.clone());
+                self.lookup_in_game(&mut mus_game, history, |game| {
+                    lance_en_juego(game.mus_game().and_then(|partida| partida.fase()))
+                })
+            }
+            GameType::MusGameTwoHands => {
+                let manos = [
+                    manos[0].clone(),
+                    manos[1].clone(),
+                    manos[2].clone(),
+                    manos[3].clone(),
+                ];
+                let mut mus_game = MusGameTwoHands::new(
+                    tantos,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
+                )
+                .with_bet_abstraction(self.bet_abstraction())
+                .with_discard_abstraction(self.discard_abstraction())
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante)
+                .with_hands(manos);
+                self.lookup_in_game(&mut mus_game, history, |game| {
+                    lance_en_juego(game.mus_game().and_then(|partida| partida.fase()))
+                })
             }
-            GameType::MusGameTwoHands => todo!(),
             GameType::MusGameTwoPlayers => {
                 let manos = [manos[0].clone(), manos[1].clone()];
                 let mut mus_game = MusGameTwoPlayers::new(
                     tantos,
-                    self.strategy_config.game_config.abstract_game,
-                    self.strategy_config.game_config.max_mus_rounds,
+                    game_config.abstract_game,
+                    game_config.max_mus_rounds,
                 )
+                .with_bet_abstraction(self.bet_abstraction())
+                .with_discard_abstraction(self.discard_abstraction())
+                .with_max_tantos(game_config.max_tantos)
+                .with_variante(game_config.variante)
                 .with_hands(manos.clone());
-                self.actions_for_game(&mut mus_game, history)
+                self.lookup_in_game(&mut mus_game, history, |game| {
+                    lance_en_juego(game.mus_game().and_then(|partida| partida.fase()))
+                })
             }
         }
     }
 
-    fn actions_for_game(
+    fn lookup_in_game<G: Game<Action = Accion>>(
         &self,
-        game: &mut impl Game<Action = Accion>,
+        game: &mut G,
         history: &[Accion],
-    ) -> Option<(Vec<Accion>, Vec<f64>)> {
+        lance: impl FnOnce(&G) -> Lance,
+    ) -> Result<Lookup, SolverError> {
         for action in history {
             game.act(*action);
         }
-        let actions = game.actions();
-        let turno = match game.current_player() {
+        self.lookup_state(game, lance)
+    }
+
+    /// Busca en la estrategia la situación actual de `game`.
+    fn lookup_state<G: Game<Action = Accion>>(
+        &self,
+        game: &G,
+        lance: impl FnOnce(&G) -> Lance,
+    ) -> Result<Lookup, SolverError> {
+        let player = match game.current_player() {
             NodeType::Player(t) => t,
-            NodeType::Terminal | NodeType::Chance => return None,
+            NodeType::Terminal | NodeType::Chance => {
+                return Err(SolverError::NoPlayerToAct(game.history_str()));
+            }
         };
-        let info_set = game.info_set_str(turno as usize);
-        let strategy = self.nodes.get(&info_set).cloned();
-        Some(actions).zip(strategy)
+        let info_set = game.info_set_str(player);
+        Ok(Lookup {
+            actions: game.actions(),
+            probabilities: self.nodes.get(&info_set).cloned(),
+            info_set,
+            player,
+            lance: lance(game),
+        })
+    }
+
+    /// Probabilidades de otra situación de la partida si en ella se elige entre `actions`.
+    fn probabilities_with_actions(
+        &self,
+        manos: &[Mano],
+        tantos: [u8; 2],
+        history: &[Accion],
+        actions: &[Accion],
+    ) -> Option<Vec<f64>> {
+        self.lookup(manos, tantos, history)
+            .ok()
+            .filter(|lookup| lookup.actions == actions)
+            .and_then(|lookup| lookup.probabilities)
+    }
+
+    fn abstract_fallback(
+        &self,
+        manos: &[Mano],
+        tantos: [u8; 2],
+        history: &[Accion],
+        lookup: &Lookup,
+    ) -> Option<Vec<f64>> {
+        let game_config = &self.strategy_config.game_config;
+        let asientos = match game_config.game_type {
+            GameType::LanceGameTwoHands(_) | GameType::MusGameTwoHands => {
+                vec![lookup.player, lookup.player + 2]
+            }
+            _ => vec![lookup.player],
+        };
+        let todas: Vec<Mano> = DistribucionCartaIter::<4>::new(game_config.variante.frecuencias())
+            .map(|(cartas, _)| Mano::new(cartas))
+            .collect();
+        asientos.into_iter().find_map(|asiento| {
+            let mano = &manos[asiento];
+            let abstracta = ManosNormalizadas::mano_to_abstract_string(mano, &lookup.lance);
+            let mut candidatas: Vec<&Mano> = todas
+                .iter()
+                .filter(|candidata| {
+                    *candidata != mano
+                        && ManosNormalizadas::mano_to_abstract_string(candidata, &lookup.lance)
+                            == abstracta
+                })
+                .collect();
+            candidatas.sort_by_cached_key(|candidata| {
+                (
+                    Reverse(cartas_comunes(mano, candidata)),
+                    candidata.to_string(),
+                )
+            });
+            let mut manos = manos.to_vec();
+            candidatas.into_iter().find_map(|candidata| {
+                manos[asiento] = candidata.clone();
+                self.probabilities_with_actions(&manos, tantos, history, &lookup.actions)
+            })
+        })
+    }
+
+    fn tantos_fallback(
+        &self,
+        manos: &[Mano],
+        tantos: [u8; 2],
+        history: &[Accion],
+        lookup: &Lookup,
+        distance: u8,
+    ) -> Option<Vec<f64>> {
+        let max_tantos = self.strategy_config.game_config.max_tantos as i16;
+        let tantos = tantos.map(i16::from);
+        (1..=distance as i16).find_map(|d| {
+            (-d..=d)
+                .flat_map(|d0| (-d..=d).map(move |d1| (d0, d1)))
+                .filter(|(d0, d1)| d0.abs().max(d1.abs()) == d)
+                .map(|(d0, d1)| [tantos[0] + d0, tantos[1] + d1])
+                .filter(|vecino| vecino.iter().all(|t| (0..max_tantos).contains(t)))
+                .find_map(|vecino| {
+                    self.probabilities_with_actions(
+                        manos,
+                        vecino.map(|t| t as u8),
+                        history,
+                        &lookup.actions,
+                    )
+                })
+        })
     }
     //pub fn best_response_value(
     //    &self,
@@ -354,3 +771,171 @@ impl Strategy {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{CfrMethod, solver::DefaultPolicy};
+
+    use super::*;
+
+    fn strategy(game_type: GameType, nodes: &[(&str, Vec<f64>)]) -> Strategy {
+        Strategy {
+            strategy_config: StrategyConfig {
+                trainer_config: TrainerConfig {
+                    method: CfrMethod::ExternalSampling,
+                    ..Default::default()
+                },
+                game_config: GameConfig {
+                    max_mus_rounds: 0,
+                    ..GameConfig::new(game_type)
+                },
+                stop_reasons: Vec::new(),
+            },
+            nodes: nodes
+                .iter()
+                .map(|(info_set, probabilities)| (info_set.to_string(), probabilities.clone()))
+                .collect(),
+            exploitability: None,
+            utility_table: Vec::new(),
+        }
+    }
+
+    fn manos(manos: [&str; 4]) -> Vec<Mano> {
+        manos.iter().map(|mano| mano.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn actions_lance_game() {
+        let manos = manos(["RR11", "RC51", "R751", "C741"]);
+        let strategy = strategy(
+            GameType::LanceGame(Lance::Grande),
+            &[("0:0,2-2,RR11,,M", vec![0.6, 0.1, 0.1, 0.1, 0.1])],
+        );
+        let (actions, probabilities) = strategy.actions(&manos, [0, 0], &[]).unwrap();
+        assert_eq!(actions.len(), probabilities.len());
+        assert_eq!(probabilities[0], 0.6);
+        assert!(matches!(
+            strategy.actions(&manos, [0, 0], &[Accion::Paso]),
+            Err(SolverError::MissingInfoSet(info_set)) if info_set == "0:0,2-2,R751,,Mp*"
+        ));
+        // Sin pares no se juega el lance.
+        let sin_pares = self::manos(["R751", "RC51", "C741", "7541"]);
+        let strategy = self::strategy(GameType::LanceGame(Lance::Pares), &[]);
+        assert!(matches!(
+            strategy.actions(&sin_pares, [0, 0], &[]),
+            Err(SolverError::NoPlayerToAct(_))
+        ));
+    }
+
+    #[test]
+    fn actions_two_hands() {
+        let manos = manos(["RR11", "RC51", "R751", "C741"]);
+        let strategy = strategy(
+            GameType::LanceGameTwoHands(Lance::Grande),
+            &[("0:0,2-2,RC51,C741,Me2", vec![0.5, 0.5])],
+        );
+        // El compañero repite la acción de la pareja.
+        assert_eq!(
+            strategy
+                .actions(&manos, [0, 0], &[Accion::Envido(2)])
+                .unwrap(),
+            (vec![Accion::Envido(2)], vec![1.])
+        );
+        let (_, probabilities) = strategy
+            .actions(&manos, [0, 0], &[Accion::Envido(2), Accion::Paso])
+            .unwrap();
+        assert_eq!(probabilities, vec![0.5, 0.5]);
+
+        let strategy = self::strategy(GameType::MusGameTwoHands, &[("0:0,RR11,R751,M", vec![1.])]);
+        let result = strategy.actions(&manos, [0, 0], &[]);
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn actions_ocho_reyes() {
+        // A ocho reyes los treses son reyes y los doses ases, también en las manos leídas de un
+        // texto.
+        let manos = manos(["R321", "RC51", "R751", "C741"]);
+        let strategy = strategy(
+            GameType::LanceGame(Lance::Grande),
+            &[("0:0,2-2,RR11,,M", vec![0.6, 0.1, 0.1, 0.1, 0.1])],
+        );
+        let (_, probabilities) = strategy.actions(&manos, [0, 0], &[]).unwrap();
+        assert_eq!(probabilities[0], 0.6);
+        let strategy = self::strategy(GameType::MusGameTwoHands, &[("0:0,RR11,R751,M", vec![1.])]);
+        assert!(strategy.actions(&manos, [0, 0], &[]).is_ok());
+    }
+
+    #[test]
+    fn actions_with_fallback() {
+        let manos = manos(["RR51", "RC51", "R751", "C741"]);
+        let game_type = GameType::LanceGame(Lance::Grande);
+        let lookup = |strategy: &Strategy, fallbacks: &[Fallback]| {
+            strategy.actions_with_fallback(&manos, [0, 0], &[], fallbacks)
+        };
+
+        let strategy = self::strategy(game_type, &[("0:0,2-2,RR51,,M", vec![0.6; 5])]);
+        let result = lookup(&strategy, &Fallback::DEFAULT_CHAIN).unwrap();
+        assert_eq!(result.fallback, None);
+
+        // RR55 y RR54 comparten tres cartas con RR51 y tienen la misma jugada abstracta.
+        let strategy = self::strategy(
+            game_type,
+            &[
+                ("0:0,2-2,RR55,,M", vec![0.5; 5]),
+                ("0:0,2-2,RR54,,M", vec![0.4; 5]),
+            ],
+        );
+        let result = lookup(&strategy, &Fallback::DEFAULT_CHAIN).unwrap();
+        assert_eq!(result.fallback, Some(Fallback::Abstract));
+        assert_eq!(result.probabilities, vec![0.4; 5]);
+
+        let strategy = self::strategy(game_type, &[("1:1,2-2,RR51,,M", vec![0.3; 5])]);
+        let result = lookup(&strategy, &Fallback::DEFAULT_CHAIN).unwrap();
+        assert_eq!(result.fallback, Some(Fallback::NeighbouringTantos(2)));
+        assert_eq!(result.probabilities, vec![0.3; 5]);
+
+        let strategy = self::strategy(game_type, &[]);
+        let first_action = Fallback::Default(DefaultPolicy::FirstAction);
+        let result = lookup(&strategy, &[first_action]).unwrap();
+        assert_eq!(result.fallback, Some(first_action));
+        assert_eq!(result.probabilities, vec![1., 0., 0., 0., 0.]);
+        assert!(matches!(
+            lookup(
+                &strategy,
+                &[Fallback::Abstract, Fallback::NeighbouringTantos(1)]
+            ),
+            Err(SolverError::MissingInfoSet(_))
+        ));
+    }
+
+    #[test]
+    fn game_actions_with_fallback() {
+        let manos = manos(["RR11", "RC51", "R751", "C741"]);
+        let game = MusGameTwoHands::new([0, 0], false, 0)
+            .with_hands(core::array::from_fn(|i| manos[i].clone()));
+        let strategy = self::strategy(GameType::MusGameTwoHands, &[("0:0,RR11,R751,M", vec![1.])]);
+        let result = strategy.game_actions_with_fallback(&game, &[]).unwrap();
+        assert_eq!(
+            result,
+            strategy
+                .actions_with_fallback(&manos, [0, 0], &[], &[])
+                .unwrap()
+        );
+
+        // Las alternativas que reconstruyen la partida se saltan.
+        let game = MusGameTwoHands::new([1, 1], false, 0)
+            .with_hands(core::array::from_fn(|i| manos[i].clone()));
+        let result = strategy
+            .game_actions_with_fallback(&game, &Fallback::DEFAULT_CHAIN)
+            .unwrap();
+        assert_eq!(
+            result.fallback,
+            Some(Fallback::Default(DefaultPolicy::Uniform))
+        );
+        assert!(matches!(
+            strategy.game_actions_with_fallback(&game, &[Fallback::NeighbouringTantos(1)]),
+            Err(SolverError::MissingInfoSet(_))
+        ));
+    }
+}
@@ -1,14 +1,91 @@
-use std::{fmt::Debug, rc::Rc};
+use std::{
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 use indicatif::{ProgressBar, ProgressStyle};
+use rand::{SeedableRng, rngs::StdRng};
 
 use crate::{
-    Cfr, CfrMethod, Game,
-    solver::{GameConfig, GameType, LanceGame, MusGame, MusGameTwoHands, MusGameTwoPlayers},
+    Cfr, CfrMethod, Game, RegretPruning, SampledGame, StopReason, StopTracker, StoppingCriteria,
+    solver::{
+        Checkpoint, GameConfig, GameType, LanceGame, LanceGameTwoHands, MusGame, MusGameTwoHands,
+        MusGameTwoPlayers, UtilityTable, UtilityTarget,
+    },
 };
 
 pub struct Trainer {
     tantos: [u8; 2],
+    checkpoint_config: Option<CheckpointConfig>,
+    resume_from: Option<Checkpoint>,
+    init_from: Option<Cfr>,
+    convergence_log: Option<PathBuf>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Resultado de un entrenamiento.
+pub struct TrainingResult {
+    pub cfr: Cfr,
+    /// Última explotabilidad estimada del último marcador entrenado, si se ha evaluado.
+    pub exploitability: Option<f64>,
+    /// Motivo por el que terminó el entrenamiento del último marcador entrenado, o
+    /// `StopReason::TimeLimit` si se agotó el tiempo total.
+    pub stop_reason: StopReason,
+    /// Motivo por el que terminó el entrenamiento de cada marcador, en el orden en que se
+    /// entrenaron.
+    pub stop_reasons: Vec<([u8; 2], StopReason)>,
+    /// Utilidad esperada de cada marcador entrenado. Los marcadores sin entrenar conservan la
+    /// diferencia de tantos.
+    pub utility_table: Vec<Vec<f64>>,
+}
+
+/// Fila del registro de convergencia.
+#[derive(Debug, serde::Serialize)]
+struct ConvergenceRecord {
+    tantos: String,
+    iteration: usize,
+    elapsed_secs: f64,
+    nodes: usize,
+    /// Explotabilidad calculada sobre repartos muestreados. Está sesgada hacia arriba, así que
+    /// sirve para comparar puntos del entrenamiento pero no es la explotabilidad real. Ver
+    /// `SampledGame`.
+    exploitability_upper_biased: f64,
+}
+
+/// Configuración del guardado periódico de checkpoints durante el entrenamiento.
+#[derive(Debug, Clone)]
+pub struct CheckpointConfig {
+    pub path: PathBuf,
+    /// Se guarda un checkpoint cada este número de iteraciones.
+    pub every_iterations: Option<usize>,
+    /// Se guarda un checkpoint cada vez que transcurre este tiempo.
+    pub every: Option<Duration>,
+}
+
+/// Semilla del entrenamiento del marcador `tantos`. Al reanudar desde un checkpoint no se vuelve
+/// a sembrar el marcador en curso, sino que se restaura el estado guardado del generador.
+fn state_seed(seed: u64, tantos: [u8; 2]) -> u64 {
+    seed ^ (u64::from(tantos[0]) << 56) ^ (u64::from(tantos[1]) << 48)
+}
+
+/// Máximo de iteraciones entre dos comprobaciones del tiempo transcurrido.
+const CHUNK_SIZE: usize = 1000;
+
+/// Última iteración del siguiente tramo de entrenamiento que empieza en `iteration`. Los tramos
+/// terminan en múltiplos de `every` para guardar checkpoints y evaluar en esas iteraciones.
+fn chunk_end(iteration: usize, iterations: usize, every: &[Option<usize>]) -> usize {
+    every
+        .iter()
+        .flatten()
+        .map(|every| (iteration / every + 1) * every)
+        .fold(iteration.saturating_add(CHUNK_SIZE), usize::min)
+        .min(iterations)
 }
 
 #[derive(
@@ -22,49 +99,277 @@ pub struct Trainer {
 )]
 pub struct TrainerConfig {
     pub method: CfrMethod,
+    /// Número máximo de iteraciones por marcador. `usize::MAX` si solo se termina por los
+    /// criterios de parada.
     pub iterations: usize,
+    /// Número de hilos de entrenamiento. Solo chance sampling y external sampling se entrenan en
+    /// paralelo.
+    pub threads: usize,
+    /// Se estima la explotabilidad de la estrategia media cada este número de iteraciones y al
+    /// terminar cada marcador. Sin este valor no se evalúa.
+    pub eval_every: Option<usize>,
+    /// Número de repartos sobre los que se calcula la mejor respuesta al estimar la
+    /// explotabilidad. La estimación está sesgada hacia arriba, más cuantos menos repartos. Ver
+    /// `SampledGame`. También es el número de repartos con el que se estima la utilidad esperada
+    /// de `LanceGameTwoHands`.
+    pub eval_samples: usize,
+    /// Criterios de parada del entrenamiento de cada marcador.
+    pub stopping: StoppingCriteria,
+    /// Tiempo máximo del entrenamiento completo. Al agotarse no se entrenan más marcadores.
+    pub total_time_limit: Option<Duration>,
+    /// Semilla de los generadores aleatorios. Dos entrenamientos con la misma semilla y un solo
+    /// hilo producen la misma estrategia.
+    pub seed: u64,
+    /// Poda de las acciones con regret muy negativo. Ver `RegretPruning`.
+    pub pruning: Option<RegretPruning>,
+    /// Iteraciones de cada bloque de descuento de DCFR y Linear CFR. Ver
+    /// `Cfr::with_discount_interval`.
+    pub discount_interval: usize,
+}
+
+impl TrainerConfig {
+    /// Iteraciones por bloque de descuento por defecto. Cada descuento recorre todos los nodos,
+    /// así que descontar en cada iteración ralentiza mucho las partidas completas.
+    pub const DEFAULT_DISCOUNT_INTERVAL: usize = 100;
+}
+
+impl Default for TrainerConfig {
+    /// Mil iteraciones de chance sampling por marcador en un hilo, con la semilla 0 y sin
+    /// evaluación ni poda, como la línea de comandos con `--iter 1000 --seed 0`.
+    fn default() -> Self {
+        Self {
+            method: CfrMethod::ChanceSampling,
+            iterations: 1000,
+            threads: 1,
+            eval_every: None,
+            eval_samples: 1000,
+            stopping: Default::default(),
+            total_time_limit: None,
+            seed: 0,
+            pruning: None,
+            discount_interval: Self::DEFAULT_DISCOUNT_INTERVAL,
+        }
+    }
 }
 
 impl Trainer {
     pub fn new() -> Self {
-        Self { tantos: [0; 2] }
+        Self {
+            tantos: [0; 2],
+            checkpoint_config: None,
+            resume_from: None,
+            init_from: None,
+            convergence_log: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
     }
 
     pub fn with_tantos(self, tantos: [u8; 2]) -> Self {
-        Self { tantos }
+        Self { tantos, ..self }
+    }
+
+    pub fn with_checkpoints(self, checkpoint_config: CheckpointConfig) -> Self {
+        Self {
+            checkpoint_config: Some(checkpoint_config),
+            ..self
+        }
+    }
+
+    /// Añade al fichero CSV `path` una fila por cada estimación de la explotabilidad.
+    pub fn with_convergence_log(self, path: PathBuf) -> Self {
+        Self {
+            convergence_log: Some(path),
+            ..self
+        }
+    }
+
+    /// Al activarse `cancel` se termina la iteración en curso, se abandonan la evaluación y el
+    /// cálculo de la utilidad esperada si están en marcha, se guarda un checkpoint si están
+    /// configurados y `Trainer::train` devuelve la estrategia parcial con
+    /// `StopReason::Interrupted`.
+    pub fn with_cancel_flag(self, cancel: Arc<AtomicBool>) -> Self {
+        Self { cancel, ..self }
+    }
+
+    /// Continúa el entrenamiento guardado en el checkpoint. El marcador inicial es el del
+    /// entrenamiento original.
+    pub fn resume_from(self, checkpoint: Checkpoint) -> Self {
+        Self {
+            tantos: checkpoint.target,
+            resume_from: Some(checkpoint),
+            ..self
+        }
+    }
+
+    /// Empieza el entrenamiento desde `cfr` en lugar de desde la estrategia uniforme, por ejemplo
+    /// con un `Cfr::from_strategy`. No tiene efecto al reanudar desde un checkpoint.
+    pub fn init_from(self, cfr: Cfr) -> Self {
+        Self {
+            init_from: Some(cfr),
+            ..self
+        }
     }
 
-    pub fn train(&self, game_config: &GameConfig, trainer_config: &TrainerConfig) -> Cfr {
-        let mut cfr = Cfr::new();
-        let mut utility_table = MusGame::default_utility_table();
+    pub fn train(
+        &self,
+        game_config: &GameConfig,
+        trainer_config: &TrainerConfig,
+    ) -> TrainingResult {
+        let start = Instant::now();
+        let mut convergence_log =
+            self.convergence_log
+                .as_ref()
+                .and_then(|path| match Self::open_convergence_log(path) {
+                    Ok(writer) => Some(writer),
+                    Err(err) => {
+                        println!("Error abriendo el registro de convergencia: {err}");
+                        None
+                    }
+                });
+        let mut exploitability = None;
+        let mut stop_reason = StopReason::Iterations;
+        let total_tracker = StopTracker::new(&StoppingCriteria {
+            time_limit: trainer_config.total_time_limit,
+            ..Default::default()
+        });
+        let (mut cfr, mut utility_table, mut stop_reasons, mut resume_position) =
+            match &self.resume_from {
+                Some(checkpoint) => (
+                    checkpoint.cfr(),
+                    checkpoint.utility_table.clone(),
+                    checkpoint.stop_reasons.clone(),
+                    Some((checkpoint.tantos, checkpoint.iteration)),
+                ),
+                None => (
+                    self.init_from.clone().unwrap_or_default(),
+                    UtilityTable::diferencia_tantos(game_config.max_tantos),
+                    Vec::new(),
+                    None,
+                ),
+            };
+        cfr.set_pruning(trainer_config.pruning);
+        cfr.set_discount_interval(trainer_config.discount_interval);
+        let bet_abstraction = Arc::new(game_config.bet_abstraction.clone());
+        let discard_abstraction = Arc::new(game_config.discard_abstraction.clone());
         let target = self.tantos;
-        (0..40).rev().for_each(|t1| {
-            for t2 in 0..(40 - t1) {
-                let tantos = [t1 + t2, 39 - t2];
+        let max_tantos = game_config.max_tantos;
+        'marcadores: for t1 in (0..max_tantos).rev() {
+            for t2 in 0..(max_tantos - t1) {
+                let tantos = [t1 + t2, max_tantos - 1 - t2];
                 if tantos[0] < target[0] || tantos[1] < target[1] {
                     continue;
                 }
+                // Al reanudar se saltan los marcadores ya entrenados.
+                let first_iteration = match resume_position {
+                    Some((resume_tantos, iteration)) if resume_tantos == tantos => {
+                        resume_position = None;
+                        iteration
+                    }
+                    Some(_) => continue,
+                    None => {
+                        cfr.set_seed(state_seed(trainer_config.seed, tantos));
+                        0
+                    }
+                };
+                let save_checkpoint = |cfr: &mut Cfr, iteration: usize| {
+                    self.save_checkpoint(
+                        cfr,
+                        game_config,
+                        trainer_config,
+                        (tantos, iteration),
+                        &utility_table,
+                        &stop_reasons,
+                    )
+                };
+                let mut tracker = StopTracker::new(&trainer_config.stopping);
+                let check_stop = |cfr: &Cfr, iteration: usize, value: Option<f64>| {
+                    let mut reason = None;
+                    if let Some(value) = value {
+                        exploitability = Some(value);
+                        reason = tracker.record_exploitability(value);
+                        Self::log_convergence(
+                            convergence_log.as_mut(),
+                            ConvergenceRecord {
+                                tantos: format!("{}:{}", tantos[0], tantos[1]),
+                                iteration,
+                                elapsed_secs: start.elapsed().as_secs_f64(),
+                                nodes: cfr.num_nodes(),
+                                exploitability_upper_biased: value,
+                            },
+                        );
+                    }
+                    reason
+                        .or_else(|| tracker.check_time())
+                        .or_else(|| total_tracker.check_time())
+                };
                 let expected_utility = match game_config.game_type {
                     GameType::LanceGame(lance) => {
                         let mut lance_game =
-                            LanceGame::new(lance, tantos, game_config.abstract_game);
-                        train_game(&mut cfr, &mut lance_game, trainer_config);
-                        let expected_utility = cfr.expected_utility(&lance_game)[0];
-                        utility_table[tantos[0] as usize][tantos[1] as usize] = expected_utility;
-                        expected_utility
+                            LanceGame::new(lance, tantos, game_config.abstract_game)
+                                .with_bet_abstraction(bet_abstraction.clone())
+                                .with_max_tantos(max_tantos)
+                                .with_variante(game_config.variante);
+                        stop_reason = self.train_game(
+                            &mut cfr,
+                            &mut lance_game,
+                            trainer_config,
+                            first_iteration,
+                            save_checkpoint,
+                            check_stop,
+                        );
+                        self.expected_utility(&cfr, &lance_game, stop_reason)
+                    }
+                    GameType::LanceGameTwoHands(lance) => {
+                        let mut lance_game =
+                            LanceGameTwoHands::new(lance, tantos, game_config.abstract_game)
+                                .with_bet_abstraction(bet_abstraction.clone())
+                                .with_max_tantos(max_tantos)
+                                .with_variante(game_config.variante);
+                        stop_reason = self.train_game(
+                            &mut cfr,
+                            &mut lance_game,
+                            trainer_config,
+                            first_iteration,
+                            save_checkpoint,
+                            check_stop,
+                        );
+                        // Recorrer todos los repartos de las dos manos no es factible, así que la
+                        // utilidad esperada se estima con una muestra de repartos.
+                        let mut utility_rng =
+                            StdRng::seed_from_u64(state_seed(trainer_config.seed, tantos));
+                        let sampled_game = SampledGame::new(
+                            &lance_game,
+                            trainer_config.eval_samples,
+                            &mut utility_rng,
+                        );
+                        self.expected_utility(&cfr, &sampled_game, stop_reason)
                     }
-                    GameType::LanceGameTwoHands(_) => todo!(),
                     GameType::MusGame => {
                         let mut mus_game = MusGame::new(
                             tantos,
                             game_config.abstract_game,
                             game_config.max_mus_rounds,
                         )
-                        .with_utility_table(Rc::new(utility_table));
-                        train_game(&mut cfr, &mut mus_game, trainer_config);
-                        let expected_utility = cfr.expected_utility(&mus_game)[0];
-                        utility_table[tantos[0] as usize][tantos[1] as usize] = expected_utility;
-                        expected_utility
+                        .with_utility_table(Arc::new(UtilityTable::new(
+                            utility_table.clone(),
+                            &game_config.utility_target,
+                        )))
+                        .with_max_tantos(max_tantos)
+                        .with_variante(game_config.variante)
+                        .with_bet_abstraction(bet_abstraction.clone())
+                        .with_discard_abstraction(discard_abstraction.clone());
+                        if let Some(signals) = &game_config.signals {
+                            mus_game = mus_game.with_signals(Arc::new(signals.clone()));
+                        }
+                        stop_reason = self.train_game(
+                            &mut cfr,
+                            &mut mus_game,
+                            trainer_config,
+                            first_iteration,
+                            save_checkpoint,
+                            check_stop,
+                        );
+                        self.expected_utility(&cfr, &mus_game, stop_reason)
                     }
                     GameType::MusGameTwoHands => {
                         let mut mus_game = MusGameTwoHands::new(
@@ -72,11 +377,23 @@ impl Trainer {
                             game_config.abstract_game,
                             game_config.max_mus_rounds,
                         )
-                        .with_utility_table(Rc::new(utility_table));
-                        train_game(&mut cfr, &mut mus_game, trainer_config);
-                        let expected_utility = cfr.expected_utility(&mus_game)[0];
-                        utility_table[tantos[0] as usize][tantos[1] as usize] = expected_utility;
-                        expected_utility
+                        .with_utility_table(Arc::new(UtilityTable::new(
+                            utility_table.clone(),
+                            &game_config.utility_target,
+                        )))
+                        .with_max_tantos(max_tantos)
+                        .with_variante(game_config.variante)
+                        .with_bet_abstraction(bet_abstraction.clone())
+                        .with_discard_abstraction(discard_abstraction.clone());
+                        stop_reason = self.train_game(
+                            &mut cfr,
+                            &mut mus_game,
+                            trainer_config,
+                            first_iteration,
+                            save_checkpoint,
+                            check_stop,
+                        );
+                        self.expected_utility(&cfr, &mus_game, stop_reason)
                     }
                     GameType::MusGameTwoPlayers => {
                         let mut mus_game = MusGameTwoPlayers::new(
@@ -84,22 +401,248 @@ impl Trainer {
                             game_config.abstract_game,
                             game_config.max_mus_rounds,
                         )
-                        .with_utility_table(Rc::new(utility_table));
-                        train_game(&mut cfr, &mut mus_game, trainer_config);
-                        let expected_utility = cfr.expected_utility(&mus_game)[0];
-                        utility_table[tantos[0] as usize][tantos[1] as usize] = expected_utility;
-                        expected_utility
+                        .with_utility_table(Arc::new(UtilityTable::new(
+                            utility_table.clone(),
+                            &game_config.utility_target,
+                        )))
+                        .with_max_tantos(max_tantos)
+                        .with_variante(game_config.variante)
+                        .with_bet_abstraction(bet_abstraction.clone())
+                        .with_discard_abstraction(discard_abstraction.clone());
+                        stop_reason = self.train_game(
+                            &mut cfr,
+                            &mut mus_game,
+                            trainer_config,
+                            first_iteration,
+                            save_checkpoint,
+                            check_stop,
+                        );
+                        self.expected_utility(&cfr, &mus_game, stop_reason)
+                    }
+                };
+                // Si se interrumpe al calcular la utilidad esperada, el marcador ya está entrenado:
+                // el checkpoint se guarda al final del marcador, para calcularla al reanudar.
+                let Some(expected_utility) = expected_utility else {
+                    if stop_reason != StopReason::Interrupted {
+                        save_checkpoint(&mut cfr, trainer_config.iterations);
+                        stop_reason = StopReason::Interrupted;
                     }
+                    stop_reasons.push((tantos, stop_reason));
+                    break 'marcadores;
                 };
+                stop_reasons.push((tantos, stop_reason));
+                utility_table[tantos[0] as usize][tantos[1] as usize] = expected_utility;
                 println!("Finished training.");
                 println!(
                     "Expected utility {}-{}: {}",
                     tantos[0], tantos[1], expected_utility
                 );
+                if matches!(game_config.utility_target, UtilityTarget::Encuentro { .. })
+                    && !matches!(
+                        game_config.game_type,
+                        GameType::LanceGame(_) | GameType::LanceGameTwoHands(_)
+                    )
+                {
+                    println!(
+                        "Probabilidad de ganar el encuentro: {}",
+                        (expected_utility + 1.) / 2.
+                    );
+                }
                 println!();
+                if total_tracker.check_time().is_some() {
+                    println!("Se ha agotado el tiempo total de entrenamiento.");
+                    stop_reason = StopReason::TimeLimit;
+                    break 'marcadores;
+                }
             }
-        });
-        cfr
+        }
+        TrainingResult {
+            cfr,
+            exploitability,
+            stop_reason,
+            stop_reasons,
+            utility_table,
+        }
+    }
+
+    /// Utilidad esperada del jugador 0 en `game` tras entrenar el marcador. No se calcula si el
+    /// entrenamiento se ha interrumpido, y se abandona si se interrumpe durante el cálculo.
+    fn expected_utility<G>(&self, cfr: &Cfr, game: &G, stop_reason: StopReason) -> Option<f64>
+    where
+        G: Game + Clone,
+        G::Action: Eq + Copy,
+    {
+        if stop_reason == StopReason::Interrupted {
+            return None;
+        }
+        cfr.expected_utility_cancellable(game, &self.cancel)
+            .map(|utility| utility[0])
+    }
+
+    fn log_convergence(writer: Option<&mut csv::Writer<File>>, record: ConvergenceRecord) {
+        let Some(writer) = writer else {
+            return;
+        };
+        if let Err(err) = writer.serialize(record).and_then(|_| Ok(writer.flush()?)) {
+            println!("Error escribiendo el registro de convergencia: {err}");
+        }
+    }
+
+    /// Entrena `game` desde `first_iteration`. Tras cada tramo de entrenamiento se llama a
+    /// `check_stop` con la explotabilidad, si se ha evaluado, para decidir si se termina antes de
+    /// completar las iteraciones.
+    fn train_game<G, F, S>(
+        &self,
+        cfr: &mut Cfr,
+        game: &mut G,
+        trainer_config: &TrainerConfig,
+        first_iteration: usize,
+        mut save_checkpoint: F,
+        mut check_stop: S,
+    ) -> StopReason
+    where
+        G: Game + Debug + Clone + Send,
+        G::Action: Eq + Copy,
+        F: FnMut(&mut Cfr, usize),
+        S: FnMut(&Cfr, usize, Option<f64>) -> Option<StopReason>,
+    {
+        let checkpoint_config = self.checkpoint_config.as_ref();
+        let now = Instant::now();
+        let pb = if trainer_config.iterations == usize::MAX {
+            let pb = ProgressBar::no_length();
+            pb.set_style(ProgressStyle::with_template("{spinner} {human_pos} {msg} ").unwrap());
+            pb
+        } else {
+            let pb = ProgressBar::new(trainer_config.iterations as u64);
+            pb.set_style(
+                ProgressStyle::with_template(
+                    "{wide_bar:40.cyan/blue} {human_pos}/{human_len} {msg} ",
+                )
+                .unwrap()
+                .progress_chars("##-"),
+            );
+            pb
+        };
+        pb.set_position(first_iteration as u64);
+        // Todas las evaluaciones de un marcador usan los mismos repartos para que sean comparables.
+        // Tienen su propio generador para no alterar la secuencia aleatoria del entrenamiento.
+        let mut eval_rng = StdRng::seed_from_u64(trainer_config.seed);
+        let mut eval_game = trainer_config
+            .eval_every
+            .map(|_| SampledGame::new(game, trainer_config.eval_samples, &mut eval_rng));
+        let mut last_checkpoint = (first_iteration, Instant::now());
+        let mut iteration = first_iteration;
+        let mut stop_reason = StopReason::Iterations;
+        while iteration < trainer_config.iterations {
+            let chunk_end = chunk_end(
+                iteration,
+                trainer_config.iterations,
+                &[
+                    checkpoint_config.and_then(|c| c.every_iterations),
+                    trainer_config.eval_every,
+                ],
+            );
+            let chunk_end = cfr.train_parallel(
+                game,
+                trainer_config.method,
+                iteration..chunk_end,
+                trainer_config.threads,
+                &self.cancel,
+                |i, util| {
+                    pb.inc(1);
+                    if i.is_multiple_of(1000) {
+                        pb.set_message(format!(
+                            "Utility: {}",
+                            util.iter()
+                                .map(|u| format!("{u:.5}"))
+                                .collect::<Vec<String>>()
+                                .join(" "),
+                        ));
+                    }
+                },
+            );
+            iteration = chunk_end;
+            if self.cancel.load(Ordering::Relaxed) {
+                save_checkpoint(cfr, iteration);
+                stop_reason = StopReason::Interrupted;
+                break;
+            }
+            if let Some(checkpoint_config) = checkpoint_config {
+                let iterations_due = checkpoint_config
+                    .every_iterations
+                    .is_some_and(|every| iteration - last_checkpoint.0 >= every);
+                let time_due = checkpoint_config
+                    .every
+                    .is_some_and(|every| last_checkpoint.1.elapsed() >= every);
+                if iterations_due || time_due {
+                    save_checkpoint(cfr, iteration);
+                    last_checkpoint = (iteration, Instant::now());
+                }
+            }
+            let mut exploitability = None;
+            if let (Some(eval_game), Some(every)) = (eval_game.as_mut(), trainer_config.eval_every)
+                && (iteration.is_multiple_of(every) || iteration == trainer_config.iterations)
+            {
+                eval_game.reset();
+                let Some(value) = cfr.exploitability_cancellable(eval_game, &self.cancel) else {
+                    save_checkpoint(cfr, iteration);
+                    stop_reason = StopReason::Interrupted;
+                    break;
+                };
+                pb.println(format!(
+                    "Exploitability (sampled, upper-biased): {value:.6}"
+                ));
+                exploitability = Some(value);
+            }
+            if let Some(reason) = check_stop(cfr, iteration, exploitability) {
+                stop_reason = reason;
+                break;
+            }
+        }
+        pb.abandon_with_message(format!("Stopped: {stop_reason}"));
+        let elapsed = now.elapsed();
+        println!("Elapsed: {elapsed:.2?}");
+        stop_reason
+    }
+
+    fn open_convergence_log(path: &Path) -> Result<csv::Writer<File>, std::io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        // Al reanudar un entrenamiento se continúa el mismo registro sin repetir la cabecera.
+        let has_headers = file.metadata()?.len() == 0;
+        Ok(csv::WriterBuilder::new()
+            .has_headers(has_headers)
+            .from_writer(file))
+    }
+
+    fn save_checkpoint(
+        &self,
+        cfr: &mut Cfr,
+        game_config: &GameConfig,
+        trainer_config: &TrainerConfig,
+        (tantos, iteration): ([u8; 2], usize),
+        utility_table: &[Vec<f64>],
+        stop_reasons: &[([u8; 2], StopReason)],
+    ) {
+        let Some(checkpoint_config) = &self.checkpoint_config else {
+            return;
+        };
+        let checkpoint = Checkpoint {
+            trainer_config: trainer_config.clone(),
+            game_config: game_config.clone(),
+            target: self.tantos,
+            tantos,
+            iteration,
+            utility_table: utility_table.to_vec(),
+            stop_reasons: stop_reasons.to_vec(),
+            nodes: Default::default(),
+            info_sets: Default::default(),
+            warm_start: Default::default(),
+            rng_state: cfr.rng_state(),
+        };
+        // Un checkpoint fallido no debe interrumpir un entrenamiento de días.
+        if let Err(err) = checkpoint.save(cfr, &checkpoint_config.path) {
+            println!("Error guardando checkpoint: {err}");
+        }
     }
 }
 
@@ -108,41 +651,3 @@ impl Default for Trainer {
         Self::new()
     }
 }
-
-fn train_game<G>(cfr: &mut Cfr, game: &mut G, trainer_config: &TrainerConfig) -> Vec<f64>
-where
-    G: Game + Debug + Clone,
-    G::Action: Eq + Copy,
-{
-    use std::time::Instant;
-
-    let now = Instant::now();
-    let pb = ProgressBar::new(trainer_config.iterations as u64);
-    pb.set_style(
-        ProgressStyle::with_template("{wide_bar:40.cyan/blue} {human_pos}/{human_len} {msg} ")
-            .unwrap()
-            .progress_chars("##-"),
-    );
-    let mut last_util = vec![0.; G::N_PLAYERS];
-    cfr.train(
-        game,
-        trainer_config.method,
-        trainer_config.iterations,
-        |i, util| {
-            pb.inc(1);
-            if i.is_multiple_of(1000) {
-                pb.set_message(format!(
-                    "Utility: {}",
-                    util.iter()
-                        .map(|u| format!("{u:.5}"))
-                        .collect::<Vec<String>>()
-                        .join(" "),
-                ));
-                last_util.copy_from_slice(util);
-            }
-        },
-    );
-    let elapsed = now.elapsed();
-    println!("Elapsed: {elapsed:.2?}");
-    last_util
-}
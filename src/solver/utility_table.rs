@@ -0,0 +1,166 @@
+use std::str::FromStr;
+
+use crate::mus::{Encuentro, ReglasEncuentro};
+
+/// Qué se maximiza al entrenar las partidas completas: la diferencia de tantos al acabar el
+/// chico o la probabilidad de ganar un encuentro a vacas y chicos.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    rkyv::Archive,
+)]
+pub enum UtilityTarget {
+    #[default]
+    Tantos,
+    /// Encuentro de `vacas` vacas ganadas, cada una de `chicos` chicos ganados.
+    Encuentro { chicos: u8, vacas: u8 },
+}
+
+impl UtilityTarget {
+    /// Utilidad de ganar un chico a `max_tantos`, o None si se maximiza la diferencia de tantos.
+    ///
+    /// Como los chicos se juegan de forma independiente, la probabilidad de ganar el encuentro
+    /// es una función afín de la probabilidad de ganar el chico en curso, y las estrategias que
+    /// maximizan una y otra son las mismas. Ganar el chico vale lo que sube la probabilidad de
+    /// ganar el encuentro desde su inicio, y perderlo lo mismo en negativo, de modo que la
+    /// utilidad esperada es `2p - 1`, siendo p la probabilidad de ganar el encuentro si los
+    /// chicos siguientes se ganan con probabilidad 1/2.
+    pub fn valor_chico(&self, max_tantos: u8) -> Option<f64> {
+        match self {
+            UtilityTarget::Tantos => None,
+            UtilityTarget::Encuentro { chicos, vacas } => {
+                let encuentro = Encuentro::new(ReglasEncuentro {
+                    tantos: max_tantos,
+                    chicos: *chicos,
+                    vacas: *vacas,
+                })
+                .expect("Las reglas del encuentro deben ser válidas");
+                Some(encuentro.valor_chico())
+            }
+        }
+    }
+}
+
+impl FromStr for UtilityTarget {
+    type Err = String;
+
+    /// `tantos` o los chicos por vaca y las vacas del encuentro, como `2:2` para un encuentro al
+    /// mejor de tres vacas de tres chicos.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "tantos" {
+            return Ok(UtilityTarget::Tantos);
+        }
+        let error = || {
+            format!(
+                "Encuentro no válido ({s}). Debe ser tantos o los chicos por vaca y las vacas del encuentro, como 2:2."
+            )
+        };
+        let (chicos, vacas) = s.split_once(':').ok_or_else(error)?;
+        match (chicos.trim().parse(), vacas.trim().parse()) {
+            (Ok(chicos), Ok(vacas)) if chicos > 0 && vacas > 0 => {
+                Ok(UtilityTarget::Encuentro { chicos, vacas })
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Utilidades de las partidas completas al acabar la mano. El chico se gana al llegar a tantos
+/// como filas tiene la tabla.
+#[derive(Debug, Clone)]
+pub struct UtilityTable {
+    /// Utilidad esperada de la pareja mano al empezar una mano con cada marcador.
+    pub tantos: Vec<Vec<f64>>,
+    /// Utilidad de ganar el chico. Sin ella se usa la diferencia de tantos.
+    pub chico: Option<f64>,
+}
+
+impl UtilityTable {
+    pub fn new(tantos: Vec<Vec<f64>>, target: &UtilityTarget) -> Self {
+        Self {
+            chico: target.valor_chico(tantos.len() as u8),
+            tantos,
+        }
+    }
+
+    /// Tabla con la diferencia de tantos de cada marcador de un chico a `max_tantos`.
+    pub fn diferencia_tantos(max_tantos: u8) -> Vec<Vec<f64>> {
+        (0..max_tantos)
+            .map(|t1| (0..max_tantos).map(|t2| t1 as f64 - t2 as f64).collect())
+            .collect()
+    }
+
+    pub fn max_tantos(&self) -> u8 {
+        self.tantos.len() as u8
+    }
+
+    /// Utilidad de `player` al acabar una mano con el marcador `tantos`. Si el chico no ha
+    /// terminado, es la utilidad esperada de la mano siguiente, en la que la otra pareja es mano.
+    pub fn utility(&self, player: usize, tantos: &[u8; 2]) -> f64 {
+        let max_tantos = self.max_tantos();
+        if tantos[0] == max_tantos || tantos[1] == max_tantos {
+            let payoff = match self.chico {
+                Some(valor) if tantos[0] == max_tantos => [valor, -valor],
+                Some(valor) => [-valor, valor],
+                None => [
+                    tantos[0] as f64 - tantos[1] as f64,
+                    tantos[1] as f64 - tantos[0] as f64,
+                ],
+            };
+            payoff[player % 2]
+        } else {
+            let expected_utility = self.tantos[tantos[1] as usize][tantos[0] as usize];
+            if player.is_multiple_of(2) {
+                -expected_utility
+            } else {
+                expected_utility
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utility_table() {
+        let tantos = UtilityTable::diferencia_tantos(40);
+        let diferencia = UtilityTable::new(tantos.clone(), &UtilityTarget::Tantos);
+        assert_eq!(diferencia.utility(0, &[40, 0]), 40.);
+        assert_eq!(diferencia.utility(1, &[40, 0]), -40.);
+        // La mano siguiente la empieza la otra pareja.
+        assert_eq!(diferencia.utility(0, &[30, 10]), 20.);
+
+        let target: UtilityTarget = "2:2".parse().unwrap();
+        assert_eq!(
+            target,
+            UtilityTarget::Encuentro {
+                chicos: 2,
+                vacas: 2
+            }
+        );
+        let encuentro = UtilityTable::new(tantos, &target);
+        assert_eq!(encuentro.utility(0, &[40, 0]), 0.25);
+        assert_eq!(encuentro.utility(3, &[0, 40]), 0.25);
+        assert_eq!(encuentro.utility(0, &[0, 40]), -0.25);
+        assert_eq!(encuentro.utility(0, &[30, 10]), 20.);
+
+        // A 30 tantos el chico acaba en 30.
+        let treinta = UtilityTable::new(UtilityTable::diferencia_tantos(30), &target);
+        assert_eq!(treinta.utility(0, &[30, 0]), 0.25);
+        assert_eq!(treinta.utility(0, &[20, 10]), 10.);
+
+        assert_eq!("tantos".parse(), Ok(UtilityTarget::Tantos));
+        assert!("0:2".parse::<UtilityTarget>().is_err());
+        assert!("2".parse::<UtilityTarget>().is_err());
+    }
+}